@@ -3,6 +3,7 @@ use crate::scope::Register;
 
 use std::{u16};
 use std::iter::FromIterator;
+use std::collections::HashMap;
 use resast::prelude::*;
 
 
@@ -11,13 +12,33 @@ pub type BytecodeResult = Result<Bytecode, CompilerError>;
 /// Labels are used as targets of jumps
 pub type Label = u32;
 
+/// The magic number starting [versioned](struct.Bytecode.html#method.encode_versioned) bytecode
+pub const BYTECODE_MAGIC: [u8; 4] = *b"JSYC";
+
+/// The version of the bytecode format. Must be increased on every incompatible change.
+///
+/// Version 2 encodes the key of [PropAccess](enum.Instruction.html#Instruction::PropAccess) as
+/// [property key](enum.OperandKind.html#OperandKind::PropertyKey), i.e. with a leading tag byte.
+pub const BYTECODE_VERSION: u16 = 2;
+
+/// Computes the CRC-32 (IEEE 802.3) checksum of some bytes
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 }
+        })
+    })
+}
+
 /// This trait is implemented by elements that are part of the final bytecode
 pub trait ToBytes {
-    fn to_bytes(&self) -> Vec<u8>;
+    /// Encodes the element. Fails if the element cannot be represented in the bytecode format,
+    /// e.g. a string longer than 65535 bytes.
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>>;
 
-    fn length_in_bytes(&self) -> usize {
-        self.to_bytes().len()
-    }
+    /// The length of the encoded element in bytes. This is computable even when the
+    /// encoding itself fails.
+    fn length_in_bytes(&self) -> usize;
 }
 
 /// Represents the basics instructions known to this compiler
@@ -28,19 +49,97 @@ pub enum Instruction
     LoadFloatNum,
     LoadLongNum,
     LoadNum,
+    LoadNum16,
     LoadArray,
-
+    /// Loads a constant from the [constant table](../constant_table/struct.ConstantTable.html) by its index
+    LoadConst,
+    /// Creates a new symbol with the description in the string operand, like ``Symbol(description)``
+    LoadSymbol,
+    /// Loads a well-known symbol like ``Symbol.iterator``, the operand is a [WellKnownSymbol](enum.WellKnownSymbol.html).
+    LoadWellKnownSymbol,
+
+    /// Reads a property of an object. The key is a [property key](enum.OperandKind.html#OperandKind::PropertyKey),
+    /// i.e. a register or a well-known symbol like in ``obj[Symbol.iterator]``. Symbols in a register are
+    /// used as keys as they are, like in JavaScript.
     PropAccess,
     CallFunc,
-    Eval,
+    /// Evaluates the string in a register as JavaScript code, like a direct ``eval(code)`` call.
+    ///
+    /// Free identifiers of the evaluated code are resolved through the frames pushed by
+    /// [EnterScope](enum.Instruction.html#Instruction::EnterScope), innermost first, before the global scope.
+    /// Values other than strings are returned unchanged.
+    EvalDirect,
+    /// Evaluates the string in a register as JavaScript code in the global scope, like an indirect
+    /// ``(0, eval)(code)`` call. The scope stack of the VM is ignored.
+    EvalIndirect,
+    CheckArity,
+    DefaultParam,
+    CollectRestArgs,
+    GetArrayLength,
+    /// Reads the length of a string, i.e. the number of its UTF-16 code units
+    GetLength,
+    /// Extracts a part of a string between a start and end index. The last operand is a
+    /// [StringSliceKind](enum.StringSliceKind.html), which selects the semantics of ``slice`` or ``substring``.
+    StringSlice,
+    /// Returns the first index of a substring like ``str.indexOf(needle, start)``, or -1.
+    ///
+    /// Without a start position, the last operand is register 255, which always holds 0.
+    StringIndexOf,
+    /// Returns the UTF-16 code unit at an index as string like ``str.charAt(index)``, or ``''`` if it is out of bounds
+    StringCharAt,
+    /// Returns the UTF-16 code unit at an index as number like ``str.charCodeAt(index)``, or ``NaN`` if it is out of bounds
+    StringCharCodeAt,
+    /// Reads an element by its index. Reading out of bounds results in ``undefined``, it does not throw.
+    ArrayGet,
+    /// Writes an element by its index. Writing out of bounds extends the array like in JavaScript.
+    ArraySet,
+    /// Sorts an array in place with a bytecode function as comparator, like ``array.sort(comparator)``.
+    ///
+    /// The operands are the destination of the sorted array, the array, the address of the comparator and
+    /// its argument registers like for ``BytecodeFuncCallback``. The VM calls the comparator directly
+    /// instead of through a JavaScript function.
+    ArraySort,
+    /// Throws an ``Error`` with the given message if the register holds a falsy value.
+    Assert,
+    /// Pushes a new frame onto the scope stack of the VM. Only emitted for blocks of functions that might call ``eval``.
+    ///
+    /// Every ``EnterScope`` is paired with a [LeaveScope](enum.Instruction.html#Instruction::LeaveScope), also when a
+    /// block is left by ``break``, ``continue`` or ``return``. If an exception leaves the block, ``Try`` restores
+    /// the scope stack before running the catch block.
+    EnterScope,
+    /// Pops the innermost frame off the scope stack of the VM.
+    LeaveScope,
+    /// Pushes the object in a register onto the scope stack of the VM, compiled from ``with(obj) { ... }``.
+    ///
+    /// It is paired with a [LeaveScope](enum.Instruction.html#Instruction::LeaveScope) like ``EnterScope``.
+    /// Code run by ``EvalDirect`` resolves identifiers through the object. Identifiers in the body of the
+    /// ``with`` statement are checked with [HasBinding](enum.Instruction.html#Instruction::HasBinding) instead.
+    WithStatement,
+    /// Checks whether the object of a ``with`` statement has a binding for a name, like ``name in Object(obj)``.
+    /// Names listed in the ``Symbol.unscopables`` property of the object have no binding.
+    HasBinding,
+    CreateRegExp,
+    RegExpTest,
+    RegExpExec,
     CallBytecodeFunc,
     ReturnBytecodeFunc,
     Copy,
+    /// Ends the execution. The VM jumps behind the end of the bytecode, so it can not be resumed.
     Exit,
+    /// Suspends the VM and returns control to the embedder, without tearing anything down.
+    ///
+    /// ``run()`` returns 1 instead of 0 and keeps all registers. The bytecode pointer points behind the
+    /// ``Halt``, so calling ``run()`` again resumes the execution there. A ``Halt`` is only resumable
+    /// from the top level of the script or from functions called by ``CallBytecodeFunc``, not from
+    /// within ``Try`` blocks or callbacks, since these run the VM nested.
+    ///
+    /// Unlike ``Exit``, ``Halt`` does not end the control flow, the following operation is reachable.
+    Halt,
     BytecodeFuncCallback,
     PropertySet,
     Try,
     Throw,
+    Nop,
 
     JumpCond,
     Jump,
@@ -55,10 +154,270 @@ pub enum Instruction
     CompLessThanEqual,
     CompGreaterThanEqual,
 
+    ToNumber,
+    ToString,
+    ToBoolean,
+    /// Converts a value like a call of the builtin ``String(value)``. Unlike concatenation, this also converts symbols.
+    TypeCoerceToString,
+    /// Converts a value like a call of the builtin ``Number(value)``. Unlike ``+value``, this also converts BigInts.
+    TypeCoerceToNumber,
+    /// Parses a string like the builtin ``parseInt(string, radix)``. The radix is an immediate, 0 means
+    /// that it is derived from the string like when ``parseInt`` is called without a radix.
+    ParseInt,
+    /// Parses a string like the builtin ``parseFloat(string)``.
+    ParseFloat,
+    /// Orders memory accesses to ``SharedArrayBuffer``s, the operand is a [MemoryOrdering](enum.MemoryOrdering.html).
+    ///
+    /// The VM must neither move reads of shared memory before an acquire barrier nor writes after a
+    /// release barrier. A sequentially consistent barrier does both. VMs which execute operations in
+    /// order and access shared memory only through ``Atomics`` fulfill this without doing anything.
+    MemoryBarrier,
+
+    /// Rounds down like ``Math.floor(x)``
+    MathFloor,
+    /// Rounds up like ``Math.ceil(x)``
+    MathCeil,
+    /// Rounds to the nearest integer like ``Math.round(x)``, i.e. halves are rounded up
+    MathRound,
+    /// Returns the absolute value like ``Math.abs(x)``
+    MathAbs,
+    /// Returns the square root like ``Math.sqrt(x)``
+    MathSqrt,
+    /// Raises the first operand to the power of the second like ``Math.pow(x, y)``
+    MathPow,
+    /// Returns the smallest of the registers array like ``Math.min(...)``, ``Infinity`` if it is empty
+    MathMin,
+    /// Returns the largest of the registers array like ``Math.max(...)``, ``-Infinity`` if it is empty
+    MathMax,
+    /// Returns a pseudo-random number like ``Math.random()``
+    ///
+    /// The VM takes the numbers from its random source, which must return numbers in the range [0, 1).
+    /// The VM never seeds the source itself. It uses ``Math.random`` unless it is replaced, e.g. by a seeded
+    /// generator to get reproducible runs.
+    MathRandom,
+    /// Returns the current time in milliseconds since the epoch like ``Date.now()``
+    ///
+    /// The VM must provide timestamps that never decrease, even if the system clock is set back.
+    DateNow,
+    /// Prints the values of the registers array like ``console.log(...)``, only emitted for debug output
+    ConsoleLog,
+    /// Serializes a value like ``JSON.stringify(value, replacer, indent)``
+    ///
+    /// Without replacer or indent, the register holding 0 is passed, which is ignored as either.
+    JSONStringify,
+    /// Parses a string like ``JSON.parse(string, reviver)``
+    ///
+    /// Without reviver, the register holding 0 is passed, which is ignored as reviver.
+    JSONParse,
+    /// Copies the properties of the sources in the registers array to the target like
+    /// ``Object.assign(target, ...sources)`` and returns the target
+    ///
+    /// Only own enumerable properties are copied, later sources overwrite earlier ones.
+    ObjectAssign,
+    /// Returns the names of the own enumerable string-keyed properties like ``Object.keys(obj)``
+    ObjectKeys,
+    /// Returns the values of the own enumerable string-keyed properties like ``Object.values(obj)``
+    ObjectValues,
+    /// Returns ``[name, value]`` pairs of the own enumerable string-keyed properties like ``Object.entries(obj)``
+    ObjectEntries,
+    /// Freezes an object like ``Object.freeze(obj)`` and returns it
+    ///
+    /// The VM runs in strict mode, so setting a property of a frozen object throws a ``TypeError``.
+    ObjectFreeze,
+    /// Checks whether an object is frozen like ``Object.isFrozen(obj)``
+    ObjectIsFrozen,
+    /// Creates an array from an iterable or array-like object like ``Array.from(iterable, mapFn)``
+    ///
+    /// Without map function, the register 255 is passed. The VM checks the register itself, not its
+    /// value, since its value 0 is no function.
+    ArrayFrom,
+    /// Checks whether a value is an array like ``Array.isArray(x)``, array-like objects are no arrays
+    ArrayIsArray,
+    /// Waits for all promises of an iterable like ``Promise.all(promises)``
+    PromiseAll,
+    /// Settles like the first settled promise of an iterable like ``Promise.race(promises)``
+    PromiseRace,
+    /// Fulfills like the first fulfilled promise of an iterable like ``Promise.any(promises)``
+    ///
+    /// ``Promise.any`` was added in ES2021, older engines running the VM lack it. Once the compiler has an
+    /// option for the targeted language version, this instruction should only be emitted for ES2021 or later.
+    PromiseAny,
+    /// Creates a set like ``new Set(values)``
+    ///
+    /// Without initial values, the register 255 is passed. The VM checks the register itself, not its
+    /// value, since its value 0 is not iterable.
+    NewSet,
+    /// Creates a map like ``new Map(entries)``, without initial entries, the register 255 is passed like for
+    /// [NewSet](#Instruction::NewSet)
+    NewMap,
+    /// Creates a weak map like ``new WeakMap(entries)``, without initial entries, the register 255 is passed like for
+    /// [NewSet](#Instruction::NewSet)
+    NewWeakMap,
+    /// Adds a value to a set like ``set.add(value)`` and returns the set
+    SetAdd,
+    /// Checks whether a set contains a value like ``set.has(value)``
+    SetHas,
+    /// Removes a value from a set like ``set.delete(value)``, returns whether the value was contained
+    SetDelete,
+    /// Returns the value of a key of a map like ``map.get(key)``, ``undefined`` if there is none
+    MapGet,
+    /// Sets the value of a key of a map like ``map.set(key, value)`` and returns the map
+    MapSet,
+    /// Checks whether a map contains a key like ``map.has(key)``
+    MapHas,
+    /// Returns the value of a key of a map or the default if it is ``undefined`` or ``null`` like
+    /// ``map.get(key) ?? default``
+    ///
+    /// The parser does not support the ``??`` operator, thus the compiler does not emit this instruction yet.
+    MapGetOrDefault,
+    /// Returns the value of a key of a weak map like ``weakMap.get(key)``
+    ///
+    /// Unlike ``WeakMap.prototype.get``, this throws a ``TypeError`` if the key is no object.
+    WeakMapGet,
+    /// Sets the value of a key of a weak map like ``weakMap.set(key, value)`` and returns the weak map,
+    /// throws a ``TypeError`` if the key is no object
+    WeakMapSet,
+    /// Calls a bytecode function in tail position like ``return f(a, b)``, reusing the call frame
+    ///
+    /// Operands are the function, the argument list like ``CallBytecodeFunc`` and the registers the
+    /// calling function declares as modified like ``ReturnBytecodeFunc``. Since the callee returns to
+    /// the caller of the calling function directly, there is no return register and mutual recursion
+    /// does not grow the register backups.
+    TailCall,
+    /// Loads the global object like ``globalThis``
+    LoadGlobalThis,
+    /// Copies the own enumerable string-keyed properties of an object to a target like ``{ ...source }`` and
+    /// returns the target
+    ///
+    /// Operands are the destination, the target and the source. Like in a spread element, a source that is
+    /// ``null`` or ``undefined`` is ignored. If the target is the register holding 0, a new object is created.
+    ObjectSpread,
+    /// Copies a part of an array like ``arr.slice(start, end)``, negative indices count from the end
+    ///
+    /// Operands are the destination, the array, the start and the end. The register holding 0 stands for
+    /// the end of the array if it is passed as end.
+    ArraySlice,
+    /// Concatenates an array with the values of a registers array like ``arr.concat(...)``
+    ///
+    /// Arrays are spread into the result, all other values are appended as they are.
+    ArrayConcat,
+    /// Creates a new array from the results of a bytecode function called on each element of an array,
+    /// like ``array.map(callback)``
+    ///
+    /// The operands are like for ``ArraySort``. The callback is called with the element, its index and the array.
+    ArrayMap,
+    /// Creates a new array from the elements of an array for which a bytecode function returns a truthy value,
+    /// like ``array.filter(callback)``
+    ///
+    /// The operands are like for ``ArrayMap``.
+    ArrayFilter,
+    /// Reduces an array to a single value with a bytecode function, like ``array.reduce(callback, initial)``
+    ///
+    /// The operands are like for ``ArrayMap``, followed by the initial value. The register holding 0 stands
+    /// for a missing initial value, in which case reducing an empty array throws a ``TypeError``.
+    /// The callback is called with the accumulator, the element, its index and the array.
+    ArrayReduce,
+    /// Finds the first element of an array for which a bytecode function returns a truthy value,
+    /// like ``array.find(predicate)``, or ``undefined`` if there is none
+    ///
+    /// The operands are like for ``ArrayMap``.
+    ArrayFind,
+    /// Finds the index of the first element of an array for which a bytecode function returns a truthy value,
+    /// like ``array.findIndex(predicate)``, or -1 if there is none
+    ///
+    /// The operands are like for ``ArrayMap``.
+    ArrayFindIndex,
+    /// Checks whether a bytecode function returns a truthy value for all elements of an array,
+    /// like ``array.every(predicate)``
+    ///
+    /// The operands are like for ``ArrayMap``. The predicate is not called after the first falsy result.
+    ArrayEvery,
+    /// Checks whether a bytecode function returns a truthy value for any element of an array,
+    /// like ``array.some(predicate)``
+    ///
+    /// The operands are like for ``ArrayMap``. The predicate is not called after the first truthy result.
+    ArraySome,
+    /// Calls a bytecode function for each element of an array, like ``array.forEach(callback)``
+    ///
+    /// The operands are like for ``ArrayMap`` without the destination. Elements appended by the callback
+    /// are not visited, changed elements are visited with their new value.
+    ArrayForEach,
+    /// Flattens nested arrays up to a depth into a new array, like ``array.flat(depth)``
+    ///
+    /// The operands are the destination, the array and the depth. The register holding 0 stands for
+    /// an infinite depth.
+    ArrayFlat,
+    /// Maps an array with a bytecode function and flattens the results by one level, like ``array.flatMap(callback)``
+    ///
+    /// The operands are like for ``ArrayMap``.
+    ArrayFlatMap,
+    /// Splits a string into an array like ``str.split(separator, limit)``, the separator is a string or a regular expression
+    ///
+    /// The operands are the destination, the string, the separator and the limit. The register holding 0
+    /// stands for no limit.
+    StringSplit,
+    /// Repeats a string like ``str.repeat(count)``
+    ///
+    /// The count is truncated to an integer. A negative or infinite count throws a ``RangeError``.
+    StringRepeat,
+    /// Pads the start of a string with a fill up to a length, like ``str.padStart(length, fill)``
+    ///
+    /// The operands are the destination, the string, the length and the fill. The register holding 0
+    /// stands for a space as fill. Strings which are not shorter than the length are not changed.
+    StringPadStart,
+    /// Pads the end of a string with a fill up to a length, like ``str.padEnd(length, fill)``
+    ///
+    /// The operands are like for ``StringPadStart``.
+    StringPadEnd,
+    /// Removes whitespace and line terminators from both ends of a string like ``str.trim()``
+    StringTrim,
+    /// Removes whitespace and line terminators from the start of a string like ``str.trimStart()``
+    StringTrimStart,
+    /// Removes whitespace and line terminators from the end of a string like ``str.trimEnd()``
+    StringTrimEnd,
+    /// Checks whether a string contains another string like ``str.includes(search, position)``
+    ///
+    /// The operands are the destination, the string, the searched string and the position. The register
+    /// holding 0 stands for the default position. Arrays are searched like ``array.includes(search, position)``.
+    StringIncludes,
+    /// Checks whether a string starts with another string like ``str.startsWith(search, position)``
+    ///
+    /// The operands are like for ``StringIncludes``.
+    StringStartsWith,
+    /// Checks whether a string ends with another string like ``str.endsWith(search, position)``
+    ///
+    /// The operands are like for ``StringIncludes``, the default position is the length of the string.
+    StringEndsWith,
+    /// Matches a string against a regular expression like ``str.match(regexp)``, the result is an array or ``null``
+    StringMatch,
+    /// Matches a string against a global regular expression like ``str.matchAll(regexp)``, the result is
+    /// an iterator of match arrays
+    ///
+    /// A regular expression without the global flag throws a ``TypeError``.
+    StringMatchAll,
+    /// Replaces the first match of a string or regular expression like ``str.replace(pattern, replacement)``
+    ///
+    /// The operands are the destination, the string, the pattern and the replacement. The replacement is
+    /// a string, which may reference capture groups like ``$1``, or a function called with the match and the groups.
+    StringReplace,
+    /// Replaces all matches of a string or global regular expression like ``str.replaceAll(pattern, replacement)``
+    ///
+    /// The operands are like for ``StringReplace``. A regular expression without the global flag throws a ``TypeError``.
+    StringReplaceAll,
+    /// Calls the ``apply`` method of a value with a this value and an array of arguments like ``func.apply(thisArg, args)``
+    ///
+    /// The operands are the destination, the receiver, the this value and the arguments. The receiver is not
+    /// known to be a function at compile time, thus the method is looked up at runtime like for any other call.
+    /// For functions, non-strict ones get the global this if the this value is ``null`` or ``undefined`` and
+    /// holes in the arguments are ``undefined``.
+    CallApply,
+
     Add,
     Minus,
     Mul,
     Div,
+    Concat,
+    AddImmediate,
     // LeftShift
     // RightShift
     // Mod,
@@ -69,21 +428,87 @@ pub enum Instruction
 }
 
 impl Instruction {
-    fn to_byte(&self) -> u8 {
+    /// Returns all instructions
+    pub fn variants() -> &'static [Instruction] {
+        &[
+            Instruction::LoadString, Instruction::LoadFloatNum, Instruction::LoadLongNum,
+            Instruction::LoadNum, Instruction::LoadNum16, Instruction::LoadArray, Instruction::LoadConst,
+            Instruction::LoadSymbol, Instruction::LoadWellKnownSymbol, Instruction::PropAccess, Instruction::CallFunc,
+            Instruction::EvalDirect, Instruction::EvalIndirect, Instruction::CheckArity, Instruction::DefaultParam,
+            Instruction::CollectRestArgs, Instruction::GetArrayLength, Instruction::GetLength, Instruction::StringSlice,
+            Instruction::StringIndexOf, Instruction::StringCharAt, Instruction::StringCharCodeAt, Instruction::ArrayGet,
+            Instruction::ArraySet, Instruction::ArraySort, Instruction::Assert, Instruction::EnterScope, Instruction::LeaveScope,
+            Instruction::WithStatement, Instruction::HasBinding, Instruction::CreateRegExp, Instruction::RegExpTest,
+            Instruction::RegExpExec, Instruction::CallBytecodeFunc, Instruction::ReturnBytecodeFunc,
+            Instruction::Copy, Instruction::Exit, Instruction::Halt, Instruction::BytecodeFuncCallback,
+            Instruction::PropertySet, Instruction::Try, Instruction::Throw, Instruction::Nop,
+            Instruction::JumpCond, Instruction::Jump, Instruction::JumpCondNeg, Instruction::CompEqual,
+            Instruction::CompNotEqual, Instruction::CompStrictEqual, Instruction::CompStrictNotEqual,
+            Instruction::CompLessThan, Instruction::CompGreaterThan, Instruction::CompLessThanEqual,
+            Instruction::CompGreaterThanEqual, Instruction::ToNumber, Instruction::ToString,
+            Instruction::ToBoolean, Instruction::TypeCoerceToString, Instruction::TypeCoerceToNumber,
+            Instruction::ParseInt, Instruction::ParseFloat, Instruction::MemoryBarrier,
+            Instruction::MathFloor, Instruction::MathCeil, Instruction::MathRound, Instruction::MathAbs,
+            Instruction::MathSqrt, Instruction::MathPow, Instruction::MathMin, Instruction::MathMax,
+            Instruction::MathRandom, Instruction::DateNow, Instruction::ConsoleLog,
+            Instruction::JSONStringify, Instruction::JSONParse, Instruction::ObjectAssign,
+            Instruction::ObjectKeys, Instruction::ObjectValues, Instruction::ObjectEntries,
+            Instruction::ObjectFreeze, Instruction::ObjectIsFrozen, Instruction::ArrayFrom, Instruction::ArrayIsArray,
+            Instruction::PromiseAll, Instruction::PromiseRace, Instruction::PromiseAny,
+            Instruction::NewSet, Instruction::NewMap, Instruction::NewWeakMap,
+            Instruction::SetAdd, Instruction::SetHas, Instruction::SetDelete,
+            Instruction::MapGet, Instruction::MapSet, Instruction::MapHas, Instruction::MapGetOrDefault,
+            Instruction::WeakMapGet, Instruction::WeakMapSet, Instruction::TailCall, Instruction::LoadGlobalThis,
+            Instruction::ObjectSpread, Instruction::ArraySlice, Instruction::ArrayConcat, Instruction::ArrayMap,
+            Instruction::ArrayFilter, Instruction::ArrayReduce, Instruction::ArrayFind, Instruction::ArrayFindIndex,
+            Instruction::ArrayEvery, Instruction::ArraySome, Instruction::ArrayForEach, Instruction::ArrayFlat,
+            Instruction::ArrayFlatMap, Instruction::StringSplit, Instruction::StringRepeat,
+            Instruction::StringPadStart, Instruction::StringPadEnd,
+            Instruction::StringTrim, Instruction::StringTrimStart, Instruction::StringTrimEnd,
+            Instruction::StringIncludes, Instruction::StringStartsWith, Instruction::StringEndsWith,
+            Instruction::StringMatch, Instruction::StringMatchAll, Instruction::StringReplace, Instruction::StringReplaceAll,
+            Instruction::CallApply,
+            Instruction::Add, Instruction::Minus, Instruction::Mul, Instruction::Div,
+            Instruction::Concat, Instruction::AddImmediate,
+        ]
+    }
+
+    pub(crate) fn to_byte(&self) -> u8 {
         match self {
             Instruction::LoadString => 1,
             Instruction::LoadNum => 2,
             Instruction::LoadFloatNum => 3,
             Instruction::LoadLongNum => 4,
             Instruction::LoadArray => 5,
+            Instruction::LoadNum16 => 6,
+            Instruction::LoadConst => 7,
+            Instruction::LoadSymbol => 120,
+            Instruction::LoadWellKnownSymbol => 121,
 
             Instruction::PropAccess => 10,
             Instruction::CallFunc => 11,
-            Instruction::Eval => 12,
+            Instruction::EvalDirect => 12,
+            Instruction::EvalIndirect => 63,
+            Instruction::CheckArity => 90,
+            Instruction::DefaultParam => 91,
+            Instruction::CollectRestArgs => 92,
+            Instruction::GetArrayLength => 93,
+            Instruction::GetLength => 123,
+            Instruction::StringSlice => 124,
+            Instruction::StringIndexOf => 125,
+            Instruction::StringCharAt => 126,
+            Instruction::StringCharCodeAt => 127,
+            Instruction::ArrayGet => 94,
+            Instruction::ArraySet => 95,
+            Instruction::Assert => 96,
+            Instruction::EnterScope => 97,
+            Instruction::LeaveScope => 98,
+            Instruction::WithStatement => 99,
             Instruction::CallBytecodeFunc => 13,
             Instruction::ReturnBytecodeFunc => 14,
             Instruction::Copy => 15,
             Instruction::Exit => 16,
+            Instruction::Halt => 117,
             Instruction::JumpCond => 17,
             Instruction::Jump => 18,
             Instruction::JumpCondNeg => 19,
@@ -91,6 +516,7 @@ impl Instruction {
             Instruction::PropertySet => 21,
             Instruction::Try => 22,
             Instruction::Throw => 23,
+            Instruction::Nop => 24,
 
             Instruction::CompEqual => 50,
             Instruction::CompNotEqual => 51,
@@ -101,10 +527,385 @@ impl Instruction {
             Instruction::CompLessThanEqual => 56,
             Instruction::CompGreaterThanEqual => 57,
 
+            Instruction::ToNumber => 84,
+            Instruction::ToString => 85,
+            Instruction::ToBoolean => 86,
+            Instruction::TypeCoerceToString => 111,
+            Instruction::TypeCoerceToNumber => 112,
+            Instruction::ParseInt => 113,
+            Instruction::ParseFloat => 114,
+            Instruction::ArraySort => 119,
+            Instruction::MemoryBarrier => 118,
+            Instruction::MathFloor => 130,
+            Instruction::MathCeil => 131,
+            Instruction::MathRound => 132,
+            Instruction::MathAbs => 133,
+            Instruction::MathSqrt => 134,
+            Instruction::MathPow => 135,
+            Instruction::MathMin => 136,
+            Instruction::MathMax => 137,
+            Instruction::MathRandom => 138,
+            Instruction::DateNow => 139,
+            Instruction::ConsoleLog => 140,
+            Instruction::JSONStringify => 141,
+            Instruction::JSONParse => 142,
+            Instruction::ObjectAssign => 143,
+            Instruction::ObjectKeys => 144,
+            Instruction::ObjectValues => 145,
+            Instruction::ObjectEntries => 146,
+            Instruction::ObjectFreeze => 147,
+            Instruction::ObjectIsFrozen => 148,
+            Instruction::ArrayFrom => 149,
+            Instruction::ArrayIsArray => 150,
+            Instruction::PromiseAll => 151,
+            Instruction::PromiseRace => 152,
+            Instruction::PromiseAny => 153,
+            Instruction::NewSet => 154,
+            Instruction::NewMap => 155,
+            Instruction::SetAdd => 156,
+            Instruction::SetHas => 157,
+            Instruction::SetDelete => 158,
+            Instruction::MapGet => 159,
+            Instruction::MapSet => 160,
+            Instruction::MapHas => 161,
+            Instruction::MapGetOrDefault => 162,
+            Instruction::WeakMapGet => 163,
+            Instruction::WeakMapSet => 164,
+            Instruction::TailCall => 165,
+            Instruction::LoadGlobalThis => 166,
+            Instruction::ObjectSpread => 167,
+            Instruction::ArraySlice => 168,
+            Instruction::ArrayConcat => 169,
+            Instruction::ArrayMap => 170,
+            Instruction::ArrayFilter => 171,
+            Instruction::ArrayReduce => 172,
+            Instruction::ArrayFind => 173,
+            Instruction::ArrayFindIndex => 174,
+            Instruction::ArrayEvery => 175,
+            Instruction::ArraySome => 176,
+            Instruction::ArrayForEach => 177,
+            Instruction::ArrayFlat => 178,
+            Instruction::ArrayFlatMap => 179,
+            Instruction::StringSplit => 180,
+            Instruction::StringRepeat => 181,
+            Instruction::StringPadStart => 182,
+            Instruction::StringPadEnd => 183,
+            Instruction::StringTrim => 184,
+            Instruction::StringTrimStart => 185,
+            Instruction::StringTrimEnd => 186,
+            Instruction::StringIncludes => 187,
+            Instruction::StringStartsWith => 188,
+            Instruction::StringEndsWith => 189,
+            Instruction::StringMatch => 190,
+            Instruction::StringMatchAll => 191,
+            Instruction::StringReplace => 192,
+            Instruction::StringReplaceAll => 193,
+            Instruction::CallApply => 194,
+            Instruction::HasBinding => 195,
+            Instruction::NewWeakMap => 196,
+            Instruction::CreateRegExp => 87,
+            Instruction::RegExpTest => 88,
+            Instruction::RegExpExec => 89,
+
             Instruction::Add => 100,
             Instruction::Minus => 102,
             Instruction::Mul => 101,
             Instruction::Div => 103,
+            Instruction::Concat => 109,
+            Instruction::AddImmediate => 110,
+        }
+    }
+
+    /// Returns the instruction encoded by an opcode, if there is any
+    pub fn from_byte(byte: u8) -> Option<Instruction> {
+        match byte {
+            1 => Some(Instruction::LoadString),
+            2 => Some(Instruction::LoadNum),
+            3 => Some(Instruction::LoadFloatNum),
+            4 => Some(Instruction::LoadLongNum),
+            5 => Some(Instruction::LoadArray),
+            6 => Some(Instruction::LoadNum16),
+            7 => Some(Instruction::LoadConst),
+            120 => Some(Instruction::LoadSymbol),
+            121 => Some(Instruction::LoadWellKnownSymbol),
+            10 => Some(Instruction::PropAccess),
+            11 => Some(Instruction::CallFunc),
+            12 => Some(Instruction::EvalDirect),
+            63 => Some(Instruction::EvalIndirect),
+            13 => Some(Instruction::CallBytecodeFunc),
+            14 => Some(Instruction::ReturnBytecodeFunc),
+            15 => Some(Instruction::Copy),
+            16 => Some(Instruction::Exit),
+            117 => Some(Instruction::Halt),
+            17 => Some(Instruction::JumpCond),
+            18 => Some(Instruction::Jump),
+            19 => Some(Instruction::JumpCondNeg),
+            20 => Some(Instruction::BytecodeFuncCallback),
+            21 => Some(Instruction::PropertySet),
+            22 => Some(Instruction::Try),
+            23 => Some(Instruction::Throw),
+            24 => Some(Instruction::Nop),
+
+            50 => Some(Instruction::CompEqual),
+            51 => Some(Instruction::CompNotEqual),
+            52 => Some(Instruction::CompStrictEqual),
+            53 => Some(Instruction::CompStrictNotEqual),
+            54 => Some(Instruction::CompLessThan),
+            55 => Some(Instruction::CompGreaterThan),
+            56 => Some(Instruction::CompLessThanEqual),
+            57 => Some(Instruction::CompGreaterThanEqual),
+
+            84 => Some(Instruction::ToNumber),
+            85 => Some(Instruction::ToString),
+            86 => Some(Instruction::ToBoolean),
+            111 => Some(Instruction::TypeCoerceToString),
+            112 => Some(Instruction::TypeCoerceToNumber),
+            113 => Some(Instruction::ParseInt),
+            114 => Some(Instruction::ParseFloat),
+            119 => Some(Instruction::ArraySort),
+            118 => Some(Instruction::MemoryBarrier),
+            130 => Some(Instruction::MathFloor),
+            131 => Some(Instruction::MathCeil),
+            132 => Some(Instruction::MathRound),
+            133 => Some(Instruction::MathAbs),
+            134 => Some(Instruction::MathSqrt),
+            135 => Some(Instruction::MathPow),
+            136 => Some(Instruction::MathMin),
+            137 => Some(Instruction::MathMax),
+            138 => Some(Instruction::MathRandom),
+            139 => Some(Instruction::DateNow),
+            140 => Some(Instruction::ConsoleLog),
+            141 => Some(Instruction::JSONStringify),
+            142 => Some(Instruction::JSONParse),
+            143 => Some(Instruction::ObjectAssign),
+            144 => Some(Instruction::ObjectKeys),
+            145 => Some(Instruction::ObjectValues),
+            146 => Some(Instruction::ObjectEntries),
+            147 => Some(Instruction::ObjectFreeze),
+            148 => Some(Instruction::ObjectIsFrozen),
+            149 => Some(Instruction::ArrayFrom),
+            150 => Some(Instruction::ArrayIsArray),
+            151 => Some(Instruction::PromiseAll),
+            152 => Some(Instruction::PromiseRace),
+            153 => Some(Instruction::PromiseAny),
+            154 => Some(Instruction::NewSet),
+            155 => Some(Instruction::NewMap),
+            156 => Some(Instruction::SetAdd),
+            157 => Some(Instruction::SetHas),
+            158 => Some(Instruction::SetDelete),
+            159 => Some(Instruction::MapGet),
+            160 => Some(Instruction::MapSet),
+            161 => Some(Instruction::MapHas),
+            162 => Some(Instruction::MapGetOrDefault),
+            163 => Some(Instruction::WeakMapGet),
+            164 => Some(Instruction::WeakMapSet),
+            165 => Some(Instruction::TailCall),
+            166 => Some(Instruction::LoadGlobalThis),
+            167 => Some(Instruction::ObjectSpread),
+            168 => Some(Instruction::ArraySlice),
+            169 => Some(Instruction::ArrayConcat),
+            170 => Some(Instruction::ArrayMap),
+            171 => Some(Instruction::ArrayFilter),
+            172 => Some(Instruction::ArrayReduce),
+            173 => Some(Instruction::ArrayFind),
+            174 => Some(Instruction::ArrayFindIndex),
+            175 => Some(Instruction::ArrayEvery),
+            176 => Some(Instruction::ArraySome),
+            177 => Some(Instruction::ArrayForEach),
+            178 => Some(Instruction::ArrayFlat),
+            179 => Some(Instruction::ArrayFlatMap),
+            180 => Some(Instruction::StringSplit),
+            181 => Some(Instruction::StringRepeat),
+            182 => Some(Instruction::StringPadStart),
+            183 => Some(Instruction::StringPadEnd),
+            184 => Some(Instruction::StringTrim),
+            185 => Some(Instruction::StringTrimStart),
+            186 => Some(Instruction::StringTrimEnd),
+            187 => Some(Instruction::StringIncludes),
+            188 => Some(Instruction::StringStartsWith),
+            189 => Some(Instruction::StringEndsWith),
+            190 => Some(Instruction::StringMatch),
+            191 => Some(Instruction::StringMatchAll),
+            192 => Some(Instruction::StringReplace),
+            193 => Some(Instruction::StringReplaceAll),
+            194 => Some(Instruction::CallApply),
+            195 => Some(Instruction::HasBinding),
+            196 => Some(Instruction::NewWeakMap),
+            87 => Some(Instruction::CreateRegExp),
+            88 => Some(Instruction::RegExpTest),
+            89 => Some(Instruction::RegExpExec),
+            90 => Some(Instruction::CheckArity),
+            91 => Some(Instruction::DefaultParam),
+            92 => Some(Instruction::CollectRestArgs),
+            93 => Some(Instruction::GetArrayLength),
+            123 => Some(Instruction::GetLength),
+            124 => Some(Instruction::StringSlice),
+            125 => Some(Instruction::StringIndexOf),
+            126 => Some(Instruction::StringCharAt),
+            127 => Some(Instruction::StringCharCodeAt),
+            94 => Some(Instruction::ArrayGet),
+            95 => Some(Instruction::ArraySet),
+            96 => Some(Instruction::Assert),
+            97 => Some(Instruction::EnterScope),
+            98 => Some(Instruction::LeaveScope),
+            99 => Some(Instruction::WithStatement),
+            100 => Some(Instruction::Add),
+            101 => Some(Instruction::Mul),
+            102 => Some(Instruction::Minus),
+            103 => Some(Instruction::Div),
+
+            109 => Some(Instruction::Concat),
+            110 => Some(Instruction::AddImmediate),
+
+            _ => None
+        }
+    }
+
+    /// Returns the kinds of the operands this instruction expects in the final bytecode
+    ///
+    /// Addresses, such as jump targets, are encoded as [LongNum](enum.OperandKind.html#OperandKind::LongNum).
+    pub fn expected_operand_types(&self) -> &'static [OperandKind] {
+        use OperandKind::*;
+
+        match self {
+            Instruction::LoadString => &[Reg, String],
+            Instruction::LoadNum => &[Reg, ShortNum],
+            Instruction::LoadFloatNum => &[Reg, FloatNum],
+            Instruction::LoadLongNum => &[Reg, LongNum],
+            Instruction::LoadArray => &[Reg, RegistersArray],
+            Instruction::LoadNum16 => &[Reg, ShortNum16],
+            Instruction::LoadConst => &[Reg, ShortNum16],
+            Instruction::LoadSymbol => &[Reg, String],
+            Instruction::LoadWellKnownSymbol => &[Reg, ShortNum],
+
+            Instruction::PropAccess => &[Reg, Reg, PropertyKey],
+            Instruction::CallFunc => &[Reg, Reg, Reg, RegistersArray],
+            Instruction::EvalDirect |
+            Instruction::EvalIndirect => &[Reg, Reg],
+            Instruction::CheckArity => &[ShortNum, Reg],
+            Instruction::DefaultParam => &[Reg, Reg],
+            Instruction::CollectRestArgs => &[Reg, ShortNum],
+            Instruction::GetArrayLength |
+            Instruction::GetLength => &[Reg, Reg],
+            Instruction::StringSlice => &[Reg, Reg, Reg, Reg, ShortNum],
+            Instruction::StringIndexOf => &[Reg, Reg, Reg, Reg],
+            Instruction::StringCharAt |
+            Instruction::StringCharCodeAt => &[Reg, Reg, Reg],
+            Instruction::ArrayGet => &[Reg, Reg, Reg],
+            Instruction::ArraySet => &[Reg, Reg, Reg],
+            Instruction::Assert => &[Reg, String],
+            Instruction::EnterScope |
+            Instruction::LeaveScope => &[],
+            Instruction::CallBytecodeFunc => &[LongNum, Reg, RegistersArray],
+            Instruction::ReturnBytecodeFunc => &[Reg, RegistersArray],
+            Instruction::Copy => &[Reg, Reg],
+            Instruction::Exit |
+            Instruction::Halt => &[],
+            Instruction::JumpCond => &[Reg, LongNum],
+            Instruction::Jump => &[LongNum],
+            Instruction::JumpCondNeg => &[Reg, LongNum],
+            Instruction::BytecodeFuncCallback => &[Reg, LongNum, RegistersArray],
+            Instruction::ArraySort => &[Reg, Reg, LongNum, RegistersArray],
+            Instruction::PropertySet => &[Reg, Reg, Reg],
+            Instruction::Try => &[Reg, LongNum, LongNum],
+            Instruction::Throw |
+            Instruction::WithStatement => &[Reg],
+            Instruction::HasBinding => &[Reg, Reg, Reg],
+            Instruction::Nop => &[],
+
+            Instruction::CompEqual |
+            Instruction::CompNotEqual |
+            Instruction::CompStrictEqual |
+            Instruction::CompStrictNotEqual |
+            Instruction::CompLessThan |
+            Instruction::CompGreaterThan |
+            Instruction::CompLessThanEqual |
+            Instruction::CompGreaterThanEqual => &[Reg, Reg, Reg],
+
+            Instruction::ToNumber |
+            Instruction::ToString |
+            Instruction::ToBoolean |
+            Instruction::TypeCoerceToString |
+            Instruction::TypeCoerceToNumber |
+            Instruction::ParseFloat => &[Reg, Reg],
+            Instruction::ParseInt => &[Reg, Reg, ShortNum],
+            Instruction::MemoryBarrier => &[ShortNum],
+            Instruction::MathFloor |
+            Instruction::MathCeil |
+            Instruction::MathRound |
+            Instruction::MathAbs |
+            Instruction::MathSqrt => &[Reg, Reg],
+            Instruction::MathPow => &[Reg, Reg, Reg],
+            Instruction::MathMin |
+            Instruction::MathMax => &[Reg, RegistersArray],
+            Instruction::MathRandom |
+            Instruction::DateNow => &[Reg],
+            Instruction::ConsoleLog => &[RegistersArray],
+            Instruction::JSONStringify => &[Reg, Reg, Reg, Reg],
+            Instruction::JSONParse => &[Reg, Reg, Reg],
+            Instruction::ObjectAssign => &[Reg, Reg, RegistersArray],
+            Instruction::ObjectKeys |
+            Instruction::ObjectValues |
+            Instruction::ObjectEntries |
+            Instruction::ObjectFreeze |
+            Instruction::ObjectIsFrozen |
+            Instruction::ArrayIsArray |
+            Instruction::PromiseAll |
+            Instruction::PromiseRace |
+            Instruction::PromiseAny |
+            Instruction::NewSet |
+            Instruction::NewMap => &[Reg, Reg],
+            Instruction::NewWeakMap => &[Reg, Reg],
+            Instruction::SetAdd |
+            Instruction::SetHas |
+            Instruction::SetDelete |
+            Instruction::MapGet |
+            Instruction::MapHas |
+            Instruction::WeakMapGet => &[Reg, Reg, Reg],
+            Instruction::MapSet |
+            Instruction::MapGetOrDefault |
+            Instruction::WeakMapSet => &[Reg, Reg, Reg, Reg],
+            Instruction::TailCall => &[LongNum, RegistersArray, RegistersArray],
+            Instruction::LoadGlobalThis => &[Reg],
+            Instruction::ObjectSpread => &[Reg, Reg, Reg],
+            Instruction::ArraySlice => &[Reg, Reg, Reg, Reg],
+            Instruction::ArrayConcat => &[Reg, Reg, RegistersArray],
+            Instruction::ArrayMap => &[Reg, Reg, LongNum, RegistersArray],
+            Instruction::ArrayFilter => &[Reg, Reg, LongNum, RegistersArray],
+            Instruction::ArrayReduce => &[Reg, Reg, LongNum, RegistersArray, Reg],
+            Instruction::ArrayFind => &[Reg, Reg, LongNum, RegistersArray],
+            Instruction::ArrayFindIndex => &[Reg, Reg, LongNum, RegistersArray],
+            Instruction::ArrayEvery => &[Reg, Reg, LongNum, RegistersArray],
+            Instruction::ArraySome => &[Reg, Reg, LongNum, RegistersArray],
+            Instruction::ArrayForEach => &[Reg, LongNum, RegistersArray],
+            Instruction::ArrayFlat => &[Reg, Reg, Reg],
+            Instruction::ArrayFlatMap => &[Reg, Reg, LongNum, RegistersArray],
+            Instruction::StringSplit => &[Reg, Reg, Reg, Reg],
+            Instruction::StringRepeat => &[Reg, Reg, Reg],
+            Instruction::StringPadStart => &[Reg, Reg, Reg, Reg],
+            Instruction::StringPadEnd => &[Reg, Reg, Reg, Reg],
+            Instruction::StringTrim => &[Reg, Reg],
+            Instruction::StringTrimStart => &[Reg, Reg],
+            Instruction::StringTrimEnd => &[Reg, Reg],
+            Instruction::StringIncludes => &[Reg, Reg, Reg, Reg],
+            Instruction::StringStartsWith => &[Reg, Reg, Reg, Reg],
+            Instruction::StringEndsWith => &[Reg, Reg, Reg, Reg],
+            Instruction::StringMatch => &[Reg, Reg, Reg],
+            Instruction::StringMatchAll => &[Reg, Reg, Reg],
+            Instruction::StringReplace => &[Reg, Reg, Reg, Reg],
+            Instruction::StringReplaceAll => &[Reg, Reg, Reg, Reg],
+            Instruction::CallApply => &[Reg, Reg, Reg, Reg],
+            Instruction::ArrayFrom => &[Reg, Reg, Reg],
+            Instruction::CreateRegExp => &[Reg, RegEx],
+            Instruction::RegExpTest |
+            Instruction::RegExpExec => &[Reg, Reg, Reg],
+
+            Instruction::Add |
+            Instruction::Minus |
+            Instruction::Mul |
+            Instruction::Div |
+            Instruction::Concat => &[Reg, Reg, Reg],
+            Instruction::AddImmediate => &[Reg, Reg, ShortNum],
         }
     }
 
@@ -115,14 +916,36 @@ impl Instruction {
             Instruction::LoadFloatNum => "LoadFloatNum",
             Instruction::LoadLongNum => "LoadLongNum",
             Instruction::LoadArray => "LoadArray",
+            Instruction::LoadNum16 => "LoadNum16",
+            Instruction::LoadConst => "LoadConst",
+            Instruction::LoadSymbol => "LoadSymbol",
+            Instruction::LoadWellKnownSymbol => "LoadWellKnownSymbol",
 
             Instruction::PropAccess => "PropAccess",
             Instruction::CallFunc => "CallFunc",
-            Instruction::Eval => "Eval",
+            Instruction::EvalDirect => "EvalDirect",
+            Instruction::EvalIndirect => "EvalIndirect",
+            Instruction::CheckArity => "CheckArity",
+            Instruction::DefaultParam => "DefaultParam",
+            Instruction::CollectRestArgs => "CollectRestArgs",
+            Instruction::GetArrayLength => "GetArrayLength",
+            Instruction::GetLength => "GetLength",
+            Instruction::StringSlice => "StringSlice",
+            Instruction::StringIndexOf => "StringIndexOf",
+            Instruction::StringCharAt => "StringCharAt",
+            Instruction::StringCharCodeAt => "StringCharCodeAt",
+            Instruction::ArrayGet => "ArrayGet",
+            Instruction::ArraySet => "ArraySet",
+            Instruction::Assert => "Assert",
+            Instruction::EnterScope => "EnterScope",
+            Instruction::LeaveScope => "LeaveScope",
+            Instruction::WithStatement => "WithStatement",
+            Instruction::HasBinding => "HasBinding",
             Instruction::CallBytecodeFunc => "CallBytecodeFunc",
             Instruction::ReturnBytecodeFunc => "ReturnBytecodeFunc",
             Instruction::Copy => "Copy",
             Instruction::Exit => "Exit",
+            Instruction::Halt => "Halt",
             Instruction::JumpCond => "JumpCond",
             Instruction::Jump => "Jump",
             Instruction::JumpCondNeg => "JumpCondNeg",
@@ -130,6 +953,7 @@ impl Instruction {
             Instruction::PropertySet => "PropertySet",
             Instruction::Try => "Try",
             Instruction::Throw => "Throw",
+            Instruction::Nop => "Nop",
 
             Instruction::CompEqual => "CompEqual",
             Instruction::CompNotEqual => "CompNotEqual",
@@ -140,23 +964,210 @@ impl Instruction {
             Instruction::CompLessThanEqual => "CompLessThanEqual",
             Instruction::CompGreaterThanEqual => "CompGreaterThanEqual",
 
+            Instruction::ToNumber => "ToNumber",
+            Instruction::ToString => "ToString",
+            Instruction::ToBoolean => "ToBoolean",
+            Instruction::TypeCoerceToString => "TypeCoerceToString",
+            Instruction::TypeCoerceToNumber => "TypeCoerceToNumber",
+            Instruction::ParseInt => "ParseInt",
+            Instruction::ParseFloat => "ParseFloat",
+            Instruction::ArraySort => "ArraySort",
+            Instruction::MemoryBarrier => "MemoryBarrier",
+            Instruction::MathFloor => "MathFloor",
+            Instruction::MathCeil => "MathCeil",
+            Instruction::MathRound => "MathRound",
+            Instruction::MathAbs => "MathAbs",
+            Instruction::MathSqrt => "MathSqrt",
+            Instruction::MathPow => "MathPow",
+            Instruction::MathMin => "MathMin",
+            Instruction::MathMax => "MathMax",
+            Instruction::MathRandom => "MathRandom",
+            Instruction::DateNow => "DateNow",
+            Instruction::ConsoleLog => "ConsoleLog",
+            Instruction::JSONStringify => "JSONStringify",
+            Instruction::JSONParse => "JSONParse",
+            Instruction::ObjectAssign => "ObjectAssign",
+            Instruction::ObjectKeys => "ObjectKeys",
+            Instruction::ObjectValues => "ObjectValues",
+            Instruction::ObjectEntries => "ObjectEntries",
+            Instruction::ObjectFreeze => "ObjectFreeze",
+            Instruction::ObjectIsFrozen => "ObjectIsFrozen",
+            Instruction::ArrayFrom => "ArrayFrom",
+            Instruction::ArrayIsArray => "ArrayIsArray",
+            Instruction::PromiseAll => "PromiseAll",
+            Instruction::PromiseRace => "PromiseRace",
+            Instruction::PromiseAny => "PromiseAny",
+            Instruction::NewSet => "NewSet",
+            Instruction::NewMap => "NewMap",
+            Instruction::NewWeakMap => "NewWeakMap",
+            Instruction::SetAdd => "SetAdd",
+            Instruction::SetHas => "SetHas",
+            Instruction::SetDelete => "SetDelete",
+            Instruction::MapGet => "MapGet",
+            Instruction::MapSet => "MapSet",
+            Instruction::MapHas => "MapHas",
+            Instruction::MapGetOrDefault => "MapGetOrDefault",
+            Instruction::WeakMapGet => "WeakMapGet",
+            Instruction::WeakMapSet => "WeakMapSet",
+            Instruction::TailCall => "TailCall",
+            Instruction::LoadGlobalThis => "LoadGlobalThis",
+            Instruction::ObjectSpread => "ObjectSpread",
+            Instruction::ArraySlice => "ArraySlice",
+            Instruction::ArrayConcat => "ArrayConcat",
+            Instruction::ArrayMap => "ArrayMap",
+            Instruction::ArrayFilter => "ArrayFilter",
+            Instruction::ArrayReduce => "ArrayReduce",
+            Instruction::ArrayFind => "ArrayFind",
+            Instruction::ArrayFindIndex => "ArrayFindIndex",
+            Instruction::ArrayEvery => "ArrayEvery",
+            Instruction::ArraySome => "ArraySome",
+            Instruction::ArrayForEach => "ArrayForEach",
+            Instruction::ArrayFlat => "ArrayFlat",
+            Instruction::ArrayFlatMap => "ArrayFlatMap",
+            Instruction::StringSplit => "StringSplit",
+            Instruction::StringRepeat => "StringRepeat",
+            Instruction::StringPadStart => "StringPadStart",
+            Instruction::StringPadEnd => "StringPadEnd",
+            Instruction::StringTrim => "StringTrim",
+            Instruction::StringTrimStart => "StringTrimStart",
+            Instruction::StringTrimEnd => "StringTrimEnd",
+            Instruction::StringIncludes => "StringIncludes",
+            Instruction::StringStartsWith => "StringStartsWith",
+            Instruction::StringEndsWith => "StringEndsWith",
+            Instruction::StringMatch => "StringMatch",
+            Instruction::StringMatchAll => "StringMatchAll",
+            Instruction::StringReplace => "StringReplace",
+            Instruction::StringReplaceAll => "StringReplaceAll",
+            Instruction::CallApply => "CallApply",
+            Instruction::CreateRegExp => "CreateRegExp",
+            Instruction::RegExpTest => "RegExpTest",
+            Instruction::RegExpExec => "RegExpExec",
+
             Instruction::Add => "Add",
             Instruction::Minus => "Minus",
             Instruction::Mul => "Mul",
             Instruction::Div => "Div",
+            Instruction::Concat => "Concat",
+            Instruction::AddImmediate => "AddImmediate",
         }
     }
 }
 
 
+/// The ordering guarantees of a [MemoryBarrier](enum.Instruction.html#Instruction::MemoryBarrier)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MemoryOrdering {
+    SequentiallyConsistent,
+    /// Later reads of shared memory see at least the writes that happened before
+    Acquire,
+    /// Earlier writes to shared memory are visible before the following ones
+    Release,
+}
+
+impl From<MemoryOrdering> for Operand {
+    fn from(ordering: MemoryOrdering) -> Operand {
+        Operand::ShortNum(match ordering {
+            MemoryOrdering::SequentiallyConsistent => 0,
+            MemoryOrdering::Acquire => 1,
+            MemoryOrdering::Release => 2,
+        })
+    }
+}
+
+/// The string method whose semantics a [StringSlice](enum.Instruction.html#Instruction::StringSlice) has
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StringSliceKind {
+    /// Negative indices count from the end, a start after the end results in an empty string
+    Slice,
+    /// Negative indices are treated as 0, a start after the end swaps both
+    Substring,
+}
+
+impl StringSliceKind {
+    /// Returns the kind of a call of the string method ``name``
+    pub fn from_method(name: &str) -> Option<StringSliceKind> {
+        match name {
+            "slice" => Some(StringSliceKind::Slice),
+            "substring" => Some(StringSliceKind::Substring),
+            _ => None
+        }
+    }
+}
+
+impl From<StringSliceKind> for Operand {
+    fn from(kind: StringSliceKind) -> Operand {
+        Operand::ShortNum(match kind {
+            StringSliceKind::Slice => 0,
+            StringSliceKind::Substring => 1,
+        })
+    }
+}
+
+/// The well-known symbols, i.e. the symbols which are properties of ``Symbol``, like ``Symbol.iterator``
+///
+/// The values are the indices encoded in operands, e.g. in a [SymbolKey](enum.Operand.html#Operand::SymbolKey).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WellKnownSymbol {
+    Iterator = 0,
+    ToPrimitive = 1,
+    AsyncIterator = 2,
+    HasInstance = 3,
+    IsConcatSpreadable = 4,
+    Match = 5,
+    MatchAll = 6,
+    Replace = 7,
+    Search = 8,
+    Species = 9,
+    Split = 10,
+    ToStringTag = 11,
+    Unscopables = 12,
+}
+
+impl WellKnownSymbol {
+    /// Returns the well-known symbol which is the property ``name`` of ``Symbol``
+    pub fn from_name(name: &str) -> Option<WellKnownSymbol> {
+        match name {
+            "iterator" => Some(WellKnownSymbol::Iterator),
+            "toPrimitive" => Some(WellKnownSymbol::ToPrimitive),
+            "asyncIterator" => Some(WellKnownSymbol::AsyncIterator),
+            "hasInstance" => Some(WellKnownSymbol::HasInstance),
+            "isConcatSpreadable" => Some(WellKnownSymbol::IsConcatSpreadable),
+            "match" => Some(WellKnownSymbol::Match),
+            "matchAll" => Some(WellKnownSymbol::MatchAll),
+            "replace" => Some(WellKnownSymbol::Replace),
+            "search" => Some(WellKnownSymbol::Search),
+            "species" => Some(WellKnownSymbol::Species),
+            "split" => Some(WellKnownSymbol::Split),
+            "toStringTag" => Some(WellKnownSymbol::ToStringTag),
+            "unscopables" => Some(WellKnownSymbol::Unscopables),
+            _ => None
+        }
+    }
+
+    /// Returns the well-known symbol with the given index
+    pub fn from_index(index: u32) -> Option<WellKnownSymbol> {
+        [WellKnownSymbol::Iterator, WellKnownSymbol::ToPrimitive, WellKnownSymbol::AsyncIterator,
+         WellKnownSymbol::HasInstance, WellKnownSymbol::IsConcatSpreadable, WellKnownSymbol::Match,
+         WellKnownSymbol::MatchAll, WellKnownSymbol::Replace, WellKnownSymbol::Search, WellKnownSymbol::Species,
+         WellKnownSymbol::Split, WellKnownSymbol::ToStringTag, WellKnownSymbol::Unscopables]
+            .iter().copied().find(|&symbol| symbol as u32 == index)
+    }
+}
+
+impl From<WellKnownSymbol> for Operand {
+    fn from(symbol: WellKnownSymbol) -> Operand {
+        Operand::ShortNum(symbol as u8)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct BytecodeAddrToken {
     pub ident: String
 }
 
 impl ToBytes for BytecodeAddrToken {
-    fn to_bytes(&self) -> Vec<u8> {
-        vec![0; 4]
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        Ok(vec![0; 4])
     }
 
     fn length_in_bytes(&self) -> usize {
@@ -170,8 +1181,8 @@ pub struct LabelAddrToken {
 }
 
 impl ToBytes for LabelAddrToken {
-    fn to_bytes(&self) -> Vec<u8> {
-        vec![0; 4]
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        Ok(vec![0; 4])
     }
 
     fn length_in_bytes(&self) -> usize {
@@ -185,8 +1196,8 @@ pub struct FunctionArguments {
 }
 
 impl ToBytes for FunctionArguments {
-    fn to_bytes(&self) -> Vec<u8> {
-        vec![0; self.args.len()]
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        Ok(vec![0; self.args.len()])
     }
 
     fn length_in_bytes(&self) -> usize {
@@ -200,8 +1211,7 @@ impl ToBytes for FunctionArguments {
 ///
 /// ``var b = 100;`` => ``b => BytecodeLiteral::IntNumber(100)``
 ///
-/// # Note
-/// JavaScript regex literals are not yet supported.
+/// ``var r = /\d+/g;`` => ``r => BytecodeLiteral::RegEx("\d+", "g")``
 #[derive(Clone, Debug, PartialEq)]
 pub enum BytecodeLiteral
 {
@@ -210,7 +1220,7 @@ pub enum BytecodeLiteral
     FloatNum(f64),
     IntNumber(i64),
     Bool(bool),
-    // RegEx(ressa::expr::RegEx)
+    RegEx(String, String)
 }
 
 impl BytecodeLiteral {
@@ -225,6 +1235,15 @@ impl BytecodeLiteral {
                 }
             }),
             Literal::Number(num_string) => {
+                // NaN and Infinity are global identifiers in JS but may still end up as
+                // number literals, e.g. after constant folding.
+                match num_string.as_str() {
+                    "NaN" => return Ok(BytecodeLiteral::FloatNum(f64::NAN)),
+                    "Infinity" => return Ok(BytecodeLiteral::FloatNum(f64::INFINITY)),
+                    "-Infinity" => return Ok(BytecodeLiteral::FloatNum(f64::NEG_INFINITY)),
+                    _ => {}
+                }
+
                 if let Ok(dec_num) = num_string.parse::<i64>() {
                     Ok(BytecodeLiteral::IntNumber(dec_num))
                 } else if let Ok(float_num) = num_string.parse::<f64>() {
@@ -256,8 +1275,8 @@ impl BytecodeLiteral {
                 }
             },
             Literal::Boolean(b) => Ok(BytecodeLiteral::Bool(b)),
-            Literal::RegEx(_) |
-            Literal::Template(_) => Err(CompilerError::are_unsupported("regex and template literals"))
+            Literal::RegEx(regex) => Ok(BytecodeLiteral::RegEx(regex.pattern, regex.flags)),
+            Literal::Template(_) => Err(CompilerError::are_unsupported("template literals"))
         }
     }
 }
@@ -270,10 +1289,29 @@ impl std::fmt::Display for BytecodeLiteral {
             BytecodeLiteral::FloatNum(float) => write!(f, "Float(){})", float),
             BytecodeLiteral::IntNumber(signed_int) => write!(f, "SignedInt({})", signed_int),
             BytecodeLiteral::Bool(bool) => write!(f, "Bool({})", bool),
+            BytecodeLiteral::RegEx(pattern, flags) => write!(f, "RegEx(/{}/{})", pattern, flags),
         }
     }
 }
 
+/// Represents the kinds of operands that can be part of a final bytecode
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OperandKind
+{
+    String,
+    FloatNum,
+    LongNum,
+    ShortNum,
+    ShortNum16,
+    Reg,
+    RegistersArray,
+    RegEx,
+    /// A [register](enum.Operand.html#Operand::Reg) or a [symbol key](enum.Operand.html#Operand::SymbolKey).
+    ///
+    /// Encoded as a tag byte followed by the operand: 0 for a register, 1 for a symbol key.
+    PropertyKey,
+}
+
 /// Represents variants of bytecode operands
 ///
 /// There are two types of operands. Regular operands(numbers, strings or registers) and token operands.
@@ -287,11 +1325,24 @@ impl std::fmt::Display for BytecodeLiteral {
 pub enum Operand
 {
     String(String),
+    /// A 64 bit float, encoded big-endian as its IEEE 754 bit pattern (``f64::to_bits``).
+    ///
+    /// ``NaN``, ``Infinity`` and ``-Infinity`` have no dedicated operands. They are encoded
+    /// as the bit patterns of ``f64::NAN``, ``f64::INFINITY`` and ``f64::NEG_INFINITY``,
+    /// i.e. ``7ff8000000000000``, ``7ff0000000000000`` and ``fff0000000000000``.
     FloatNum(f64),
     LongNum(i32),
     ShortNum(u8),
+    /// A 16 bit number, encoded as two big-endian bytes
+    ShortNum16(u16),
     Reg(u8),
     RegistersArray(Vec<u8>),
+    /// A regular expression pattern and its [encoded flags](enum.Operand.html#method.encode_regex_flags)
+    RegEx(String, u8),
+    /// The index of a string in a [string pool](../string_pool/struct.StringPool.html), encoded as two big-endian bytes
+    StringRef(u16),
+    /// A [well-known symbol](enum.WellKnownSymbol.html) used as property key, encoded as 4 byte big-endian number
+    SymbolKey(u32),
 
     FunctionAddr(BytecodeAddrToken),
     BranchAddr(LabelAddrToken),
@@ -304,26 +1355,82 @@ impl Operand {
         match literal {
             BytecodeLiteral::Null => Ok(Operand::Reg(253)), //TODO: Register of predefined void 0,
             BytecodeLiteral::String(string) => Ok(Operand::String(string)),
-            BytecodeLiteral::FloatNum(float) => Ok(Operand::FloatNum(float)),
-            BytecodeLiteral::IntNumber(int) => {
-                if int <= 255 && int >= 0 {
-                    Ok(Operand::ShortNum(int as u8))
-                } else if int <= std::i32::MAX.into() && int >= std::i32::MIN.into() {
-                    Ok(Operand::LongNum(int as i32))
-                } else {
-                    Err(CompilerError::Custom(
-                        format!("Only integers from {} to {} are allowed. Consider using a float instead",
-                        std::i32::MIN, std::i32::MAX)))
-                }
-            },
+            BytecodeLiteral::FloatNum(float) => Ok(Operand::from_number(float)),
+            BytecodeLiteral::IntNumber(int) => Ok(Operand::from_number(int as f64)),
             BytecodeLiteral::Bool(bool) => Ok(Operand::ShortNum(bool as u8)),
+            BytecodeLiteral::RegEx(pattern, flags) => Operand::regex(pattern, &flags),
+        }
+    }
+
+    /// Selects the smallest operand able to represent a JS number exactly.
+    ///
+    /// Integral values are encoded as ``ShortNum``, ``ShortNum16`` or ``LongNum``
+    /// depending on their range. Everything else (fractions, ``-0``, ``NaN``,
+    /// ``Infinity`` and integers outside of the i32 range) becomes a ``FloatNum``.
+    pub fn from_number(num: f64) -> Self {
+        let is_integral = num.is_finite() && num.fract() == 0.0 && !(num == 0.0 && num.is_sign_negative());
+
+        if !is_integral {
+            Operand::FloatNum(num)
+        } else if num >= 0.0 && num <= f64::from(u8::MAX) {
+            Operand::ShortNum(num as u8)
+        } else if num >= 0.0 && num <= f64::from(u16::MAX) {
+            Operand::ShortNum16(num as u16)
+        } else if num >= f64::from(i32::MIN) && num <= f64::from(i32::MAX) {
+            Operand::LongNum(num as i32)
+        } else {
+            Operand::FloatNum(num)
         }
     }
 
+    pub fn symbol_key(symbol: WellKnownSymbol) -> Self {
+        Operand::SymbolKey(symbol as u32)
+    }
+
+    /// Encodes the operand as [property key](enum.OperandKind.html#OperandKind::PropertyKey)
+    fn encode_property_key(&self) -> CompilerResult<Vec<u8>> {
+        let (tag, mut encoded) = match self {
+            Operand::Reg(_) => (0, self.to_bytes()?),
+            Operand::SymbolKey(_) => (1, self.to_bytes()?),
+            _ => { return Err(CompilerError::Custom(format!("The operand {} is no property key", self))); }
+        };
+        encoded.insert(0, tag);
+        Ok(encoded)
+    }
+
+    pub fn u16(val: u16) -> Self {
+        Operand::ShortNum16(val)
+    }
+
     pub fn str(string: String) -> Self {
         Operand::String(string.to_string())
     }
 
+    pub fn regex(pattern: String, flags: &str) -> CompilerResult<Self> {
+        Ok(Operand::RegEx(pattern, Operand::encode_regex_flags(flags)?))
+    }
+
+    /// Encodes the flags of a regular expression as bit field
+    ///
+    /// | Flag | ``g`` | ``i`` | ``m`` | ``s`` | ``u`` | ``y`` |
+    /// |------|-------|-------|-------|-------|-------|-------|
+    /// | Bit  | 0     | 1     | 2     | 3     | 4     | 5     |
+    pub fn encode_regex_flags(flags: &str) -> CompilerResult<u8> {
+        flags.chars().try_fold(0u8, |encoded, flag| {
+            let bit = match flag {
+                'g' => 0,
+                'i' => 1,
+                'm' => 2,
+                's' => 3,
+                'u' => 4,
+                'y' => 5,
+                _ => { return Err(CompilerError::Custom(format!("Unknown regular expression flag '{}'", flag))); }
+            };
+
+            Ok(encoded | (1 << bit))
+        })
+    }
+
     pub fn function_addr(ident: String) -> Self {
         Operand::FunctionAddr(BytecodeAddrToken{ ident })
     }
@@ -346,26 +1453,26 @@ impl Operand {
         }
     }
 
-    fn encode_string(string: String) -> Vec<u8> {
-        if string.len() > u16::max_value() as usize {
-            panic!("The string '{}' is too long. Encoded string may only have 65536 charachters.");
-        }
-
+    fn encode_string(string: String) -> CompilerResult<Vec<u8>> {
         let bytes = string.as_bytes();
 
-        let mut encoded = vec![(bytes.len() & 0xff00) as u8, (bytes.len() & 0xff) as u8];
+        if bytes.len() > u16::MAX as usize {
+            return Err(CompilerError::StringTooLong(bytes.len()));
+        }
+
+        let mut encoded = vec![(bytes.len() >> 8) as u8, (bytes.len() & 0xff) as u8];
         encoded.extend_from_slice(bytes);
-        encoded
+        Ok(encoded)
     }
 
-    fn encode_registers_array(regs: &[Register]) -> Vec<u8> {
-        if regs.len() > u8::max_value() as usize {
-            panic!("Too long registers array. Encoded byte arrays may only have 256 elements.");
+    fn encode_registers_array(regs: &[Register]) -> CompilerResult<Vec<u8>> {
+        if regs.len() > u8::MAX as usize {
+            return Err(CompilerError::TooManyArguments(regs.len()));
         }
 
         let mut encoded = vec![regs.len() as u8];
         encoded.extend_from_slice(regs);
-        encoded
+        Ok(encoded)
     }
 
     fn encode_num(num: u32) -> Vec<u8> {
@@ -392,29 +1499,41 @@ impl Operand {
 }
 
 impl ToBytes for Operand {
-    fn to_bytes(&self) -> Vec<u8> {
-        match self {
-            Operand::String(string) => Operand::encode_string(string.to_string()),
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        Ok(match self {
+            Operand::String(string) => Operand::encode_string(string.to_string())?,
             Operand::FloatNum(float_num) => Operand::encode_float_num(float_num.clone()),
             Operand::LongNum(long_num) => Operand::encode_num(long_num.clone() as u32),
+            Operand::SymbolKey(index) => Operand::encode_num(*index),
             Operand::ShortNum(num) |
             Operand::Reg(num) => vec![*num],
-            Operand::RegistersArray(regs) => Operand::encode_registers_array(&regs),
-            Operand::FunctionAddr(token)  => token.to_bytes(),
-            Operand::BranchAddr(token) => token.to_bytes(),
-            Operand::FunctionArguments(args) => args.to_bytes(),
+            Operand::ShortNum16(num) |
+            Operand::StringRef(num) => vec![(num >> 8) as u8, (num & 0xff) as u8],
+            Operand::RegistersArray(regs) => Operand::encode_registers_array(&regs)?,
+            Operand::RegEx(pattern, flags) => {
+                let mut encoded = Operand::encode_string(pattern.to_string())?;
+                encoded.push(*flags);
+                encoded
+            },
+            Operand::FunctionAddr(token)  => token.to_bytes()?,
+            Operand::BranchAddr(token) => token.to_bytes()?,
+            Operand::FunctionArguments(args) => args.to_bytes()?,
             Operand::BytecodeEnd => vec![0; 4]
-        }
+        })
     }
 
     fn length_in_bytes(&self) -> usize {
         match self {
             Operand::String(string) => 2 + string.len(),
             Operand::FloatNum(_) => 8,
-            Operand::LongNum(_) => 4,
+            Operand::LongNum(_) |
+            Operand::SymbolKey(_) => 4,
             Operand::ShortNum(_) |
             Operand::Reg(_) => 1,
+            Operand::ShortNum16(_) |
+            Operand::StringRef(_) => 2,
             Operand::RegistersArray(regs) => 1 + regs.len(),
+            Operand::RegEx(pattern, _) => 2 + pattern.len() + 1,
             Operand::FunctionAddr(token) => token.length_in_bytes(),
             Operand::BranchAddr(token) => token.length_in_bytes(),
             Operand::FunctionArguments(args) => args.length_in_bytes(),
@@ -430,8 +1549,12 @@ impl std::fmt::Display for Operand {
             Operand::FloatNum(float) => write!(f, "Float({})", float),
             Operand::LongNum(long_num) => write!(f, "LongNum({})", long_num),
             Operand::ShortNum(short_num) => write!(f, "ShortNum({})", short_num),
+            Operand::ShortNum16(short_num) => write!(f, "ShortNum16({})", short_num),
             Operand::Reg(reg) => write!(f, "Reg({})", reg),
             Operand::RegistersArray(reg_array) => write!(f, "RegArray({:?})", reg_array),
+            Operand::RegEx(pattern, flags) => write!(f, "RegEx(/{}/, {})", pattern, flags),
+            Operand::StringRef(index) => write!(f, "StringRef({})", index),
+            Operand::SymbolKey(index) => write!(f, "SymbolKey({})", index),
 
             Operand::FunctionAddr(bc_addr_token) => write!(f, "FunctionAddr({:?})", bc_addr_token),
             Operand::BranchAddr(label_addr_token) => write!(f, "BranchAddr({:?})", label_addr_token),
@@ -464,6 +1587,46 @@ impl Operation {
             operands
         }
     }
+
+    /// Checks whether this is a (conditional) jump.
+    pub fn is_branch(&self) -> bool {
+        matches!(self.instruction,
+            Instruction::Jump |
+            Instruction::JumpCond |
+            Instruction::JumpCondNeg)
+    }
+
+    /// Checks whether this calls a function or evaluates code.
+    pub fn is_call(&self) -> bool {
+        matches!(self.instruction,
+            Instruction::CallFunc |
+            Instruction::CallBytecodeFunc |
+            Instruction::TailCall |
+            Instruction::CallApply |
+            Instruction::EvalDirect |
+            Instruction::EvalIndirect)
+    }
+
+    /// Checks whether this leaves the current bytecode function or the whole bytecode.
+    pub fn is_return(&self) -> bool {
+        matches!(self.instruction,
+            Instruction::ReturnBytecodeFunc |
+            Instruction::TailCall |
+            Instruction::Exit)
+    }
+
+    /// Returns the [label](type.Label.html) a branch jumps to. Returns ``None`` if this is no branch
+    /// or if its target is not a [branch address](enum.Operand.html#Operand::BranchAddr) anymore.
+    pub fn branch_target(&self) -> Option<Label> {
+        if !self.is_branch() {
+            return None;
+        }
+
+        self.operands.iter().find_map(|operand| match operand {
+            Operand::BranchAddr(token) => Some(token.label),
+            _ => None
+        })
+    }
 }
 
 impl std::fmt::Display for Operation {
@@ -477,14 +1640,26 @@ impl std::fmt::Display for Operation {
 }
 
 impl ToBytes for Operation {
-    fn to_bytes(&self) -> Vec<u8> {
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        let kinds = self.instruction.expected_operand_types();
         let mut line = vec![self.instruction.to_byte()];
-        line.append(&mut self.operands.iter().map(|operand| operand.to_bytes()).flatten().collect::<Vec<u8>>());
-        line
+        for (i, operand) in self.operands.iter().enumerate() {
+            if kinds.get(i) == Some(&OperandKind::PropertyKey) {
+                line.append(&mut operand.encode_property_key()?);
+            } else {
+                line.append(&mut operand.to_bytes()?);
+            }
+        }
+        Ok(line)
     }
 
     fn length_in_bytes(&self) -> usize {
-        1 + self.operands.iter().fold(0, |acc, x| acc + x.length_in_bytes())
+        let property_keys = self.instruction.expected_operand_types().iter()
+                                .take(self.operands.len())
+                                .filter(|&&kind| kind == OperandKind::PropertyKey)
+                                .count();
+
+        1 + property_keys + self.operands.iter().fold(0, |acc, x| acc + x.length_in_bytes())
     }
 }
 
@@ -497,10 +1672,10 @@ pub enum BytecodeElement
 }
 
 impl ToBytes for BytecodeElement {
-    fn to_bytes(&self) -> Vec<u8> {
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
         match self {
             BytecodeElement::Operation(cmd) => cmd.to_bytes(),
-            BytecodeElement::Label(_) => vec![]
+            BytecodeElement::Label(_) => Ok(vec![])
         }
     }
 
@@ -557,39 +1732,179 @@ impl Bytecode {
         }
     }
 
+    /// Appends a [command](struct.Operation.html) as [bytecode element](enum.BytecodeElement.html).
+    ///
+    /// Like all builder methods, this consumes the bytecode and returns the extended one.
+    /// Ignoring the result is an error:
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// # use jsyc_compiler::{Bytecode, Operation, Instruction};
+    /// let bytecode = Bytecode::new();
+    /// bytecode.add(Operation::new(Instruction::Exit, vec![]));
+    /// ```
+    #[must_use = "returns a new Bytecode with the element added"]
     pub fn add(mut self, command: Operation) -> Self {
         self.elements.push(BytecodeElement::Operation(command));
         self
     }
 
     /// Appends a [label](type.Label.html) as [bytecode element](enum.BytecodeElement.html).
+    #[must_use = "returns a new Bytecode with the element added"]
     pub fn add_label(mut self, label: Label) -> Self {
         self.elements.push(BytecodeElement::Label(label));
         self
     }
 
     /// Appends another bytecode onto this bytecode.
+    #[must_use = "returns a new Bytecode with the element added"]
     pub fn add_bytecode(mut self, mut other: Bytecode) -> Self {
         self.elements.append(&mut other.elements);
         self
     }
 
+    /// Appends all [commands](struct.Operation.html) of an iterator onto this bytecode.
+    #[must_use = "returns a new Bytecode with the elements added"]
+    pub fn extend(mut self, commands: impl IntoIterator<Item = Operation>) -> Self {
+        self.elements.extend(commands.into_iter().map(BytecodeElement::Operation));
+        self
+    }
+
+    /// Appends all [bytecode elements](enum.BytecodeElement.html) of an iterator, i.e.
+    /// commands and labels, onto this bytecode.
+    #[must_use = "returns a new Bytecode with the elements added"]
+    pub fn extend_with_labels(mut self, elements: impl IntoIterator<Item = BytecodeElement>) -> Self {
+        self.elements.extend(elements);
+        self
+    }
+
+    /// Keeps only the elements for which ``pred`` returns true, like ``Vec::retain``.
+    ///
+    /// Fails if a removed [label](type.Label.html) is still the target of a
+    /// [branch address](enum.Operand.html#Operand::BranchAddr) in one of the kept commands.
+    pub fn retain(self, pred: impl Fn(&BytecodeElement) -> bool) -> CompilerResult<Self> {
+        let (kept, removed): (Vec<BytecodeElement>, Vec<BytecodeElement>) =
+            self.elements.into_iter().partition(|element| pred(element));
+
+        for element in removed.iter() {
+            if let BytecodeElement::Label(label) = element {
+                let is_referenced = kept.iter().any(|element| match element {
+                    BytecodeElement::Operation(cmd) => cmd.operands.iter().any(|operand| match operand {
+                        Operand::BranchAddr(token) => token.label == *label,
+                        _ => false
+                    }),
+                    BytecodeElement::Label(_) => false
+                });
+
+                if is_referenced {
+                    return Err(CompilerError::Custom(
+                        format!("Cannot remove label {} since it is still the target of a branch", label)));
+                }
+            }
+        }
+
+        Ok(Bytecode { elements: kept })
+    }
+
+    /// Transforms every [command](struct.Operation.html) with ``f``. Labels are left unchanged.
+    #[must_use = "returns a new Bytecode with the commands transformed"]
+    pub fn map_commands(self, f: impl Fn(Operation) -> Operation) -> Bytecode {
+        Bytecode {
+            elements: self.elements.into_iter().map(|element| match element {
+                BytecodeElement::Operation(cmd) => BytecodeElement::Operation(f(cmd)),
+                label => label
+            }).collect()
+        }
+    }
+
+    /// Returns the element index and a reference of the first [command](struct.Operation.html)
+    /// matching ``pred``.
+    pub fn find_command(&self, pred: impl Fn(&Operation) -> bool) -> Option<(usize, &Operation)> {
+        self.elements.iter().enumerate().find_map(|(index, element)| match element {
+            BytecodeElement::Operation(cmd) if pred(cmd) => Some((index, cmd)),
+            _ => None
+        })
+    }
+
+    /// Like [find_command](struct.Bytecode.html#method.find_command), but returns a mutable reference.
+    pub fn find_command_mut(&mut self, pred: impl Fn(&Operation) -> bool) -> Option<(usize, &mut Operation)> {
+        self.elements.iter_mut().enumerate().find_map(|(index, element)| match element {
+            BytecodeElement::Operation(cmd) if pred(cmd) => Some((index, cmd)),
+            _ => None
+        })
+    }
+
+    /// Returns the element indices and references of all [commands](struct.Operation.html)
+    /// matching ``pred``.
+    pub fn find_all_commands(&self, pred: impl Fn(&Operation) -> bool) -> Vec<(usize, &Operation)> {
+        self.elements.iter().enumerate().filter_map(|(index, element)| match element {
+            BytecodeElement::Operation(cmd) if pred(cmd) => Some((index, cmd)),
+            _ => None
+        }).collect()
+    }
+
+    /// Fails if a [function address](enum.Operand.html#Operand::FunctionAddr) or
+    /// [branch address](enum.Operand.html#Operand::BranchAddr) was not resolved yet,
+    /// which would be encoded as zeros.
+    pub fn assert_no_unresolved_tokens(&self) -> CompilerResult<()> {
+        let is_unresolved = |operand: &&Operand| matches!(operand, Operand::FunctionAddr(_) | Operand::BranchAddr(_));
+
+        match self.find_command(|cmd| cmd.operands.iter().any(|operand| is_unresolved(&operand))) {
+            Some((_, cmd)) => Err(CompilerError::UnresolvedAddress(
+                cmd.operands.iter().find(is_unresolved).map(|operand| operand.to_string()).unwrap_or_default())),
+            None => Ok(())
+        }
+    }
+
+    /// Returns the CRC-32 checksum of the encoded bytecode.
+    pub fn checksum(&self) -> CompilerResult<u32> {
+        Ok(crc32(&self.to_bytes()?))
+    }
+
+    /// Encodes the bytecode and prepends its [checksum](struct.Bytecode.html#method.checksum)
+    /// as 4 byte big-endian header. Use [decode_verified](struct.Bytecode.html#method.decode_verified)
+    /// to decode it.
+    pub fn encode_with_checksum(&self) -> CompilerResult<Vec<u8>> {
+        self.assert_no_unresolved_tokens()?;
+        let bytes = self.to_bytes()?;
+        let mut encoded = crc32(&bytes).to_be_bytes().to_vec();
+        encoded.extend(bytes);
+        Ok(encoded)
+    }
+
+    /// Returns the base64-encoded bytecode with a header of the
+    /// [magic number](constant.BYTECODE_MAGIC.html) and the 2 byte big-endian
+    /// [format version](constant.BYTECODE_VERSION.html).
+    pub fn encode_versioned(&self) -> CompilerResult<String> {
+        self.assert_no_unresolved_tokens()?;
+        let mut encoded = BYTECODE_MAGIC.to_vec();
+        encoded.extend_from_slice(&BYTECODE_VERSION.to_be_bytes());
+        encoded.extend(self.to_bytes()?);
+        Ok(base64::encode(&encoded))
+    }
+
     /// Returns the base64-encoded bytecode as string.
-    pub fn encode_base64(&self) -> String {
-        base64::encode(&self.to_bytes())
+    pub fn encode_base64(&self) -> CompilerResult<String> {
+        self.assert_no_unresolved_tokens()?;
+        Ok(base64::encode(&self.to_bytes()?))
     }
 
-    /// Checks whether the last element is a [return instruction](enum.Instruction.html#Instruction::ReturnBytecodeFunc).
+    /// Checks whether the last element is a [return instruction](struct.Operation.html#method.is_return).
     pub fn last_op_is_return(&self) -> bool {
         match self.elements.last() {
             Some(last_element) => match last_element {
-                BytecodeElement::Operation(cmd) => (cmd.instruction == Instruction::ReturnBytecodeFunc),
+                BytecodeElement::Operation(cmd) => cmd.is_return(),
                 _ => false
             },
             None => false
         }
     }
 
+    /// Returns the number of [commands](struct.Operation.html) in the bytecode, labels are not counted.
+    pub fn instruction_count(&self) -> usize {
+        self.elements.iter().filter(|element| matches!(element, BytecodeElement::Operation(_))).count()
+    }
+
     /// Returns an iterator over all [commands](struct.Operation.html) in the bytecode.
     pub fn commands_iter_mut(&mut self) -> impl std::iter::Iterator<Item = &mut Operation> {
         self.elements.iter_mut().filter_map(|element| match element {
@@ -597,6 +1912,79 @@ impl Bytecode {
             BytecodeElement::Label(_) => None
         })
     }
+
+    /// Calls ``f`` for every [string operand](enum.Operand.html#Operand::String) of all commands, in order.
+    pub fn for_each_string_operand(&self, mut f: impl FnMut(&str)) {
+        for element in self.elements.iter() {
+            if let BytecodeElement::Operation(cmd) = element {
+                for operand in cmd.operands.iter() {
+                    if let Operand::String(string) = operand {
+                        f(string);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns all distinct strings of [string operands](enum.Operand.html#Operand::String)
+    /// in the order of their first occurrence.
+    pub fn collect_string_constants(&self) -> Vec<String> {
+        let mut strings: Vec<String> = vec![];
+        self.for_each_string_operand(|string| {
+            if !strings.iter().any(|known| known == string) {
+                strings.push(string.to_string());
+            }
+        });
+        strings
+    }
+
+    /// Formats the bytecode like its ``Display`` implementation, but indents scoped constructs.
+    ///
+    /// Commands are indented by ``indent`` spaces. Loop bodies, i.e. the elements between a label
+    /// and the last branch jumping back to it, are indented by another ``indent`` spaces per level.
+    /// A [return](struct.Operation.html#method.is_return) ends the current function, so the
+    /// indentation is reset for the function starting after it.
+    pub fn pretty_print(&self, indent: usize) -> String {
+        let mut seen_labels = vec![];
+        let mut loop_ends: HashMap<Label, usize> = HashMap::new();
+        for (index, element) in self.elements.iter().enumerate() {
+            match element {
+                BytecodeElement::Label(label) => seen_labels.push(*label),
+                BytecodeElement::Operation(cmd) => if let Some(target) = cmd.branch_target() {
+                    if seen_labels.contains(&target) {
+                        loop_ends.insert(target, index);
+                    }
+                }
+            }
+        }
+
+        let mut open_loops: Vec<usize> = vec![];
+        let mut output = String::new();
+
+        for (index, element) in self.elements.iter().enumerate() {
+            match element {
+                BytecodeElement::Label(label) => {
+                    output.push_str(&format!("{}label_{}:\n", " ".repeat(indent * open_loops.len()), label));
+                    if let Some(&end) = loop_ends.get(label) {
+                        open_loops.push(end);
+                    }
+                }
+                BytecodeElement::Operation(cmd) => {
+                    output.push_str(&format!("{}{}\n", " ".repeat(indent * (open_loops.len() + 1)), cmd));
+
+                    if let Some(pos) = open_loops.iter().position(|&end| end == index) {
+                        open_loops.truncate(pos);
+                    }
+                    if cmd.is_return() {
+                        open_loops.clear();
+                        output.push('\n');
+                    }
+                }
+            }
+        }
+
+        output
+    }
 }
 
 impl FromIterator<Bytecode> for Bytecode {
@@ -607,9 +1995,31 @@ impl FromIterator<Bytecode> for Bytecode {
     }
 }
 
+impl IntoIterator for Bytecode {
+    type Item = BytecodeElement;
+    type IntoIter = std::vec::IntoIter<BytecodeElement>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Bytecode {
+    type Item = &'a BytecodeElement;
+    type IntoIter = std::slice::Iter<'a, BytecodeElement>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
 impl ToBytes for Bytecode {
-    fn to_bytes(&self) -> Vec<u8> {
-        self.elements.iter().map(|element| element.to_bytes()).flatten().collect()
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        let mut bytes = vec![];
+        for element in self.elements.iter() {
+            bytes.append(&mut element.to_bytes()?);
+        }
+        Ok(bytes)
     }
 
     fn length_in_bytes(&self) -> usize {
@@ -618,6 +2028,33 @@ impl ToBytes for Bytecode {
 }
 
 
+/// Panics if two instructions share the same opcode
+#[cfg(test)]
+fn verify_opcode_uniqueness() {
+    let mut opcodes: HashMap<u8, Instruction> = HashMap::new();
+
+    for instruction in Instruction::variants() {
+        if let Some(other) = opcodes.insert(instruction.to_byte(), instruction.clone()) {
+            panic!("The instructions {:?} and {:?} share the opcode {}", other, instruction, instruction.to_byte());
+        }
+    }
+}
+
+#[test]
+fn test_opcode_uniqueness() {
+    verify_opcode_uniqueness();
+}
+
+#[test]
+fn test_instruction_from_byte() {
+    for instruction in Instruction::variants() {
+        assert_eq!(Instruction::from_byte(instruction.to_byte()), Some(instruction.clone()));
+    }
+
+    assert_eq!(Instruction::from_byte(0), None);
+    assert_eq!(Instruction::from_byte(255), None);
+}
+
 #[test]
 fn test_instrution_to_byte() {
     assert_eq!(Instruction::Add.to_byte(), 100);
@@ -663,40 +2100,149 @@ fn test_bytecode_literal_from_literal() {
 
     assert_eq!(BytecodeLiteral::from_lit(Literal::Number(".1E2".into())).unwrap(),
                 BytecodeLiteral::FloatNum(10.0));
+
+    assert_eq!(BytecodeLiteral::from_lit(Literal::Number("Infinity".into())).unwrap(),
+                BytecodeLiteral::FloatNum(f64::INFINITY));
+
+    assert_eq!(BytecodeLiteral::from_lit(Literal::Number("-Infinity".into())).unwrap(),
+                BytecodeLiteral::FloatNum(f64::NEG_INFINITY));
+
+    assert!(matches!(BytecodeLiteral::from_lit(Literal::Number("NaN".into())).unwrap(),
+                BytecodeLiteral::FloatNum(num) if num.is_nan()));
 }
 
 #[test]
 fn test_encode_string() {
-    assert_eq!(Operand::String("Hello World".into()).to_bytes(),
+    assert_eq!(Operand::String("Hello World".into()).to_bytes().unwrap(),
                vec![0, 11, 72, 101, 108, 108, 111, 32, 87, 111, 114, 108, 100]);
 }
 
+#[test]
+fn test_encode_long_string() {
+    let encoded = Operand::String("a".repeat(300)).to_bytes().unwrap();
+    assert_eq!(encoded[..2], [1, 44]);
+    assert_eq!(encoded.len(), 302);
+
+    assert!(Operand::String("a".repeat(65535)).to_bytes().is_ok());
+    assert!(matches!(Operand::String("a".repeat(65536)).to_bytes(),
+                     Err(CompilerError::StringTooLong(65536))));
+    assert!(matches!(Operand::RegEx("a".repeat(65536), 0).to_bytes(),
+                     Err(CompilerError::StringTooLong(65536))));
+}
+
+#[test]
+fn test_encode_too_long_registers_array() {
+    assert_eq!(Operand::RegistersArray(vec![0; 255]).to_bytes().unwrap().len(), 256);
+    assert!(matches!(Operand::RegistersArray(vec![0; 256]).to_bytes(),
+                     Err(CompilerError::TooManyArguments(256))));
+}
+
+#[test]
+fn test_encode_regex() {
+    assert_eq!(Operand::encode_regex_flags("").unwrap(), 0);
+    assert_eq!(Operand::encode_regex_flags("g").unwrap(), 0b000001);
+    assert_eq!(Operand::encode_regex_flags("gi").unwrap(), 0b000011);
+    assert_eq!(Operand::encode_regex_flags("gimsuy").unwrap(), 0b111111);
+    assert!(Operand::encode_regex_flags("x").is_err());
+
+    assert_eq!(Operand::regex("\\d+".into(), "g").unwrap().to_bytes().unwrap(),
+               vec![0, 3, 92, 100, 43, 1]);
+    assert_eq!(Operand::regex("\\d+".into(), "g").unwrap().length_in_bytes(), 6);
+}
+
 #[test]
 fn test_encode_registers_array() {
-    assert_eq!(Operand::RegistersArray(vec![]).to_bytes(),
+    assert_eq!(Operand::RegistersArray(vec![]).to_bytes().unwrap(),
                vec![0]);
-   assert_eq!(Operand::RegistersArray(vec![1, 2, 200]).to_bytes(),
+   assert_eq!(Operand::RegistersArray(vec![1, 2, 200]).to_bytes().unwrap(),
               vec![3, 1, 2, 200]);
 }
 
+#[test]
+fn test_encode_short_num16() {
+    assert_eq!(Operand::u16(1000).to_bytes().unwrap(), vec![0x03, 0xE8]);
+    assert_eq!(Operand::u16(1000).length_in_bytes(), 2);
+
+    assert_eq!(Operand::from_literal(BytecodeLiteral::IntNumber(255)).unwrap(), Operand::ShortNum(255));
+    assert_eq!(Operand::from_literal(BytecodeLiteral::IntNumber(256)).unwrap(), Operand::ShortNum16(256));
+    assert_eq!(Operand::from_literal(BytecodeLiteral::IntNumber(65535)).unwrap(), Operand::ShortNum16(65535));
+    assert_eq!(Operand::from_literal(BytecodeLiteral::IntNumber(65536)).unwrap(), Operand::LongNum(65536));
+}
+
+#[test]
+fn test_operand_from_number() {
+    assert_eq!(Operand::from_literal(BytecodeLiteral::IntNumber(-1)).unwrap(), Operand::LongNum(-1));
+    assert_eq!(Operand::from_literal(BytecodeLiteral::FloatNum(2.5)).unwrap(), Operand::FloatNum(2.5));
+    assert_eq!(Operand::from_literal(BytecodeLiteral::FloatNum(1.0)).unwrap(), Operand::ShortNum(1));
+    assert_eq!(Operand::from_literal(BytecodeLiteral::FloatNum(1e3)).unwrap(), Operand::ShortNum16(1000));
+    assert_eq!(Operand::from_literal(BytecodeLiteral::FloatNum(-1e6)).unwrap(), Operand::LongNum(-1_000_000));
+    assert_eq!(Operand::from_literal(BytecodeLiteral::IntNumber(1 << 40)).unwrap(),
+               Operand::FloatNum((1i64 << 40) as f64));
+
+    assert_eq!(Operand::from_number(f64::INFINITY), Operand::FloatNum(f64::INFINITY));
+    assert_eq!(Operand::from_number(f64::NEG_INFINITY), Operand::FloatNum(f64::NEG_INFINITY));
+    assert!(matches!(Operand::from_number(f64::NAN), Operand::FloatNum(num) if num.is_nan()));
+    assert!(matches!(Operand::from_number(-0.0), Operand::FloatNum(num) if num.is_sign_negative()));
+}
+
+#[test]
+fn test_encode_special_floats() {
+    assert_eq!(Operand::FloatNum(f64::NAN).to_bytes().unwrap(),
+                vec![0x7f, 0xf8, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(Operand::FloatNum(f64::INFINITY).to_bytes().unwrap(),
+                vec![0x7f, 0xf0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(Operand::FloatNum(f64::NEG_INFINITY).to_bytes().unwrap(),
+                vec![0xff, 0xf0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_checksum() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+    let bytecode = Bytecode::new().add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(10)]));
+    assert_eq!(bytecode.checksum().unwrap(), crc32(&[2, 0, 10]));
+
+    let encoded = bytecode.encode_with_checksum().unwrap();
+    assert_eq!(encoded[..4], bytecode.checksum().unwrap().to_be_bytes());
+    assert_eq!(encoded[4..], [2, 0, 10]);
+}
+
+#[test]
+fn test_assert_no_unresolved_tokens() {
+    let resolved = Bytecode::new().add(Operation::new(Instruction::Jump, vec![Operand::LongNum(3)]));
+    assert!(resolved.assert_no_unresolved_tokens().is_ok());
+
+    let unresolved_branch = Bytecode::new()
+        .add_label(0)
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(0)]));
+    assert!(matches!(unresolved_branch.assert_no_unresolved_tokens(), Err(CompilerError::UnresolvedAddress(_))));
+    assert!(unresolved_branch.encode_base64().is_err());
+
+    let unresolved_func = Bytecode::new()
+        .add(Operation::new(Instruction::CallBytecodeFunc, vec![Operand::function_addr("f".into()),
+                                                               Operand::Reg(0), Operand::RegistersArray(vec![])]));
+    assert!(matches!(unresolved_func.encode_with_checksum(), Err(CompilerError::UnresolvedAddress(_))));
+}
+
 #[test]
 fn test_encode_long_num() {
-    assert_eq!(Operand::LongNum(1_234_567_891).to_bytes(),
+    assert_eq!(Operand::LongNum(1_234_567_891).to_bytes().unwrap(),
                 vec![0x49, 0x96, 0x02, 0xD3]);
 
-    assert_eq!(Operand::LongNum(-1_234_567_891 as i32).to_bytes(),
+    assert_eq!(Operand::LongNum(-1_234_567_891 as i32).to_bytes().unwrap(),
                 vec![0xB6, 0x69, 0xFD, 0x2D])
 }
 
 #[test]
 fn test_encode_float_num() {
-    assert_eq!(Operand::FloatNum(0.12345).to_bytes(),
+    assert_eq!(Operand::FloatNum(0.12345).to_bytes().unwrap(),
                 vec![63, 191, 154, 107, 80, 176, 242, 124]);
 
-    assert_eq!(Operand::FloatNum(0.5).to_bytes(),
+    assert_eq!(Operand::FloatNum(0.5).to_bytes().unwrap(),
                 vec![0x3f, 0xe0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
 
-    assert_eq!(Operand::FloatNum(-1.1234).to_bytes(),
+    assert_eq!(Operand::FloatNum(-1.1234).to_bytes().unwrap(),
                 vec![191, 241, 249, 114, 71, 69, 56, 239])
 }
 
@@ -708,13 +2254,13 @@ fn test_command() {
             Operand::Reg(150),
             Operand::Reg(151),
         ]
-    }.to_bytes(),
+    }.to_bytes().unwrap(),
     vec![100, 150, 151]);
 }
 
 #[test]
 fn test_bytecode_to_bytes() {
-    assert_eq!(Bytecode::new().to_bytes().len(), 0);
+    assert_eq!(Bytecode::new().to_bytes().unwrap().len(), 0);
     assert_eq!(Bytecode{ elements: vec![
         BytecodeElement::Operation(Operation{
             instruction: Instruction::LoadNum,
@@ -738,7 +2284,137 @@ fn test_bytecode_to_bytes() {
             ]
         }),
         ]
-    }.to_bytes(), vec![2, 151, 2, 2, 150, 3,101, 150, 151]);
+    }.to_bytes().unwrap(), vec![2, 151, 2, 2, 150, 3,101, 150, 151]);
+}
+
+#[test]
+fn test_bytecode_extend() {
+    let exit = Operation::new(Instruction::Exit, vec![]);
+    let load = Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(1)]);
+
+    assert_eq!(Bytecode::new().add(exit.clone()).extend(vec![load.clone(), exit.clone()]),
+               Bytecode::new().add(exit.clone()).add(load.clone()).add(exit.clone()));
+
+    assert_eq!(Bytecode::new().extend(std::iter::empty()), Bytecode::new());
+
+    assert_eq!(Bytecode::new().extend_with_labels(vec![BytecodeElement::Label(1),
+                                                      BytecodeElement::Operation(load.clone())]),
+               Bytecode::new().add_label(1).add(load));
+}
+
+#[test]
+fn test_bytecode_into_iter() {
+    let bytecode = Bytecode::new()
+        .add_label(0)
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    let mut labels = 0;
+    for element in &bytecode {
+        if let BytecodeElement::Label(_) = element {
+            labels += 1;
+        }
+    }
+    assert_eq!(labels, 1);
+
+    assert_eq!(bytecode.clone().into_iter().collect::<Vec<_>>(), bytecode.elements);
+    assert_eq!(bytecode.into_iter().filter(|element| matches!(element, BytecodeElement::Operation(_))).count(), 1);
+}
+
+#[test]
+fn test_bytecode_retain() {
+    let jump = Operation::new(Instruction::Jump, vec![Operand::branch_addr(1)]);
+    let nop = Operation::new(Instruction::Nop, vec![]);
+    let bytecode = Bytecode::new()
+        .add(nop.clone())
+        .add(jump.clone())
+        .add_label(1)
+        .add(nop.clone());
+
+    assert_eq!(bytecode.clone().retain(|element| match element {
+        BytecodeElement::Operation(cmd) => cmd.instruction != Instruction::Nop,
+        BytecodeElement::Label(_) => true
+    }).unwrap(), Bytecode::new().add(jump).add_label(1));
+
+    assert!(bytecode.clone().retain(|element| matches!(element, BytecodeElement::Operation(_))).is_err());
+
+    assert_eq!(bytecode.retain(|element| match element {
+        BytecodeElement::Operation(cmd) => cmd.instruction == Instruction::Nop,
+        BytecodeElement::Label(_) => false
+    }).unwrap(), Bytecode::new().add(nop.clone()).add(nop));
+}
+
+#[test]
+fn test_bytecode_map_commands() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(1)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    assert_eq!(bytecode.map_commands(|cmd| match cmd.instruction {
+        Instruction::Exit => Operation::new(Instruction::Nop, vec![]),
+        _ => cmd
+    }), Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(1)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::Nop, vec![])));
+}
+
+#[test]
+fn test_bytecode_find_command() {
+    let mut bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(1)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::Exit),
+               Some((3, &Operation::new(Instruction::Exit, vec![]))));
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::Nop), None);
+
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::LoadNum)
+                    .iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+               vec![0, 2]);
+
+    if let Some((_, cmd)) = bytecode.find_command_mut(|cmd| cmd.instruction == Instruction::Exit) {
+        cmd.instruction = Instruction::Nop;
+    }
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::Exit).is_none());
+}
+
+#[test]
+fn test_command_branch_target() {
+    let jump = Operation::new(Instruction::Jump, vec![Operand::branch_addr(3)]);
+    assert!(jump.is_branch());
+    assert_eq!(jump.branch_target(), Some(3));
+
+    let jump_cond = Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(0), Operand::branch_addr(4)]);
+    assert!(jump_cond.is_branch());
+    assert_eq!(jump_cond.branch_target(), Some(4));
+
+    let add = Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(1), Operand::Reg(2)]);
+    assert!(!add.is_branch());
+    assert_eq!(add.branch_target(), None);
+
+    let try_op = Operation::new(Instruction::Try, vec![Operand::branch_addr(1), Operand::branch_addr(2)]);
+    assert!(!try_op.is_branch());
+    assert_eq!(try_op.branch_target(), None);
+}
+
+#[test]
+fn test_command_is_call_and_return() {
+    assert!(Operation::new(Instruction::CallFunc, vec![]).is_call());
+    assert!(Operation::new(Instruction::CallBytecodeFunc, vec![]).is_call());
+    assert!(Operation::new(Instruction::CallApply, vec![]).is_call());
+    assert!(Operation::new(Instruction::EvalDirect, vec![]).is_call());
+    assert!(Operation::new(Instruction::EvalIndirect, vec![]).is_call());
+    assert!(!Operation::new(Instruction::BytecodeFuncCallback, vec![]).is_call());
+    assert!(!Operation::new(Instruction::ReturnBytecodeFunc, vec![]).is_call());
+
+    assert!(Operation::new(Instruction::ReturnBytecodeFunc, vec![]).is_return());
+    assert!(Operation::new(Instruction::Exit, vec![]).is_return());
+    assert!(!Operation::new(Instruction::Halt, vec![]).is_return());
+    assert!(!Operation::new(Instruction::Jump, vec![]).is_return());
+    assert!(!Operation::new(Instruction::CallFunc, vec![]).is_return());
 }
 
 #[test]
@@ -751,6 +2427,7 @@ fn test_last_op_is_return() {
     assert_eq!(Bytecode::new().add(
             Operation::new(Instruction::Copy, vec![Operand::Reg(0), Operand::Reg(1)])
         ).last_op_is_return(), false);
+    assert_eq!(Bytecode::new().add(Operation::new(Instruction::Exit, vec![])).last_op_is_return(), true);
 }
 
 #[test]
@@ -765,3 +2442,36 @@ fn test_format_print() {
                      LoadString Reg(2) String(\"String\")\n\
                      LoadArray Reg(0) RegArray([255, 1, 2])\n".to_string());
 }
+
+#[test]
+fn test_pretty_print() {
+    let load = Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(1)]);
+    let bytecode = Bytecode::new()
+        .add(load.clone())
+        .add_label(0)
+        .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(0), Operand::branch_addr(2)]))
+        .add_label(1)
+        .add(load.clone())
+        .add(Operation::new(Instruction::JumpCond, vec![Operand::Reg(0), Operand::branch_addr(1)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(0)]))
+        .add_label(2)
+        .add(Operation::new(Instruction::Exit, vec![]))
+        .add(load)
+        .add(Operation::new(Instruction::ReturnBytecodeFunc, vec![Operand::Reg(0), Operand::RegistersArray(vec![])]));
+
+    assert_eq!(bytecode.pretty_print(2), "  LoadNum Reg(0) ShortNum(1)\n\
+                                          label_0:\n\
+                                          \x20   JumpCondNeg Reg(0) BranchAddr(LabelAddrToken { label: 2 })\n\
+                                          \x20 label_1:\n\
+                                          \x20     LoadNum Reg(0) ShortNum(1)\n\
+                                          \x20     JumpCond Reg(0) BranchAddr(LabelAddrToken { label: 1 })\n\
+                                          \x20   Jump BranchAddr(LabelAddrToken { label: 0 })\n\
+                                          label_2:\n\
+                                          \x20 Exit\n\
+                                          \n\
+                                          \x20 LoadNum Reg(0) ShortNum(1)\n\
+                                          \x20 ReturnBytecodeFunc Reg(0) RegArray([])\n\
+                                          \n");
+
+    assert_eq!(Bytecode::new().pretty_print(4), "");
+}
@@ -0,0 +1,477 @@
+use crate::bytecode::{Bytecode, BytecodeElement, Operation, Instruction, Operand, Label};
+
+use std::collections::HashMap;
+
+
+/// A sequence of [commands](../bytecode/struct.Operation.html) that is always executed from
+/// its start to its end
+///
+/// ``successors`` and ``predecessors`` hold the ids of the connected blocks. The id of a block
+/// is its index in the [control-flow graph](struct.ControlFlowGraph.html).
+#[derive(Debug, PartialEq, Clone)]
+pub struct BasicBlock {
+    pub id: usize,
+    /// The labels pointing to the start of this block
+    pub labels: Vec<Label>,
+    pub commands: Vec<Operation>,
+    pub successors: Vec<usize>,
+    pub predecessors: Vec<usize>
+}
+
+impl BasicBlock {
+    fn new(id: usize) -> Self {
+        BasicBlock {
+            id,
+            labels: vec![],
+            commands: vec![],
+            successors: vec![],
+            predecessors: vec![]
+        }
+    }
+
+    /// Checks whether the execution starts with this block.
+    pub fn is_entry(&self) -> bool {
+        self.id == 0
+    }
+
+    /// Checks whether the execution cannot continue after this block.
+    pub fn is_exit(&self) -> bool {
+        self.successors.is_empty()
+    }
+
+    /// Returns the block as bytecode, i.e. its labels followed by its commands.
+    pub fn to_bytecode(&self) -> Bytecode {
+        Bytecode {
+            elements: self.labels.iter().map(|label| BytecodeElement::Label(*label))
+                .chain(self.commands.iter().cloned().map(BytecodeElement::Operation))
+                .collect()
+        }
+    }
+}
+
+impl std::fmt::Display for BasicBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "block_{}", self.id)?;
+        for label in self.labels.iter() {
+            write!(f, " label_{}", label)?;
+        }
+        writeln!(f, " (predecessors: {:?}, successors: {:?}):", self.predecessors, self.successors)?;
+        for cmd in self.commands.iter() {
+            writeln!(f, "  {}", cmd)?;
+        }
+        Ok(())
+    }
+}
+
+/// The control-flow graph of a bytecode
+///
+/// A block starts with its labels, if there are any, and ends with the first branch,
+/// return or throw, or right before the next label. The blocks are stored in the order
+/// in which they appear in the bytecode, thus the first block is the entry.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>
+}
+
+/// Returns whether an operation is the last one of its block
+fn ends_block(op: &Operation) -> bool {
+    op.is_branch() || op.is_return() || op.instruction == Instruction::Throw
+}
+
+/// Returns whether the execution may continue with the next block after an operation
+fn falls_through(op: &Operation) -> bool {
+    !(op.is_return() || matches!(op.instruction, Instruction::Jump | Instruction::Throw))
+}
+
+impl ControlFlowGraph {
+    /// Returns the id of the block starting with the given label
+    pub fn block_of_label(&self, label: Label) -> Option<usize> {
+        self.blocks.iter().position(|block| block.labels.contains(&label))
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize) {
+        if !self.blocks[from].successors.contains(&to) {
+            self.blocks[from].successors.push(to);
+            self.blocks[to].predecessors.push(from);
+        }
+    }
+}
+
+/// Escapes a string to be used within a double-quoted Graphviz label
+fn escape_dot(string: &str) -> String {
+    string.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Returns the label of an edge, i.e. the condition leading to the successor if the block ends with
+/// a conditional branch
+fn edge_label(cfg: &ControlFlowGraph, block: &BasicBlock, successor: usize) -> Option<&'static str> {
+    let last = block.commands.last()?;
+    let (taken, not_taken) = match last.instruction {
+        Instruction::JumpCond => ("true", "false"),
+        Instruction::JumpCondNeg => ("false", "true"),
+        _ => { return None; }
+    };
+    let target = last.branch_target().and_then(|label| cfg.block_of_label(label));
+
+    match (target == Some(successor), block.id + 1 == successor) {
+        (true, false) => Some(taken),
+        (false, true) => Some(not_taken),
+        _ => None
+    }
+}
+
+impl std::fmt::Display for ControlFlowGraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for block in self.blocks.iter() {
+            writeln!(f, "{}", block)?;
+        }
+        Ok(())
+    }
+}
+
+impl Bytecode {
+    /// Splits the bytecode into basic blocks and links them.
+    ///
+    /// Besides the jumps and the fall-through edges, the handlers of a ``Try`` operation
+    /// are added as successors of the block containing it.
+    pub fn build_cfg(&self) -> ControlFlowGraph {
+        let mut blocks = vec![];
+        let mut current = BasicBlock::new(0);
+        let mut label_blocks: HashMap<Label, usize> = HashMap::new();
+
+        for element in self.elements.iter() {
+            match element {
+                BytecodeElement::Label(label) => {
+                    if !current.commands.is_empty() {
+                        let next = BasicBlock::new(current.id + 1);
+                        blocks.push(std::mem::replace(&mut current, next));
+                    }
+                    label_blocks.insert(*label, current.id);
+                    current.labels.push(*label);
+                },
+                BytecodeElement::Operation(op) => {
+                    current.commands.push(op.clone());
+                    if ends_block(op) {
+                        let next = BasicBlock::new(current.id + 1);
+                        blocks.push(std::mem::replace(&mut current, next));
+                    }
+                }
+            }
+        }
+
+        if !current.labels.is_empty() || !current.commands.is_empty() {
+            blocks.push(current);
+        }
+
+        let mut cfg = ControlFlowGraph { blocks };
+
+        for id in 0..cfg.blocks.len() {
+            let mut targets = vec![];
+            let mut fall_through = true;
+
+            for op in cfg.blocks[id].commands.iter() {
+                if op.is_branch() || op.instruction == Instruction::Try {
+                    targets.extend(op.operands.iter().filter_map(|operand| match operand {
+                        Operand::BranchAddr(token) => label_blocks.get(&token.label).cloned(),
+                        _ => None
+                    }));
+                }
+                fall_through = falls_through(op);
+            }
+
+            for target in targets {
+                cfg.add_edge(id, target);
+            }
+
+            if fall_through && id + 1 < cfg.blocks.len() {
+                cfg.add_edge(id, id + 1);
+            }
+        }
+
+        cfg
+    }
+
+    /// Returns the [control-flow graph](struct.ControlFlowGraph.html) in the dot format of Graphviz.
+    ///
+    /// Each basic block is a node listing its commands, the edges connect it with its successors.
+    /// The edges leaving a conditional branch are labeled ``true`` and ``false``. Like
+    /// [build_cfg](struct.Bytecode.html#method.build_cfg), this must run before the label addresses
+    /// are finalized, since the branch targets are resolved by their labels.
+    pub fn to_dot_graph(&self) -> String {
+        let cfg = self.build_cfg();
+        let mut dot = String::from("digraph cfg {\n    node [shape=box, fontname=\"monospace\"];\n");
+
+        for block in cfg.blocks.iter() {
+            let mut label = format!("block_{}", block.id);
+            for block_label in block.labels.iter() {
+                label.push_str(&format!(" label_{}", block_label));
+            }
+            label.push_str("\\l");
+            for cmd in block.commands.iter() {
+                label.push_str(&format!("  {}\\l", escape_dot(&cmd.to_string())));
+            }
+            dot.push_str(&format!("    block_{} [label=\"{}\"];\n", block.id, label));
+        }
+
+        for block in cfg.blocks.iter() {
+            for successor in block.successors.iter() {
+                match edge_label(&cfg, block, *successor) {
+                    Some(label) => dot.push_str(&format!("    block_{} -> block_{} [label=\"{}\"];\n",
+                                                         block.id, successor, label)),
+                    None => dot.push_str(&format!("    block_{} -> block_{};\n", block.id, successor))
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Orders the blocks of a control-flow graph for emission.
+    ///
+    /// The blocks are sorted topologically by a depth-first search from the entry, back edges
+    /// are ignored. Among the successors of a block, the fall-through block is preferred to be
+    /// placed right after it. Blocks not reachable from the entry, e.g. functions only reachable
+    /// by calls, are sorted afterwards in the same way, starting with the first of them.
+    pub fn topological_sort_blocks(cfg: &ControlFlowGraph) -> Vec<usize> {
+        fn visit(cfg: &ControlFlowGraph, id: usize, visited: &mut Vec<bool>, postorder: &mut Vec<usize>) {
+            visited[id] = true;
+
+            let block = &cfg.blocks[id];
+            let mut successors = block.successors.clone();
+            // The successor visited last ends up first in the reverse postorder
+            successors.sort_by_key(|successor| *successor == id + 1);
+
+            for successor in successors {
+                if !visited[successor] {
+                    visit(cfg, successor, visited, postorder);
+                }
+            }
+            postorder.push(id);
+        }
+
+        let mut visited = vec![false; cfg.blocks.len()];
+        let mut order = vec![];
+
+        for id in 0..cfg.blocks.len() {
+            if !visited[id] {
+                let mut postorder = vec![];
+                visit(cfg, id, &mut visited, &mut postorder);
+                order.extend(postorder.into_iter().rev());
+            }
+        }
+
+        order
+    }
+
+    /// Emits the blocks of a control-flow graph in the given order.
+    ///
+    /// If a block falls through to a block which is not placed right after it, a ``Jump`` to it
+    /// is added, creating a new label if necessary. Jumps to the directly following block are
+    /// removed. Since branch targets are resolved by their labels, this must run before the
+    /// label addresses are finalized.
+    pub fn reorder_by(order: &[usize], cfg: &ControlFlowGraph) -> Bytecode {
+        let mut next_label = cfg.blocks.iter()
+            .flat_map(|block| block.labels.iter())
+            .max()
+            .map_or(0, |label| label + 1);
+
+        let needs_jump = |position: usize, id: usize| {
+            cfg.blocks[id].commands.last().is_none_or(falls_through) &&
+                id + 1 < cfg.blocks.len() &&
+                order.get(position + 1) != Some(&(id + 1))
+        };
+
+        let mut blocks = cfg.blocks.clone();
+        for (position, id) in order.iter().enumerate() {
+            if needs_jump(position, *id) && blocks[*id + 1].labels.is_empty() {
+                blocks[*id + 1].labels.push(next_label);
+                next_label += 1;
+            }
+        }
+
+        let mut bytecode = Bytecode::new();
+
+        for (position, id) in order.iter().enumerate() {
+            let block = &blocks[*id];
+            let next = order.get(position + 1).map(|next_id| &blocks[*next_id]);
+            let mut commands = block.commands.clone();
+
+            if let (Some(last), Some(next)) = (commands.last(), next) {
+                if last.instruction == Instruction::Jump &&
+                    last.branch_target().is_some_and(|label| next.labels.contains(&label)) {
+                    commands.pop();
+                }
+            }
+
+            bytecode = bytecode.extend_with_labels(block.labels.iter().map(|label| BytecodeElement::Label(*label)))
+                               .extend(commands);
+
+            if needs_jump(position, *id) {
+                bytecode = bytecode.add(Operation::new(Instruction::Jump,
+                                                       vec![Operand::branch_addr(blocks[*id + 1].labels[0])]));
+            }
+        }
+
+        bytecode
+    }
+}
+
+
+#[test]
+fn test_build_cfg_straight_line() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::Copy, vec![]))
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    let cfg = bytecode.build_cfg();
+    assert_eq!(cfg.blocks.len(), 1);
+    assert_eq!(cfg.blocks[0].to_bytecode(), bytecode);
+    assert!(cfg.blocks[0].is_entry());
+    assert!(cfg.blocks[0].is_exit());
+}
+
+#[test]
+fn test_build_cfg_if_else() {
+    // if (r0) { r1 = r2 } else { r1 = r3 }; exit
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(0), Operand::branch_addr(1)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(2)]))
+        .add_label(1)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(3)]))
+        .add_label(2)
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    let cfg = bytecode.build_cfg();
+    assert_eq!(cfg.blocks.len(), 4);
+    assert_eq!(cfg.block_of_label(1), Some(2));
+    assert_eq!(cfg.block_of_label(2), Some(3));
+    assert_eq!(cfg.blocks.iter().map(|block| block.successors.clone()).collect::<Vec<_>>(),
+               vec![vec![2, 1], vec![3], vec![3], vec![]]);
+    assert_eq!(cfg.blocks.iter().map(|block| block.predecessors.clone()).collect::<Vec<_>>(),
+               vec![vec![], vec![0], vec![0], vec![1, 2]]);
+    assert_eq!(cfg.blocks.iter().filter(|block| block.is_exit()).count(), 1);
+    assert!(!cfg.blocks[1].is_entry());
+}
+
+#[test]
+fn test_to_dot_graph() {
+    use crate::compiler::BytecodeCompiler;
+    use crate::jshelper::{JSAst, JSSourceCode};
+
+    // The branch targets are resolved by their labels, thus the addresses must not be finalized yet
+    let ast = JSAst::parse(&JSSourceCode::from_str("var a = true; var b; if (a) { b = a; } else { b = 2; }")).unwrap();
+    let mut compiler = BytecodeCompiler::new();
+    let bytecode = match ast.ast {
+        resast::Program::Script(parts) => parts.iter()
+            .map(|part| compiler.compile_program_part(part))
+            .collect::<Result<Bytecode, _>>().unwrap(),
+        resast::Program::Mod(_) => unreachable!()
+    };
+
+    let dot = bytecode.to_dot_graph();
+    assert!(dot.starts_with("digraph cfg {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert_eq!(dot.lines().filter(|line| line.contains("[label=\"block_")).count(), 4);
+    assert!(dot.contains("    block_1 [label=\"block_1\\l  Copy Reg(1) Reg(0)\\l  Jump BranchAddr(LabelAddrToken { label: 1 })\\l\"];\n"));
+    assert!(dot.contains("    block_2 [label=\"block_2 label_0\\l  LoadNum Reg(1) ShortNum(2)\\l\"];\n"));
+
+    // The diamond: the condition branches to both arms, which join again
+    let edges: Vec<&str> = dot.lines().filter(|line| line.contains("->")).collect();
+    assert_eq!(edges, vec![
+        "    block_0 -> block_2 [label=\"false\"];",
+        "    block_0 -> block_1 [label=\"true\"];",
+        "    block_1 -> block_3;",
+        "    block_2 -> block_3;"
+    ]);
+
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(0), Operand::String("say \"hi\"".into())]));
+    assert!(bytecode.to_dot_graph().contains(r#"LoadString Reg(0) String(\"say \"hi\"\")\l"#));
+}
+
+#[test]
+fn test_build_cfg_loop() {
+    let bytecode = Bytecode::new()
+        .add_label(0)
+        .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(0), Operand::branch_addr(1)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(0)]))
+        .add_label(1);
+
+    let cfg = bytecode.build_cfg();
+    assert_eq!(cfg.blocks.len(), 3);
+    assert_eq!(cfg.blocks.iter().map(|block| block.successors.clone()).collect::<Vec<_>>(),
+               vec![vec![2, 1], vec![0], vec![]]);
+    assert_eq!(cfg.blocks.iter().map(|block| block.predecessors.clone()).collect::<Vec<_>>(),
+               vec![vec![1], vec![0], vec![0]]);
+}
+
+#[test]
+fn test_basic_block_display() {
+    let cfg = Bytecode::new()
+        .add_label(3)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::Exit, vec![]))
+        .build_cfg();
+
+    assert_eq!(format!("{}", cfg.blocks[0]),
+               "block_0 label_3 (predecessors: [], successors: []):\n  Copy Reg(1) Reg(2)\n  Exit\n");
+}
+
+#[test]
+fn test_topological_sort_blocks() {
+    // if (r0) { r1 = r2 } else { r1 = r3 } with the else branch first
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::JumpCond, vec![Operand::Reg(0), Operand::branch_addr(1)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(3)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(2)]))
+        .add_label(1)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(2)]))
+        .add_label(2)
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    let cfg = bytecode.build_cfg();
+    assert_eq!(Bytecode::topological_sort_blocks(&cfg), vec![0, 1, 2, 3]);
+
+    // The original order is kept, so is the bytecode
+    assert_eq!(Bytecode::reorder_by(&[0, 1, 2, 3], &cfg), bytecode);
+
+    // Moving the else branch to the end requires a jump and a new label for the fall-through
+    assert_eq!(Bytecode::reorder_by(&[0, 2, 3, 1], &cfg), Bytecode::new()
+        .add(Operation::new(Instruction::JumpCond, vec![Operand::Reg(0), Operand::branch_addr(1)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(3)]))
+        .add_label(1)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(2)]))
+        .add_label(2)
+        .add(Operation::new(Instruction::Exit, vec![]))
+        .add_label(3)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(3)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(2)])));
+}
+
+#[test]
+fn test_topological_sort_blocks_with_back_edge() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(1)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(2)]))
+        .add_label(1)
+        .add(Operation::new(Instruction::JumpCond, vec![Operand::Reg(0), Operand::branch_addr(0)]))
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    let cfg = bytecode.build_cfg();
+    let order = Bytecode::topological_sort_blocks(&cfg);
+    assert_eq!(order, vec![0, 2, 3, 1]);
+
+    // The jump of the now empty entry block is removed, the loop body jumps back to the condition
+
+    assert_eq!(Bytecode::reorder_by(&order, &cfg), Bytecode::new()
+        .add_label(1)
+        .add(Operation::new(Instruction::JumpCond, vec![Operand::Reg(0), Operand::branch_addr(0)]))
+        .add(Operation::new(Instruction::Exit, vec![]))
+        .add_label(0)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(1)])));
+}
@@ -1,9 +1,10 @@
-use crate::error::{CompilerError, CompilerResult};
+use crate::error::{CompilerError, CompilerResult, CompilerWarning};
+use crate::config::CompilerConfig;
 use crate::jshelper::{JSSourceCode, JSAst};
 use crate::bytecode::{Bytecode, BytecodeResult};
 use crate::scope::*;
 use crate::bytecode::{*};
-use crate::instruction_set::{InstructionSet, CommonLiteral, ReservedeRegister};
+use crate::instruction_set::{InstructionSet, CommonLiteral, ReservedeRegister, BuiltinCollection, BuiltinType};
 
 use resast::prelude::*;
 use std::borrow::Borrow;
@@ -20,16 +21,18 @@ struct BytecodeFunction
     // to allow functions using callbacks to themselves.
     bytecode: Option<Bytecode>,
     arguments: Vec<Register>,
+    rest_argument: Option<Register>,
     // Same explanation as above for 'bytecode'
     used_decls: Option<Vec<Register>>,
 }
 
 impl BytecodeFunction {
-    pub fn new_phantom(ident: Identifier, arg_regs: Vec<Register>) -> Self {
+    pub fn new_phantom(ident: Identifier, arg_regs: Vec<Register>, rest_arg_reg: Option<Register>) -> Self {
         BytecodeFunction {
             ident: ident,
             bytecode: None,
             arguments: arg_regs,
+            rest_argument: rest_arg_reg,
             used_decls: None,
         }
     }
@@ -39,6 +42,7 @@ impl BytecodeFunction {
             ident: phantom.ident,
             bytecode: Some(bytecode),
             arguments: phantom.arguments,
+            rest_argument: phantom.rest_argument,
             used_decls: Some(used_decls),
         }
     }
@@ -47,12 +51,14 @@ impl BytecodeFunction {
 #[derive(Clone)]
 struct LoopBlock {
     start_label: Label,
-    end_label: Label
+    end_label: Label,
+    // The number of scopes entered by EnterScope at the start of the loop
+    scope_depth: usize
 }
 
 impl LoopBlock {
-    pub fn new(start_label: Label, end_label: Label) -> Self {
-        LoopBlock {start_label, end_label}
+    pub fn new(start_label: Label, end_label: Label, scope_depth: usize) -> Self {
+        LoopBlock {start_label, end_label, scope_depth}
     }
 
     pub fn start_label(&self) -> Label {
@@ -91,8 +97,8 @@ impl LabelGenerator {
         counter
     }
 
-    pub fn generate_loop_label_block(&mut self) -> Rc<LoopBlock> {
-        let block = Rc::new(LoopBlock::new(self.generate_label(), self.generate_label()));
+    pub fn generate_loop_label_block(&mut self, scope_depth: usize) -> Rc<LoopBlock> {
+        let block = Rc::new(LoopBlock::new(self.generate_label(), self.generate_label(), scope_depth));
         self.loop_blocks.push(block.clone());
 
         if let Some(current_js_label) = &self.current_js_label {
@@ -136,6 +142,28 @@ impl DeclDepencies {
     }
 }
 
+/// Returns the identifier and the default value of a function parameter
+fn function_param(param: &FunctionArg) -> CompilerResult<(&Identifier, Option<&Expr>)> {
+    let pat = match param {
+        FunctionArg::Expr(Expr::Ident(ident)) => { return Ok((ident, None)); },
+        FunctionArg::Expr(Expr::Assignment(assign)) if assign.operator == AssignmentOperator::Equal => match &assign.left {
+            AssignmentLeft::Expr(expr) => match expr.borrow() {
+                Expr::Ident(ident) => { return Ok((ident, Some(assign.right.borrow()))); },
+                _ => None
+            },
+            AssignmentLeft::Pat(pat) => Some((pat, Some(assign.right.borrow())))
+        },
+        FunctionArg::Pat(Pat::Assignment(assign)) => Some((assign.left.borrow(), Some(assign.right.borrow()))),
+        FunctionArg::Pat(pat) => Some((pat, None)),
+        _ => None
+    };
+
+    match pat {
+        Some((Pat::Identifier(ident), default)) => Ok((ident, default)),
+        _ => Err(CompilerError::Custom("Only identifiers are accepted as function arguments".into()))
+    }
+}
+
 /// Compiles JavaScript source code into bytecode.
 ///
 /// ```
@@ -154,7 +182,21 @@ pub struct BytecodeCompiler {
     functions: Vec<BytecodeFunction>,
     isa: InstructionSet,
     label_generator: LabelGenerator,
-    decl_dependencies: DeclDepencies
+    decl_dependencies: DeclDepencies,
+    config: CompilerConfig,
+    warnings: Vec<CompilerWarning>,
+    // Whether the current function (or script) might call eval, which requires tracking scopes at runtime
+    eval_possible: bool,
+    // The number of scopes entered by EnterScope in the current function
+    scope_depth: usize,
+    // The number of try statements around the current statement in the current function
+    try_depth: usize,
+    // The registers of the objects of the 'with' statements around the current statement, innermost last
+    with_objects: Vec<Reg>,
+    // The builtin collections 'const' declarations are initialized with, by their register
+    const_collections: HashMap<Reg, BuiltinCollection>,
+    // The builtin types of the values 'const' declarations are initialized with, by their register
+    const_types: HashMap<Reg, BuiltinType>
 }
 
 // fn testy<'xzy>(s: &'xzy mut BytecodeCompiler<'xzy>, pp: &ProgramPart) -> BytecodeResult {
@@ -165,6 +207,11 @@ impl BytecodeCompiler {
 
     /// Creates a new bytecode compiler
     pub fn new() -> Self {
+        BytecodeCompiler::with_config(CompilerConfig::default())
+    }
+
+    /// Creates a new bytecode compiler using the given [configuration](../config/struct.CompilerConfig.html)
+    pub fn with_config(config: CompilerConfig) -> Self {
         let mut scopes = Scopes::new();
         let isa = InstructionSet::default(scopes.current_scope_mut().unwrap());
         isa.common_lits().add_to_lit_cache(&mut scopes).unwrap();
@@ -174,7 +221,15 @@ impl BytecodeCompiler {
             functions: vec![],
             isa: isa,
             label_generator: LabelGenerator::new(),
-            decl_dependencies: DeclDepencies::new()
+            decl_dependencies: DeclDepencies::new(),
+            config,
+            warnings: vec![],
+            eval_possible: false,
+            scope_depth: 0,
+            try_depth: 0,
+            with_objects: vec![],
+            const_collections: HashMap::new(),
+            const_types: HashMap::new()
         }
     }
 
@@ -198,6 +253,19 @@ impl BytecodeCompiler {
         &self.decl_dependencies
     }
 
+    /// Returns the warnings of all compilations so far, e.g. about deprecated syntax
+    pub fn warnings(&self) -> &[CompilerWarning] {
+        &self.warnings
+    }
+
+    /// Returns the registers of the identifiers declared so far.
+    ///
+    /// After a compilation, the table holds the top-level declarations. The registers of the
+    /// [declaration dependencies](struct.BytecodeCompiler.html#method.decl_dependencies) are not part of it.
+    pub fn symbol_table(&self) -> SymbolTable {
+        self.scopes.symbol_table()
+    }
+
     /// Compiles the provided JavaScript code into bytecode.
     ///
     /// ```
@@ -214,6 +282,7 @@ impl BytecodeCompiler {
         let mut bytecode = match ast.ast {
             resast::Program::Mod(_) => Err(CompilerError::are_unsupported("ES6 modules")),
             resast::Program::Script(s) => {
+                self.eval_possible = contains_direct_eval(&s);
                 s.iter().map(|part| self.compile_program_part(part)).collect::<BytecodeResult>()
             },
         }?;
@@ -255,8 +324,33 @@ impl BytecodeCompiler {
         decls.iter().map(|decl| {
             match &decl.id {
                 Pat::Identifier(ident) => {
+                    let builtin_type = match (kind, &decl.init) {
+                        (VariableKind::Const, Some(init)) => self.known_type(init),
+                        _ => None
+                    };
                     let reg = self.scopes.add_decl(ident.to_string(), DeclarationType::Variable(MyVariableKind::from(kind)))?;
+
+                    // A const can not be reassigned, so it stays the collection it is initialized with
+                    let collection = match (kind, &decl.init) {
+                        (VariableKind::Const, Some(Expr::New(new))) => self.new_collection(new),
+                        (VariableKind::Const, Some(Expr::Array(_))) => Some(BuiltinCollection::Array),
+                        _ => None
+                    };
+                    match collection {
+                        Some(collection) => { self.const_collections.insert(reg, collection); },
+                        None => { self.const_collections.remove(&reg); }
+                    }
+                    match builtin_type {
+                        Some(builtin_type) => { self.const_types.insert(reg, builtin_type); },
+                        None => { self.const_types.remove(&reg); }
+                    }
+
                     match &decl.init {
+                        // The initializer of a 'var' is an assignment, which might assign a property of a 'with' object
+                        Some(expr) if *kind == VariableKind::Var && !self.with_objects.is_empty() => {
+                            let (value_bc, value_reg) = self.maybe_compile_expr(expr, None)?;
+                            Ok(value_bc.add_bytecode(self.compile_with_name_write(ident, value_reg)?))
+                        },
                         Some(expr) => Ok(self.maybe_compile_expr(expr, Some(reg))?.0),
                         None => Ok(Bytecode::new())
                     }
@@ -275,7 +369,7 @@ impl BytecodeCompiler {
             Stmt::Block(block_stmt) => self.compile_block_stmt(block_stmt),
             Stmt::Empty => Ok(Bytecode::new()),
             Stmt::Debugger => Err(CompilerError::are_unsupported("Debugger statements")),
-            Stmt::With(_) => Err(CompilerError::are_unsupported("'with' statements")),
+            Stmt::With(with_stmt) => self.compile_with_stmt(with_stmt),
             Stmt::Return(ret) => self.compile_return_stmt(ret),
             Stmt::Labeled(labeled_stmt) => self.compile_label_stmt(labeled_stmt),
             Stmt::Break(break_stmt) => self.compile_break_stmt(break_stmt),
@@ -294,18 +388,35 @@ impl BytecodeCompiler {
     }
 
     fn compile_block_stmt(&mut self, block_stmt: &BlockStmt) -> BytecodeResult {
+        if self.eval_possible {
+            self.scope_depth += 1;
+        }
+
         self.scopes.enter_new_block_scope()?;
-        let maybe_bc = block_stmt.iter().map(|part| self.compile_program_part(part)).collect();
+        let maybe_bc: BytecodeResult = block_stmt.iter().map(|part| self.compile_program_part(part)).collect();
         self.scopes.leave_current_block_scope()?;
 
-        maybe_bc
+        if !self.eval_possible {
+            return maybe_bc;
+        }
+
+        self.scope_depth -= 1;
+        Ok(Bytecode::new()
+            .add(Operation::new(Instruction::EnterScope, vec![]))
+            .add_bytecode(maybe_bc?)
+            .add(Operation::new(Instruction::LeaveScope, vec![])))
+    }
+
+    /// Leaves all scopes entered since the given scope depth
+    fn leave_scopes_until(&self, scope_depth: usize) -> Bytecode {
+        Bytecode::new().extend((scope_depth..self.scope_depth).map(|_| Operation::new(Instruction::LeaveScope, vec![])))
     }
 
     fn compile_return_stmt(&mut self, ret: &Option<Expr>) -> BytecodeResult {
         let used_decl_regs: Vec<Reg> = self.scopes.current_scope()?.used_decls.iter()
                                             .map(|used_decl| used_decl.register).collect();
 
-        let (bytecode, ret_reg) = match ret {
+        let (mut bytecode, ret_reg) = match ret {
             Some(ret_expr) => {
                 let (bytecode, ret_reg) = self.maybe_compile_expr(ret_expr, None)?;
                 (bytecode, ret_reg)
@@ -313,7 +424,28 @@ impl BytecodeCompiler {
             None => (Bytecode::new(), self.isa.common_literal_reg(&CommonLiteral::Void0))
         };
 
+        // A bytecode function call whose result is returned right away is a tail call
+        let tail_call = match bytecode.elements.last() {
+            Some(BytecodeElement::Operation(cmd)) if self.try_depth == 0 &&
+                                                     cmd.instruction == Instruction::CallBytecodeFunc => {
+                match cmd.operands.as_slice() {
+                    [func, Operand::Reg(reg), args] if *reg == ret_reg => Some(Operation::new(Instruction::TailCall,
+                        vec![func.clone(), args.clone(), Operand::RegistersArray(used_decl_regs.clone())])),
+                    _ => None
+                }
+            },
+            _ => None
+        };
+
+        if let Some(tail_call) = tail_call {
+            bytecode.elements.pop();
+            return Ok(bytecode
+                .add_bytecode(self.leave_scopes_until(0))
+                .add(tail_call));
+        }
+
         Ok(bytecode
+            .add_bytecode(self.leave_scopes_until(0))
             .add(Operation::new(Instruction::ReturnBytecodeFunc,
                                 vec![Operand::Reg(ret_reg), Operand::RegistersArray(used_decl_regs)]))
         )
@@ -347,14 +479,14 @@ impl BytecodeCompiler {
     fn compile_break_stmt(&mut self, break_stmt: &Option<Identifier>) -> BytecodeResult {
         let maybe_block = self.try_get_block_with_maybe_js_label(break_stmt);
 
-        maybe_block.map(|block| Bytecode::new().add(Operation::new(Instruction::Jump, vec![
+        maybe_block.map(|block| self.leave_scopes_until(block.scope_depth).add(Operation::new(Instruction::Jump, vec![
             Operand::branch_addr(block.end_label())])))
     }
 
     fn compile_continue_stmt(&mut self, continue_stmt: &Option<Identifier>) -> BytecodeResult {
         let maybe_block = self.try_get_block_with_maybe_js_label(continue_stmt);
 
-        maybe_block.map(|block| Bytecode::new().add(Operation::new(Instruction::Jump, vec![
+        maybe_block.map(|block| self.leave_scopes_until(block.scope_depth).add(Operation::new(Instruction::Jump, vec![
             Operand::branch_addr(block.start_label())])))
     }
 
@@ -386,6 +518,87 @@ impl BytecodeCompiler {
         }
     }
 
+    fn compile_with_stmt(&mut self, with_stmt: &WithStmt) -> BytecodeResult {
+        if self.config.disallow_with {
+            return Err(CompilerError::UnsupportedSyntax("'with' statements".into()));
+        }
+        self.warnings.push(CompilerWarning::DeprecatedSyntax("'with' statements".into()));
+
+        let (object_bc, object_reg) = self.maybe_compile_expr(&with_stmt.object, None)?;
+
+        self.scope_depth += 1;
+        self.with_objects.push(object_reg);
+        let maybe_body_bc = self.compile_stmt(&with_stmt.body);
+        self.with_objects.pop();
+        self.scope_depth -= 1;
+
+        Ok(object_bc
+            .add(Operation::new(Instruction::WithStatement, vec![Operand::Reg(object_reg)]))
+            .add_bytecode(maybe_body_bc?)
+            .add(Operation::new(Instruction::LeaveScope, vec![])))
+    }
+
+    // Compiles the checks of a name in the body of 'with' statements. The given operation runs for the
+    // innermost object with a binding for the name, the fallback if none of them has one.
+    fn compile_with_name_lookup(&mut self, ident: &Identifier, object_op: impl Fn(Reg, Reg) -> Operation,
+                                fallback_bc: Bytecode) -> BytecodeResult {
+        let name = Expr::Literal(Literal::String(format!("\"{}\"", ident)));
+        let (name_bc, name_reg) = self.maybe_compile_expr(&name, None)?;
+        let has_binding_reg = self.scopes.reserve_register()?;
+        let end_label = self.label_generator.generate_label();
+
+        let mut bytecode = name_bc;
+        for object_reg in self.with_objects.clone().into_iter().rev() {
+            let next_object_label = self.label_generator.generate_label();
+            bytecode = bytecode
+                .add(Operation::new(Instruction::HasBinding, vec![
+                    Operand::Reg(has_binding_reg), Operand::Reg(object_reg), Operand::Reg(name_reg)]))
+                .add(Operation::new(Instruction::JumpCondNeg, vec![
+                    Operand::Reg(has_binding_reg), Operand::branch_addr(next_object_label)]))
+                .add(object_op(object_reg, name_reg))
+                .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(end_label)]))
+                .add_label(next_object_label);
+        }
+
+        Ok(bytecode
+            .add_bytecode(fallback_bc)
+            .add_label(end_label))
+    }
+
+    // Resolves a name outside of the 'with' objects, like it is resolved outside of a 'with' body
+    fn compile_scope_name(&mut self, ident: &Identifier) -> CompilerResult<(Bytecode, Reg)> {
+        let with_objects = std::mem::take(&mut self.with_objects);
+        let maybe_name = self.maybe_compile_expr(&Expr::Ident(ident.clone()), None);
+        self.with_objects = with_objects;
+        maybe_name
+    }
+
+    fn compile_with_name_read(&mut self, ident: &Identifier, target_reg: Option<Reg>) -> CompilerResult<(Bytecode, Reg)> {
+        let (scope_bc, scope_reg) = self.compile_scope_name(ident)?;
+        let target_reg = match target_reg {
+            Some(reg) => reg,
+            None => self.scopes.reserve_register()?
+        };
+
+        let lookup_bc = self.compile_with_name_lookup(ident,
+            |object_reg, name_reg| Operation::new(Instruction::PropAccess, vec![
+                Operand::Reg(target_reg), Operand::Reg(object_reg), Operand::Reg(name_reg)]),
+            self.compile_operand_assignment(target_reg, Operand::Reg(scope_reg))?)?;
+
+        Ok((scope_bc.add_bytecode(lookup_bc), target_reg))
+    }
+
+    fn compile_with_name_write(&mut self, ident: &Identifier, value_reg: Reg) -> BytecodeResult {
+        let (scope_bc, scope_reg) = self.compile_scope_name(ident)?;
+
+        let lookup_bc = self.compile_with_name_lookup(ident,
+            |object_reg, name_reg| Operation::new(Instruction::PropertySet, vec![
+                Operand::Reg(object_reg), Operand::Reg(name_reg), Operand::Reg(value_reg)]),
+            self.compile_operand_assignment(scope_reg, Operand::Reg(value_reg))?)?;
+
+        Ok(scope_bc.add_bytecode(lookup_bc))
+    }
+
     fn compile_throw_stmt(&mut self, throw_expr: &Expr) -> BytecodeResult {
         let (bc, reg) = self.maybe_compile_expr(throw_expr, None)?;
 
@@ -393,14 +606,11 @@ impl BytecodeCompiler {
     }
 
     fn compile_try_stmt(&mut self, try_stmt: &TryStmt) -> BytecodeResult {
-        let try_block_bc = self.compile_block_stmt(&try_stmt.block)?;
-        let trash_reg = self.isa.reserved_reg(&ReservedeRegister::TrashRegister);
-        let (catch_block_bc, catch_reg) = try_stmt.handler.as_ref()
-                                            .map(|h| self.compile_catch_clause(&h))
-                                            .unwrap_or_else(|| Ok((Bytecode::new(), trash_reg)))?;
-        let final_block_bc = try_stmt.finalizer.as_ref()
-                                            .map(|b| self.compile_block_stmt(&b))
-                                            .unwrap_or_else(|| Ok(Bytecode::new()))?;
+        // The blocks run in a nested VM run, thus calls in them are never tail calls
+        self.try_depth += 1;
+        let maybe_blocks_bc = self.compile_try_blocks(try_stmt);
+        self.try_depth -= 1;
+        let (try_block_bc, catch_block_bc, catch_reg, final_block_bc) = maybe_blocks_bc?;
 
         let catch_block_label = self.label_generator.generate_label();
         let finally_start_label = self.label_generator.generate_label();
@@ -426,6 +636,19 @@ impl BytecodeCompiler {
         )
     }
 
+    fn compile_try_blocks(&mut self, try_stmt: &TryStmt) -> CompilerResult<(Bytecode, Bytecode, Register, Bytecode)> {
+        let try_block_bc = self.compile_block_stmt(&try_stmt.block)?;
+        let trash_reg = self.isa.reserved_reg(&ReservedeRegister::TrashRegister);
+        let (catch_block_bc, catch_reg) = try_stmt.handler.as_ref()
+                                            .map(|h| self.compile_catch_clause(&h))
+                                            .unwrap_or_else(|| Ok((Bytecode::new(), trash_reg)))?;
+        let final_block_bc = try_stmt.finalizer.as_ref()
+                                            .map(|b| self.compile_block_stmt(&b))
+                                            .unwrap_or_else(|| Ok(Bytecode::new()))?;
+
+        Ok((try_block_bc, catch_block_bc, catch_reg, final_block_bc))
+    }
+
     fn compile_catch_clause(&mut self, catch_clause: &CatchClause) -> CompilerResult<(Bytecode, Register)> {
         self.scopes.enter_new_scope()?;
 
@@ -450,7 +673,7 @@ impl BytecodeCompiler {
     fn compile_while_stmt(&mut self, while_stmt: &WhileStmt) -> BytecodeResult {
         let (test_bc, test_reg) = self.maybe_compile_expr(&while_stmt.test, None)?;
 
-        let while_block = self.label_generator.generate_loop_label_block();
+        let while_block = self.label_generator.generate_loop_label_block(self.scope_depth);
         let while_cond_label = while_block.start_label();
         let while_end_label = while_block.end_label();
 
@@ -466,7 +689,7 @@ impl BytecodeCompiler {
         let body_bc = self.compile_stmt(dowhile_stmt.body.borrow())?;
         let (test_bc, test_reg) = self.maybe_compile_expr(&dowhile_stmt.test, None)?;
 
-        let dowhile_block = self.label_generator.generate_loop_label_block();
+        let dowhile_block = self.label_generator.generate_loop_label_block(self.scope_depth);
         let dowhile_start_label = dowhile_block.start_label();
 
         Ok(Bytecode::new()
@@ -486,7 +709,7 @@ impl BytecodeCompiler {
             None => Bytecode::new()
         };
 
-        let for_block = self.label_generator.generate_loop_label_block();
+        let for_block = self.label_generator.generate_loop_label_block(self.scope_depth);
         let loop_start_label = for_block.start_label();
         let loop_end_label = for_block.end_label();
 
@@ -518,11 +741,17 @@ impl BytecodeCompiler {
     }
 
     fn maybe_compile_expr(&mut self, expr: &Expr, target_reg: Option<Register>) -> CompilerResult<(Bytecode, Register)> {
+        // In the body of a 'with' statement, names are resolved against its object first
+        if let (Expr::Ident(ident), false) = (expr, self.with_objects.is_empty()) {
+            return self.compile_with_name_read(ident, target_reg);
+        }
+
         let opt_reg = match expr {
             Expr::Ident(ident) => match self.scopes.get_var(ident) {
                 Ok(var) => Some(var.register),
                 Err(_) => self.decl_dependencies.try_get_dep(ident).map(|&reg| reg)
             },
+            Expr::Literal(Literal::Template(_)) => None,
             Expr::Literal(lit) => {
                 match self.scopes.get_lit_decl(&BytecodeLiteral::from_lit(lit.clone())?) {
                     Ok(lit_decl) => Some(lit_decl.register),
@@ -581,8 +810,8 @@ impl BytecodeCompiler {
             Expr::Logical(logical) => self.compile_logical_expr(logical, target_reg),
             Expr::Member(member) => self.compile_member_expr_access(member, target_reg),
             Expr::MetaProperty(_) => Err(CompilerError::are_unsupported("meta properties")),
-            Expr::New(_) => Err(CompilerError::are_unsupported("object related expressions (new, this, {})")),
-            Expr::Object(_) => Err(CompilerError::are_unsupported("object related expressions (new, this, {})")),
+            Expr::New(new) => self.compile_new_expr(new, target_reg),
+            Expr::Object(props) => self.compile_object_expr(props, target_reg),
             Expr::Sequence(_) => Err(CompilerError::are_unsupported("seqeunce expressions")),
             Expr::Spread(_) => Err(CompilerError::are_unsupported("spread expressions")),
             Expr::Super => Err(CompilerError::are_unsupported("'super' expressions")),
@@ -607,13 +836,101 @@ impl BytecodeCompiler {
         )
     }
 
+    fn compile_object_expr(&mut self, props: &ObjectExpr, target_reg: Reg) -> BytecodeResult {
+        let mut bytecode = Bytecode::new();
+        // The key register of each property, or None for spread elements
+        let mut parts: Vec<(Option<Reg>, Reg)> = vec![];
+
+        for prop in props.iter() {
+            match prop {
+                ObjectProperty::Spread(spread) => {
+                    // The parser wraps the source in a spread expression
+                    let source = match spread.borrow() {
+                        Expr::Spread(source) => source.borrow(),
+                        source => source
+                    };
+                    let (source_bc, source_reg) = self.maybe_compile_expr(source, None)?;
+                    bytecode = bytecode.add_bytecode(source_bc);
+                    parts.push((None, source_reg));
+                },
+                ObjectProperty::Property(prop) => {
+                    let (prop_bc, key_reg, value_reg) = self.compile_object_property(prop)?;
+                    bytecode = bytecode.add_bytecode(prop_bc);
+                    parts.push((Some(key_reg), value_reg));
+                }
+            }
+        }
+
+        // The target may be read by the properties, e.g. in 'a = { ...a, b: 1 }'
+        let reads_target = parts.iter().any(|&(key_reg, reg)| key_reg == Some(target_reg) || reg == target_reg);
+        let obj_reg = if reads_target { self.scopes.reserve_register()? } else { target_reg };
+        let num0_reg = self.isa.common_literal_reg(&CommonLiteral::Num0);
+        let void0_reg = self.isa.common_literal_reg(&CommonLiteral::Void0);
+
+        // ObjectSpread creates a new object if the target is the register holding 0
+        if !matches!(parts.first(), Some((None, _))) {
+            bytecode = bytecode.add(Operation::new(Instruction::ObjectSpread, vec![
+                Operand::Reg(obj_reg), Operand::Reg(num0_reg), Operand::Reg(void0_reg)]));
+        }
+
+        for (index, (key_reg, reg)) in parts.into_iter().enumerate() {
+            bytecode = bytecode.add(match key_reg {
+                Some(key_reg) => Operation::new(Instruction::PropertySet, vec![
+                    Operand::Reg(obj_reg), Operand::Reg(key_reg), Operand::Reg(reg)]),
+                None => Operation::new(Instruction::ObjectSpread, vec![
+                    Operand::Reg(obj_reg), Operand::Reg(if index == 0 { num0_reg } else { obj_reg }), Operand::Reg(reg)])
+            });
+        }
+
+        if reads_target {
+            bytecode = bytecode.add(Operation::new(Instruction::Copy, vec![Operand::Reg(target_reg), Operand::Reg(obj_reg)]));
+        }
+
+        Ok(bytecode)
+    }
+
+    fn compile_object_property(&mut self, prop: &Property) -> CompilerResult<(Bytecode, Reg, Reg)> {
+        if prop.kind != PropertyKind::Init || prop.method {
+            return Err(CompilerError::are_unsupported("methods, getters and setters in object literals"));
+        }
+
+        let (key_bc, key_reg) = match &prop.key {
+            PropertyKey::Expr(Expr::Ident(ident)) if !prop.computed => {
+                self.maybe_compile_expr(&Expr::Literal(Literal::String(format!("\"{}\"", ident))), None)?
+            },
+            PropertyKey::Expr(expr) => self.maybe_compile_expr(expr, None)?,
+            PropertyKey::Literal(lit) => self.maybe_compile_expr(&Expr::Literal(lit.clone()), None)?,
+            PropertyKey::Pat(_) => { return Err(CompilerError::are_unsupported("patterns as property keys")); }
+        };
+
+        let (value_bc, value_reg) = match (&prop.value, &prop.key) {
+            (PropertyValue::Expr(value), _) => self.maybe_compile_expr(value, None)?,
+            // Shorthand properties like '{ a }'
+            (PropertyValue::None, PropertyKey::Expr(key)) => self.maybe_compile_expr(key, None)?,
+            _ => { return Err(CompilerError::are_unsupported("patterns as property values")); }
+        };
+
+        Ok((key_bc.add_bytecode(value_bc), key_reg, value_reg))
+    }
+
     fn compile_assignment_expr(&mut self, assign: &AssignmentExpr, _target_reg: Reg) -> BytecodeResult {
+        if let (AssignmentLeft::Expr(expr), false) = (&assign.left, self.with_objects.is_empty()) {
+            if let Expr::Ident(ident) = expr.borrow() {
+                return self.compile_with_assignment(ident, assign);
+            }
+        }
+
         let ((left_bc, left_reg), maybe_prop_reg) = match &assign.left {
             AssignmentLeft::Pat(_) => { return Err(CompilerError::are_unsupported("Patterns in assignments")); },
             AssignmentLeft::Expr(expr) => match expr.borrow() {
                 Expr::Member(member) => {
                     let (member_bc, obj_reg, prop_reg) = self.compile_member_expr(member)?;
-                    ((member_bc, obj_reg), Some(prop_reg))
+                    let set_instruction = if member.computed && is_index_expr(member.property.borrow()) {
+                        Instruction::ArraySet
+                    } else {
+                        Instruction::PropertySet
+                    };
+                    ((member_bc, obj_reg), Some((prop_reg, set_instruction)))
                 },
                 _ => (self.maybe_compile_expr(&expr, None)?, None)
             }
@@ -621,11 +938,11 @@ impl BytecodeCompiler {
 
         match assign.operator {
             AssignmentOperator::Equal => {
-                if let Some(prop_reg) = maybe_prop_reg {
+                if let Some((prop_reg, set_instruction)) = maybe_prop_reg {
                     let (value_bc, value_reg) = self.maybe_compile_expr(assign.right.borrow(), None)?;
                     Ok(left_bc
                         .add_bytecode(value_bc)
-                        .add(Operation::new(Instruction::PropertySet,
+                        .add(Operation::new(set_instruction,
                                 vec![Operand::Reg(left_reg), Operand::Reg(prop_reg), Operand::Reg(value_reg)])))
                 } else {
                     Ok(left_bc.add_bytecode(self.compile_expr(assign.right.borrow(), left_reg)?))
@@ -639,29 +956,694 @@ impl BytecodeCompiler {
         }
     }
 
+    fn compile_with_assignment(&mut self, ident: &Identifier, assign: &AssignmentExpr) -> BytecodeResult {
+        let (value_bc, value_reg) = match assign.operator {
+            AssignmentOperator::Equal => self.maybe_compile_expr(assign.right.borrow(), None)?,
+            _ => {
+                let (left_bc, left_reg) = self.compile_with_name_read(ident, None)?;
+                let (right_bc, right_reg) = self.maybe_compile_expr(assign.right.borrow(), None)?;
+                (left_bc.add_bytecode(right_bc).add(self.isa.assignment_op(&assign.operator, left_reg, right_reg)), left_reg)
+            }
+        };
+
+        Ok(value_bc.add_bytecode(self.compile_with_name_write(ident, value_reg)?))
+    }
+
     fn compile_binary_expr(&mut self, bin: &BinaryExpr, target_reg: Reg) -> BytecodeResult {
         let (left_bc, left_reg) = self.maybe_compile_expr(bin.left.borrow(), None)?;
         let (right_bc, right_reg) = self.maybe_compile_expr(bin.right.borrow(), None)?;
 
+        // If both sides are known to be strings, '+' is always a string concatenation
+        let op = if BinaryOperator::Plus == bin.operator &&
+                    is_string_expr(bin.left.borrow()) && is_string_expr(bin.right.borrow()) {
+            Operation::new(Instruction::Concat, vec![Operand::Reg(target_reg), Operand::Reg(left_reg), Operand::Reg(right_reg)])
+        } else {
+            self.isa.binary_op(&bin.operator, target_reg, left_reg, right_reg)?
+        };
+
         Ok(left_bc
             .add_bytecode(right_bc)
-            .add(self.isa.binary_op(&bin.operator, target_reg, left_reg, right_reg)?)
+            .add(op)
         )
     }
 
     fn compile_call_expr(&mut self, call: &CallExpr, target_reg: Reg) -> BytecodeResult {
         match call.callee.borrow() {
+            Expr::Ident(ident) if ident == "eval" && call.arguments.len() == 1 && self.is_builtin(ident) => {
+                self.compile_eval(Instruction::EvalDirect, &call.arguments[0], target_reg)
+            },
+            // (0, eval)(code)
+            Expr::Sequence(exprs) if call.arguments.len() == 1 && is_eval_ident(exprs.last()) && self.is_builtin("eval") => {
+                let trash_reg = self.isa.reserved_reg(&ReservedeRegister::TrashRegister);
+                let side_effects_bc = exprs[..exprs.len() - 1].iter().map(|expr| {
+                    self.compile_expr(expr, trash_reg)
+                }).collect::<BytecodeResult>()?;
+
+                Ok(side_effects_bc.add_bytecode(self.compile_eval(Instruction::EvalIndirect, &call.arguments[0], target_reg)?))
+            },
             Expr::Ident(ident) => {
                 if self.functions.iter().any(|func| func.ident == *ident) {
                     self.compile_bytecode_func_call(ident.to_string(), &call.arguments, target_reg)
                 } else {
-                    self.compile_extern_func_call(call, target_reg)
+                    match self.compile_builtin_call(ident, &call.arguments, target_reg)? {
+                        Some(bytecode) => Ok(bytecode),
+                        None => self.compile_extern_func_call(call, target_reg)
+                    }
                 }
             }
+            Expr::Member(member) if is_console_method(member, "assert") && self.is_builtin("console") => {
+                self.compile_console_assert(call, target_reg)
+            },
+            Expr::Member(member) if is_console_method(member, "log") && self.config.debug_output && self.is_builtin("console") => {
+                self.compile_console_log(&call.arguments, target_reg)
+            },
+            Expr::Member(member) => match atomics_ordering(member) {
+                Some(ordering) if self.is_builtin("Atomics") => self.compile_atomics_call(call, ordering, target_reg),
+                _ => match self.compile_method_call(member, &call.arguments, target_reg)? {
+                    Some(bytecode) => Ok(bytecode),
+                    None => self.compile_extern_func_call(call, target_reg)
+                }
+            },
             _ => self.compile_extern_func_call(call, target_reg)
         }
     }
 
+    /// Returns the builtin collection a ``new`` expression creates, if any
+    fn new_collection(&mut self, new: &NewExpr) -> Option<BuiltinCollection> {
+        match new.callee.borrow() {
+            Expr::Ident(constructor) if self.is_builtin(constructor) => BuiltinCollection::from_constructor(constructor),
+            _ => None
+        }
+    }
+
+    /// Returns the builtin collection an expression is known to be, which is only the case for
+    /// ``const`` declarations initialized with an array literal, ``new Set()``, ``new Map()`` or ``new WeakMap()``
+    fn known_collection(&mut self, expr: &Expr) -> Option<BuiltinCollection> {
+        let decl = match expr {
+            Expr::Ident(ident) => self.scopes.get_var(ident).ok()?,
+            _ => { return None; }
+        };
+
+        match decl.decl_type {
+            DeclarationType::Variable(MyVariableKind::Const) => self.const_collections.get(&decl.register).copied(),
+            _ => None
+        }
+    }
+
+    /// Returns the builtin type an expression is known to be, which is only the case for string
+    /// expressions, regular expression literals and ``const`` declarations initialized with either
+    fn known_type(&mut self, expr: &Expr) -> Option<BuiltinType> {
+        match expr {
+            Expr::Literal(Literal::RegEx(_)) => Some(BuiltinType::RegExp),
+            Expr::Ident(ident) => {
+                let decl = self.scopes.get_var(ident).ok()?;
+                match decl.decl_type {
+                    DeclarationType::Variable(MyVariableKind::Const) => self.const_types.get(&decl.register).copied(),
+                    _ => None
+                }
+            },
+            expr if is_string_expr(expr) => Some(BuiltinType::String),
+            _ => None
+        }
+    }
+
+    /// Checks whether an identifier refers to a global builtin, i.e. it is neither declared in the
+    /// current scope nor a bytecode function
+    fn is_builtin(&mut self, ident: &str) -> bool {
+        self.scopes.get_var(ident).is_err() && !self.functions.iter().any(|func| func.ident == ident)
+    }
+
+    /// Compiles ``new Set(values)``, ``new Map(entries)`` and ``new WeakMap(entries)`` into a
+    /// [NewSet](../bytecode/enum.Instruction.html#Instruction::NewSet), [NewMap](../bytecode/enum.Instruction.html#Instruction::NewMap)
+    /// or [NewWeakMap](../bytecode/enum.Instruction.html#Instruction::NewWeakMap) operation
+    ///
+    /// Other ``new`` expressions are not supported.
+    fn compile_new_expr(&mut self, new: &NewExpr, target_reg: Reg) -> BytecodeResult {
+        let instruction = match new.callee.borrow() {
+            Expr::Ident(constructor) => match self.isa.new_op(constructor, new.arguments.len()) {
+                Some(instruction) if self.is_builtin(constructor) => Some(instruction),
+                _ => None
+            },
+            _ => None
+        }.ok_or_else(|| CompilerError::are_unsupported("object related expressions (new, this, {})"))?;
+
+        let (values_bc, values_reg) = match new.arguments.first() {
+            Some(values) => self.maybe_compile_expr(values, None)?,
+            None => (Bytecode::new(), self.isa.common_literal_reg(&CommonLiteral::Num0))
+        };
+
+        Ok(values_bc.add(Operation::new(instruction, vec![Operand::Reg(target_reg), Operand::Reg(values_reg)])))
+    }
+
+    /// Compiles a call of the builtin ``eval`` into an [EvalDirect](../bytecode/enum.Instruction.html#Instruction::EvalDirect)
+    /// or [EvalIndirect](../bytecode/enum.Instruction.html#Instruction::EvalIndirect) operation
+    fn compile_eval(&mut self, instruction: Instruction, code: &Expr, target_reg: Reg) -> BytecodeResult {
+        let (code_bc, code_reg) = self.maybe_compile_expr(code, None)?;
+
+        Ok(code_bc.add(Operation::new(instruction, vec![Operand::Reg(target_reg), Operand::Reg(code_reg)])))
+    }
+
+    /// Tries to compile a call of a global builtin function into a specialized instruction
+    ///
+    /// Returns ``None`` if there is no specialized instruction for this call or the builtin is
+    /// shadowed by a declaration. In this case, the call must be compiled as a regular function call.
+    fn compile_builtin_call(&mut self, func: &str, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        if func == "parseInt" && self.is_builtin(func) {
+            return self.compile_parse_int(args, target_reg);
+        }
+
+        // Symbol() and Symbol(undefined) have no description, unlike Symbol('')
+        if let ("Symbol", [Expr::Literal(lit @ Literal::String(_))]) = (func, args) {
+            if self.is_builtin(func) {
+                return Ok(Some(Bytecode::new().add(Operation::new(Instruction::LoadSymbol, vec![
+                    Operand::Reg(target_reg), Operand::from_literal(BytecodeLiteral::from_lit(lit.clone())?)?]))));
+            }
+        }
+
+        let instruction = match self.isa.builtin_call_op(func, args.len()) {
+            Some(instruction) if self.is_builtin(func) => instruction,
+            _ => { return Ok(None); }
+        };
+
+        let (args_bytecode, arg_regs): (Vec<Bytecode>, Vec<Reg>) = args.iter().map(|arg| {
+            self.maybe_compile_expr(arg, None)
+        }).collect::<CompilerResult<Vec<(Bytecode, Reg)>>>()?.into_iter().unzip();
+
+        let operands = std::iter::once(Operand::Reg(target_reg))
+                        .chain(arg_regs.into_iter().map(Operand::Reg))
+                        .collect();
+
+        Ok(Some(args_bytecode.into_iter().collect::<Bytecode>()
+            .add(Operation::new(instruction, operands))))
+    }
+
+    /// Compiles a call of a function of the builtins ``Math``, ``Date``, ``JSON``, ``Object``, ``Array`` or
+    /// ``Promise`` like ``Math.floor(x)`` into a specialized instruction
+    ///
+    /// Missing optional arguments are passed as the register holding 0, which e.g. ``JSON.stringify``
+    /// treats like a missing argument.
+    ///
+    /// Returns ``None`` if there is no specialized instruction for this function or the builtin is shadowed.
+    fn compile_static_call(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        let instruction = match (member.object.borrow(), member.property.borrow()) {
+            (Expr::Ident(object), Expr::Ident(func)) if !member.computed => {
+                let instruction = match object.as_str() {
+                    "Math" => self.isa.math_call_op(func, args.len()),
+                    "Date" => self.isa.date_call_op(func, args.len()),
+                    "JSON" => self.isa.json_call_op(func, args.len()),
+                    "Object" => self.isa.object_call_op(func, args.len()),
+                    "Array" => self.isa.array_call_op(func, args.len()),
+                    "Promise" => self.isa.promise_call_op(func, args.len()),
+                    _ => None
+                };
+                match instruction {
+                    Some(instruction) if self.is_builtin(object) => instruction,
+                    _ => { return Ok(None); }
+                }
+            },
+            _ => { return Ok(None); }
+        };
+
+        let (args_bytecode, arg_regs): (Vec<Bytecode>, Vec<Reg>) = args.iter().map(|arg| {
+            self.maybe_compile_expr(arg, None)
+        }).collect::<CompilerResult<Vec<(Bytecode, Reg)>>>()?.into_iter().unzip();
+
+        let operands = match instruction {
+            Instruction::MathMin |
+            Instruction::MathMax => vec![Operand::Reg(target_reg), Operand::RegistersArray(arg_regs)],
+            Instruction::ObjectAssign => vec![Operand::Reg(target_reg), Operand::Reg(arg_regs[0]),
+                                              Operand::RegistersArray(arg_regs[1..].to_vec())],
+            _ => {
+                let missing_arg = Operand::Reg(self.isa.common_literal_reg(&CommonLiteral::Num0));
+                std::iter::once(Operand::Reg(target_reg))
+                    .chain(arg_regs.into_iter().map(Operand::Reg))
+                    .chain(std::iter::repeat(missing_arg))
+                    .take(instruction.expected_operand_types().len())
+                    .collect()
+            }
+        };
+
+        Ok(Some(args_bytecode.into_iter().collect::<Bytecode>()
+            .add(Operation::new(instruction, operands))))
+    }
+
+    /// Compiles array methods taking a callback, like ``array.sort(comparator)`` into an
+    /// [ArraySort](../bytecode/enum.Instruction.html#Instruction::ArraySort) operation or
+    /// ``array.map(callback)`` into an [ArrayMap](../bytecode/enum.Instruction.html#Instruction::ArrayMap) operation
+    ///
+    /// ``array.reduce(callback, initial)`` may also pass an initial value. Returns ``None`` unless the array
+    /// is a declared variable and the callback is a bytecode function.
+    fn compile_array_callback_call(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        let (array, method, callback, rest_args) = match (member.object.borrow(), member.property.borrow(), args) {
+            (Expr::Ident(array), Expr::Ident(method), [Expr::Ident(callback), rest_args @ ..]) if !member.computed => {
+                (array, method, callback, rest_args)
+            },
+            _ => { return Ok(None); }
+        };
+        let instruction = match (method.as_str(), rest_args) {
+            ("sort", []) => Instruction::ArraySort,
+            ("map", []) => Instruction::ArrayMap,
+            ("filter", []) => Instruction::ArrayFilter,
+            ("find", []) => Instruction::ArrayFind,
+            ("findIndex", []) => Instruction::ArrayFindIndex,
+            ("every", []) => Instruction::ArrayEvery,
+            ("some", []) => Instruction::ArraySome,
+            ("forEach", []) => Instruction::ArrayForEach,
+            ("flatMap", []) => Instruction::ArrayFlatMap,
+            ("reduce", []) | ("reduce", [_]) => Instruction::ArrayReduce,
+            _ => { return Ok(None); }
+        };
+
+        let array_reg = match self.scopes.get_var(array) {
+            Ok(decl) => decl.register,
+            Err(_) => { return Ok(None); }
+        };
+        if self.scopes.get_var(callback).is_ok() {
+            return Ok(None);
+        }
+        let callback_args = match self.functions.iter().find(|func| func.ident == *callback) {
+            Some(func) => func.arguments.clone(),
+            None => { return Ok(None); }
+        };
+
+        let mut operands = vec![
+            Operand::Reg(target_reg),
+            Operand::Reg(array_reg),
+            Operand::function_addr(callback.to_string()),
+            Operand::RegistersArray(callback_args)];
+        let mut bytecode = Bytecode::new();
+
+        // array.forEach returns undefined
+        if instruction == Instruction::ArrayForEach {
+            operands.remove(0);
+            let bytecode = bytecode.add(Operation::new(instruction, operands));
+            if target_reg == self.isa.reserved_reg(&ReservedeRegister::TrashRegister) {
+                return Ok(Some(bytecode));
+            }
+            return Ok(Some(bytecode.add(Operation::new(Instruction::Copy, vec![
+                Operand::Reg(target_reg), Operand::Reg(self.isa.common_literal_reg(&CommonLiteral::Void0))]))));
+        }
+
+        if instruction == Instruction::ArrayReduce {
+            let num0_reg = self.isa.common_literal_reg(&CommonLiteral::Num0);
+            let initial_reg = match rest_args.first() {
+                // The register holding 0 stands for a missing initial value, thus an initial value of 0 needs another register
+                Some(initial) => {
+                    let (initial_bc, initial_reg) = self.compile_optional_arg(initial)?;
+                    bytecode = initial_bc;
+                    initial_reg
+                },
+                None => num0_reg
+            };
+            operands.push(Operand::Reg(initial_reg));
+        }
+
+        Ok(Some(bytecode.add(Operation::new(instruction, operands))))
+    }
+
+    /// Compiles ``parseInt(string)`` and ``parseInt(string, radix)`` into a
+    /// [ParseInt](../bytecode/enum.Instruction.html#Instruction::ParseInt) operation
+    ///
+    /// The radix must be a number literal, which is either 0 or between 2 and 36. Returns ``None``
+    /// for other radixes, in this case the call must be compiled as a regular function call.
+    fn compile_parse_int(&mut self, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        let (string, radix) = match args {
+            [string] => (string, 0),
+            [string, Expr::Literal(lit @ Literal::Number(_))] => match Operand::from_literal(BytecodeLiteral::from_lit(lit.clone())?)? {
+                Operand::ShortNum(radix) if radix == 0 || (2..=36).contains(&radix) => (string, radix),
+                _ => { return Ok(None); }
+            },
+            _ => { return Ok(None); }
+        };
+
+        let (string_bc, string_reg) = self.maybe_compile_expr(string, None)?;
+
+        Ok(Some(string_bc.add(Operation::new(Instruction::ParseInt, vec![
+            Operand::Reg(target_reg), Operand::Reg(string_reg), Operand::ShortNum(radix)]))))
+    }
+
+    /// Tries to compile a method call into a specialized instruction
+    ///
+    /// Returns ``None`` if there is no specialized instruction for this method call. In this
+    /// case, the call must be compiled as a regular function call.
+    fn compile_method_call(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        if let Some(bytecode) = self.compile_static_call(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+        if let Some(bytecode) = self.compile_array_callback_call(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+        if let Some(bytecode) = self.compile_array_slice(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+        if let Some(bytecode) = self.compile_array_flat(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+        if let Some(bytecode) = self.compile_string_slice(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+        if let Some(bytecode) = self.compile_string_index_of(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+        if let Some(bytecode) = self.compile_string_split(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+        if let Some(bytecode) = self.compile_string_pad(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+        if let Some(bytecode) = self.compile_string_search(member, args, target_reg)? {
+            return Ok(Some(bytecode));
+        }
+
+        let instruction = match member.property.borrow() {
+            Expr::Ident(method) if !member.computed => {
+                let collection_op = self.known_collection(member.object.borrow()).and_then(|collection| {
+                    self.isa.collection_method_op(collection, method, args.len())
+                });
+                let typed_op = self.known_type(member.object.borrow()).and_then(|builtin_type| {
+                    self.isa.method_call_op(builtin_type, method, args.len())
+                });
+                match collection_op.or(typed_op).or_else(|| self.isa.generic_method_call_op(method, args.len())) {
+                    Some(instruction) => instruction,
+                    None => { return Ok(None); }
+                }
+            },
+            _ => { return Ok(None); }
+        };
+
+        let (obj_bc, obj_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+
+        let (args_bytecode, arg_regs): (Vec<Bytecode>, Vec<Reg>) = args.iter().map(|arg| {
+            self.maybe_compile_expr(arg, None)
+        }).collect::<CompilerResult<Vec<(Bytecode, Reg)>>>()?.into_iter().unzip();
+
+        let operands = match instruction {
+            Instruction::ArrayConcat => vec![Operand::Reg(target_reg), Operand::Reg(obj_reg), Operand::RegistersArray(arg_regs)],
+            _ => vec![Operand::Reg(target_reg), Operand::Reg(obj_reg)].into_iter()
+                    .chain(arg_regs.into_iter().map(Operand::Reg))
+                    .collect()
+        };
+
+        Ok(Some(obj_bc
+            .add_bytecode(args_bytecode.into_iter().collect())
+            .add(Operation::new(instruction, operands))))
+    }
+
+    /// Compiles an optional argument of an instruction which gets the register holding 0 (register 255) if the
+    /// argument is missing. An argument compiled into that register, e.g. the literal ``0``, is copied into
+    /// another register, so the VM does not take it for a missing one.
+    fn compile_optional_arg(&mut self, arg: &Expr) -> CompilerResult<(Bytecode, Reg)> {
+        let num0_reg = self.isa.common_literal_reg(&CommonLiteral::Num0);
+        match self.maybe_compile_expr(arg, None)? {
+            (arg_bc, arg_reg) if arg_reg == num0_reg => {
+                let copy_reg = self.scopes.reserve_register()?;
+                Ok((arg_bc.add(Operation::new(Instruction::Copy, vec![Operand::Reg(copy_reg), Operand::Reg(arg_reg)])), copy_reg))
+            },
+            arg => Ok(arg)
+        }
+    }
+
+    /// Compiles ``arr.slice(start, end)`` into an [ArraySlice](../bytecode/enum.Instruction.html#Instruction::ArraySlice)
+    /// operation
+    ///
+    /// Start and end are optional. Returns ``None`` if the receiver is not known to be an array.
+    fn compile_array_slice(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        match member.property.borrow() {
+            Expr::Ident(method) if !member.computed && method == "slice" && args.len() <= 2 => {},
+            _ => { return Ok(None); }
+        }
+        if self.known_collection(member.object.borrow()) != Some(BuiltinCollection::Array) {
+            return Ok(None);
+        }
+
+        let num0_reg = self.isa.common_literal_reg(&CommonLiteral::Num0);
+        let (arr_bc, arr_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+        let (start_bc, start_reg) = match args.first() {
+            Some(start) => self.maybe_compile_expr(start, None)?,
+            None => (Bytecode::new(), num0_reg)
+        };
+        let (end_bc, end_reg) = match args.get(1) {
+            // The register holding 0 stands for the end of the array, thus an end of 0 needs another register
+            Some(end) => self.compile_optional_arg(end)?,
+            None => (Bytecode::new(), num0_reg)
+        };
+
+        Ok(Some(arr_bc.add_bytecode(start_bc).add_bytecode(end_bc)
+            .add(Operation::new(Instruction::ArraySlice, vec![
+                Operand::Reg(target_reg), Operand::Reg(arr_reg), Operand::Reg(start_reg), Operand::Reg(end_reg)]))))
+    }
+
+    /// Compiles ``arr.flat(depth)`` into an [ArrayFlat](../bytecode/enum.Instruction.html#Instruction::ArrayFlat)
+    /// operation
+    ///
+    /// The depth defaults to 1. Returns ``None`` if the receiver is not known to be an array.
+    fn compile_array_flat(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        match member.property.borrow() {
+            Expr::Ident(method) if !member.computed && method == "flat" && args.len() <= 1 => {},
+            _ => { return Ok(None); }
+        }
+        if self.known_collection(member.object.borrow()) != Some(BuiltinCollection::Array) {
+            return Ok(None);
+        }
+
+        let num0_reg = self.isa.common_literal_reg(&CommonLiteral::Num0);
+        let (arr_bc, arr_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+        let (depth_bc, depth_reg) = match args.first() {
+            // The register holding 0 stands for an infinite depth, thus a depth of 0 needs another register
+            Some(Expr::Ident(ident)) if ident == "Infinity" && self.is_builtin(ident) => (Bytecode::new(), num0_reg),
+            Some(depth) => self.compile_optional_arg(depth)?,
+            None => (Bytecode::new(), self.isa.common_literal_reg(&CommonLiteral::Num1))
+        };
+
+        Ok(Some(arr_bc.add_bytecode(depth_bc)
+            .add(Operation::new(Instruction::ArrayFlat, vec![
+                Operand::Reg(target_reg), Operand::Reg(arr_reg), Operand::Reg(depth_reg)]))))
+    }
+
+    /// Compiles ``str.slice(start, end)`` and ``str.substring(start, end)`` into a
+    /// [StringSlice](../bytecode/enum.Instruction.html#Instruction::StringSlice) operation
+    ///
+    /// The end is optional. Returns ``None`` for other methods or numbers of arguments and if the receiver is
+    /// not known to be a string.
+    fn compile_string_slice(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        let kind = match member.property.borrow() {
+            Expr::Ident(method) if !member.computed && (1..=2).contains(&args.len()) => match StringSliceKind::from_method(method) {
+                Some(kind) => kind,
+                None => { return Ok(None); }
+            },
+            _ => { return Ok(None); }
+        };
+        if self.known_type(member.object.borrow()) != Some(BuiltinType::String) {
+            return Ok(None);
+        }
+
+        let (str_bc, str_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+        let (start_bc, start_reg) = self.maybe_compile_expr(&args[0], None)?;
+        let (end_bc, end_reg) = match args.get(1) {
+            Some(end) => self.maybe_compile_expr(end, None)?,
+            None => (Bytecode::new(), self.isa.common_literal_reg(&CommonLiteral::Void0))
+        };
+
+        Ok(Some(str_bc.add_bytecode(start_bc).add_bytecode(end_bc)
+            .add(Operation::new(Instruction::StringSlice, vec![
+                Operand::Reg(target_reg), Operand::Reg(str_reg), Operand::Reg(start_reg), Operand::Reg(end_reg), kind.into()]))))
+    }
+
+    /// Compiles ``str.indexOf(needle, start)`` into a [StringIndexOf](../bytecode/enum.Instruction.html#Instruction::StringIndexOf)
+    /// operation
+    ///
+    /// The start is optional. Returns ``None`` for other methods or numbers of arguments and if the receiver is
+    /// not known to be a string.
+    fn compile_string_index_of(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        match member.property.borrow() {
+            Expr::Ident(method) if !member.computed && method == "indexOf" && (1..=2).contains(&args.len()) => {},
+            _ => { return Ok(None); }
+        }
+        if self.known_type(member.object.borrow()) != Some(BuiltinType::String) {
+            return Ok(None);
+        }
+
+        let (str_bc, str_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+        let (needle_bc, needle_reg) = self.maybe_compile_expr(&args[0], None)?;
+        let (start_bc, start_reg) = match args.get(1) {
+            Some(start) => self.maybe_compile_expr(start, None)?,
+            None => (Bytecode::new(), self.isa.common_literal_reg(&CommonLiteral::Num0))
+        };
+
+        Ok(Some(str_bc.add_bytecode(needle_bc).add_bytecode(start_bc)
+            .add(Operation::new(Instruction::StringIndexOf, vec![
+                Operand::Reg(target_reg), Operand::Reg(str_reg), Operand::Reg(needle_reg), Operand::Reg(start_reg)]))))
+    }
+
+    /// Compiles ``str.split(separator, limit)`` into a [StringSplit](../bytecode/enum.Instruction.html#Instruction::StringSplit)
+    /// operation
+    ///
+    /// The limit is optional. Returns ``None`` for other methods or numbers of arguments and if the receiver is
+    /// not known to be a string.
+    fn compile_string_split(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        match member.property.borrow() {
+            Expr::Ident(method) if !member.computed && method == "split" && (1..=2).contains(&args.len()) => {},
+            _ => { return Ok(None); }
+        }
+        if self.known_type(member.object.borrow()) != Some(BuiltinType::String) {
+            return Ok(None);
+        }
+
+        let num0_reg = self.isa.common_literal_reg(&CommonLiteral::Num0);
+        let (str_bc, str_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+        let (separator_bc, separator_reg) = self.maybe_compile_expr(&args[0], None)?;
+        let (limit_bc, limit_reg) = match args.get(1) {
+            // The register holding 0 stands for no limit, thus a limit of 0 needs another register
+            Some(limit) => self.compile_optional_arg(limit)?,
+            None => (Bytecode::new(), num0_reg)
+        };
+
+        Ok(Some(str_bc.add_bytecode(separator_bc).add_bytecode(limit_bc)
+            .add(Operation::new(Instruction::StringSplit, vec![
+                Operand::Reg(target_reg), Operand::Reg(str_reg), Operand::Reg(separator_reg), Operand::Reg(limit_reg)]))))
+    }
+
+    /// Compiles ``str.padStart(length, fill)`` and ``str.padEnd(length, fill)`` into a
+    /// [StringPadStart](../bytecode/enum.Instruction.html#Instruction::StringPadStart) or
+    /// [StringPadEnd](../bytecode/enum.Instruction.html#Instruction::StringPadEnd) operation
+    ///
+    /// The fill is optional. Returns ``None`` for other methods or numbers of arguments and if the receiver is
+    /// not known to be a string.
+    fn compile_string_pad(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        let instruction = match member.property.borrow() {
+            Expr::Ident(method) if !member.computed && (1..=2).contains(&args.len()) => match method.as_str() {
+                "padStart" => Instruction::StringPadStart,
+                "padEnd" => Instruction::StringPadEnd,
+                _ => { return Ok(None); }
+            },
+            _ => { return Ok(None); }
+        };
+        if self.known_type(member.object.borrow()) != Some(BuiltinType::String) {
+            return Ok(None);
+        }
+
+        let num0_reg = self.isa.common_literal_reg(&CommonLiteral::Num0);
+        let (str_bc, str_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+        let (length_bc, length_reg) = self.maybe_compile_expr(&args[0], None)?;
+        let (fill_bc, fill_reg) = match args.get(1) {
+            // The register holding 0 stands for a space as fill, thus a fill of 0 needs another register
+            Some(fill) => self.compile_optional_arg(fill)?,
+            None => (Bytecode::new(), num0_reg)
+        };
+
+        Ok(Some(str_bc.add_bytecode(length_bc).add_bytecode(fill_bc)
+            .add(Operation::new(instruction, vec![
+                Operand::Reg(target_reg), Operand::Reg(str_reg), Operand::Reg(length_reg), Operand::Reg(fill_reg)]))))
+    }
+
+    /// Compiles ``str.includes(search, position)``, ``str.startsWith(search, position)`` and
+    /// ``str.endsWith(search, position)`` into a [StringIncludes](../bytecode/enum.Instruction.html#Instruction::StringIncludes),
+    /// [StringStartsWith](../bytecode/enum.Instruction.html#Instruction::StringStartsWith) or
+    /// [StringEndsWith](../bytecode/enum.Instruction.html#Instruction::StringEndsWith) operation
+    ///
+    /// The position is optional. Returns ``None`` for other methods or numbers of arguments and if the receiver is
+    /// not known to be a string.
+    fn compile_string_search(&mut self, member: &MemberExpr, args: &[Expr], target_reg: Reg) -> CompilerResult<Option<Bytecode>> {
+        let instruction = match member.property.borrow() {
+            Expr::Ident(method) if !member.computed && (1..=2).contains(&args.len()) => match method.as_str() {
+                "includes" => Instruction::StringIncludes,
+                "startsWith" => Instruction::StringStartsWith,
+                "endsWith" => Instruction::StringEndsWith,
+                _ => { return Ok(None); }
+            },
+            _ => { return Ok(None); }
+        };
+        if self.known_type(member.object.borrow()) != Some(BuiltinType::String) {
+            return Ok(None);
+        }
+
+        let num0_reg = self.isa.common_literal_reg(&CommonLiteral::Num0);
+        let (str_bc, str_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+        let (search_bc, search_reg) = self.maybe_compile_expr(&args[0], None)?;
+        let (position_bc, position_reg) = match args.get(1) {
+            // The register holding 0 stands for the default position, thus a position of 0 needs another register
+            Some(position) => self.compile_optional_arg(position)?,
+            None => (Bytecode::new(), num0_reg)
+        };
+
+        Ok(Some(str_bc.add_bytecode(search_bc).add_bytecode(position_bc)
+            .add(Operation::new(instruction, vec![
+                Operand::Reg(target_reg), Operand::Reg(str_reg), Operand::Reg(search_reg), Operand::Reg(position_reg)]))))
+    }
+
+    /// Compiles a call of ``Atomics.load``, ``Atomics.store`` or ``Atomics.wait`` as regular call
+    /// with a [MemoryBarrier](../bytecode/enum.Instruction.html#Instruction::MemoryBarrier)
+    ///
+    /// Acquire barriers are placed after the call and release barriers before it. Sequentially
+    /// consistent barriers are placed on both sides.
+    fn compile_atomics_call(&mut self, call: &CallExpr, ordering: MemoryOrdering, target_reg: Reg) -> BytecodeResult {
+        let barrier = || Operation::new(Instruction::MemoryBarrier, vec![Operand::from(ordering)]);
+        let call_bc = self.compile_extern_func_call(call, target_reg)?;
+
+        Ok(match ordering {
+            MemoryOrdering::Acquire => call_bc.add(barrier()),
+            MemoryOrdering::Release => Bytecode::new().add(barrier()).add_bytecode(call_bc),
+            MemoryOrdering::SequentiallyConsistent => Bytecode::new().add(barrier()).add_bytecode(call_bc).add(barrier())
+        })
+    }
+
+    /// Compiles ``console.assert(condition)`` and ``console.assert(condition, "message")``
+    /// into an [Assert](../bytecode/enum.Instruction.html#Instruction::Assert) operation
+    ///
+    /// Other forms, like a message that is not a string literal, are compiled as regular calls.
+    fn compile_console_assert(&mut self, call: &CallExpr, target_reg: Reg) -> BytecodeResult {
+        let message = match call.arguments.get(1) {
+            None => "Assertion failed".to_string(),
+            Some(Expr::Literal(lit @ Literal::String(_))) => match BytecodeLiteral::from_lit(lit.clone())? {
+                BytecodeLiteral::String(message) => message,
+                _ => unreachable!()
+            },
+            Some(_) => { return self.compile_extern_func_call(call, target_reg); }
+        };
+
+        let condition = match call.arguments.as_slice() {
+            [condition] | [condition, _] => condition,
+            _ => { return self.compile_extern_func_call(call, target_reg); }
+        };
+
+        // console.assert returns undefined
+        let result_bc = if target_reg == self.isa.reserved_reg(&ReservedeRegister::TrashRegister) {
+            Bytecode::new()
+        } else {
+            Bytecode::new().add(Operation::new(Instruction::Copy, vec![
+                Operand::Reg(target_reg), Operand::Reg(self.isa.common_literal_reg(&CommonLiteral::Void0))]))
+        };
+
+        if self.config.strip_asserts {
+            return Ok(result_bc);
+        }
+
+        let (condition_bc, condition_reg) = self.maybe_compile_expr(condition, None)?;
+
+        Ok(condition_bc
+            .add(Operation::new(Instruction::Assert, vec![Operand::Reg(condition_reg), Operand::String(message)]))
+            .add_bytecode(result_bc))
+    }
+
+    /// Compiles ``console.log(...)`` into a [ConsoleLog](../bytecode/enum.Instruction.html#Instruction::ConsoleLog)
+    /// operation, which is only done if [debug_output](../config/struct.CompilerConfig.html#structfield.debug_output) is set
+    fn compile_console_log(&mut self, args: &[Expr], target_reg: Reg) -> BytecodeResult {
+        let (args_bytecode, arg_regs): (Vec<Bytecode>, Vec<Reg>) = args.iter().map(|arg| {
+            self.maybe_compile_expr(arg, None)
+        }).collect::<CompilerResult<Vec<(Bytecode, Reg)>>>()?.into_iter().unzip();
+
+        let bytecode = args_bytecode.into_iter().collect::<Bytecode>()
+            .add(Operation::new(Instruction::ConsoleLog, vec![Operand::RegistersArray(arg_regs)]));
+
+        // console.log returns undefined
+        if target_reg == self.isa.reserved_reg(&ReservedeRegister::TrashRegister) {
+            Ok(bytecode)
+        } else {
+            Ok(bytecode.add(Operation::new(Instruction::Copy, vec![
+                Operand::Reg(target_reg), Operand::Reg(self.isa.common_literal_reg(&CommonLiteral::Void0))])))
+        }
+    }
+
     fn compile_conditional_expr(&mut self, conditional: &ConditionalExpr, target_reg: Reg) -> BytecodeResult {
         let (test_bc, test_reg) = self.maybe_compile_expr(conditional.test.borrow(), None)?;
         let (consequent_bc, _) = self.maybe_compile_expr(conditional.consequent.borrow(), Some(target_reg))?;
@@ -684,13 +1666,39 @@ impl BytecodeCompiler {
             self.maybe_compile_expr(arg_expr, None)
         }).collect::<CompilerResult<Vec<(Bytecode, Reg)>>>()?.into_iter().unzip();
 
+        let has_rest_arg = self.functions.iter().any(|f| f.ident == func && f.rest_argument.is_some());
+
+        let arity_check_bc = if self.config.strict_arity && !has_rest_arg {
+            self.compile_arity_check(&func, arg_regs.len())?
+        } else {
+            Bytecode::new()
+        };
+
         Ok(args_bytecode.into_iter().collect::<Bytecode>()
+            .add_bytecode(arity_check_bc)
             .add(Operation::new(Instruction::CallBytecodeFunc,
                                 vec![Operand::function_addr(func),
                                      Operand::Reg(target_reg),
                                      Operand::bc_func_args(arg_regs)])))
     }
 
+    fn compile_arity_check(&mut self, func: &str, args_count: usize) -> BytecodeResult {
+        let params_count = self.functions.iter().find(|f| f.ident == func)
+            .map(|f| f.arguments.len())
+            .ok_or_else(|| CompilerError::Custom(format!("The function '{}' does not exist", func)))?;
+
+        if params_count > 255 || args_count > 255 {
+            return Err(CompilerError::Custom("Only up to 255 arguments can be checked".into()));
+        }
+
+        let count_reg = self.scopes.reserve_register()?;
+
+        Ok(Bytecode::new()
+            .add(self.isa.load_op(count_reg, Operand::ShortNum(args_count as u8))?)
+            .add(Operation::new(Instruction::CheckArity, vec![Operand::ShortNum(params_count as u8),
+                                                              Operand::Reg(count_reg)])))
+    }
+
     fn compile_extern_func_call(&mut self, call: &CallExpr, target_reg: Reg) -> BytecodeResult {
         let (callee_bc, callee_reg) = self.maybe_compile_expr(&call.callee, None)?;
 
@@ -718,10 +1726,14 @@ impl BytecodeCompiler {
     }
 
     fn compile_operand_assignment(&self, left: Reg, right: Operand) -> BytecodeResult {
-        Ok(Bytecode::new().add(self.isa.load_op(left, right)))
+        Ok(Bytecode::new().add(self.isa.load_op(left, right)?))
     }
 
     fn compile_identifier_expr(&mut self, ident: &Identifier, target_reg: Reg) -> BytecodeResult {
+        if !self.with_objects.is_empty() {
+            return Ok(self.compile_with_name_read(ident, Some(target_reg))?.0);
+        }
+
         match self.scopes.get_var(&ident).map(|decl| decl.clone()) {
             Ok(decl) => self.compile_operand_assignment(target_reg, Operand::Reg(decl.register)),
             Err(_) => match self.functions.iter().find(|func| func.ident == *ident) {
@@ -732,6 +1744,9 @@ impl BytecodeCompiler {
                             Operand::function_addr(ident.clone()),
                             Operand::RegistersArray(func.arguments.clone())])))
                 },
+                None if ident == "globalThis" => {
+                    Ok(Bytecode::new().add(Operation::new(Instruction::LoadGlobalThis, vec![Operand::Reg(target_reg)])))
+                },
                 None => {
                     for i in 0..self.scopes.scopes.len()-1 {
                         self.scopes.scopes[i].try_reserve_specific_reg(target_reg)?;
@@ -745,6 +1760,10 @@ impl BytecodeCompiler {
     }
 
     fn compile_literal_expr(&mut self, lit: &Literal, target_reg: Reg) -> BytecodeResult {
+        if let Literal::Template(template) = lit {
+            return self.compile_template_literal(template, target_reg);
+        }
+
         let operand = Operand::from_literal(BytecodeLiteral::from_lit(lit.clone())?)?;
         // This feature is currenlty disabled
         if false { // operand.is_worth_caching()
@@ -754,6 +1773,57 @@ impl BytecodeCompiler {
         self.compile_operand_assignment(target_reg, operand)
     }
 
+    /// Compiles a template literal into a chain of [Concat](enum.Instruction.html#Instruction::Concat) instructions
+    ///
+    /// The result of a template literal is always a string, thus every interpolated
+    /// expression can be appended without any type dispatch.
+    fn compile_template_literal(&mut self, template: &TemplateLiteral, target_reg: Reg) -> BytecodeResult {
+        if template.expressions.is_empty() {
+            let cooked = template.quasis.first().map(|quasi| quasi.cooked.clone()).unwrap_or_default();
+            return self.compile_operand_assignment(target_reg, Operand::String(cooked));
+        }
+
+        let mut bytecode = Bytecode::new();
+        let mut part_regs = vec![];
+
+        for (i, quasi) in template.quasis.iter().enumerate() {
+            // The leading string is always kept to guarantee a string as first operand
+            if i == 0 || !quasi.cooked.is_empty() {
+                let quasi_reg = self.scopes.reserve_register()?;
+                bytecode = bytecode.add_bytecode(
+                    self.compile_operand_assignment(quasi_reg, Operand::String(quasi.cooked.clone()))?);
+                part_regs.push(quasi_reg);
+            }
+
+            if let Some(expr) = template.expressions.get(i) {
+                let (expr_bc, expr_reg) = self.maybe_compile_expr(expr, None)?;
+                bytecode = bytecode.add_bytecode(expr_bc);
+                part_regs.push(expr_reg);
+            }
+        }
+
+        // Accumulating directly in the target register would overwrite parts that are read later
+        let acc_reg = if part_regs[1..].contains(&target_reg) {
+            self.scopes.reserve_register()?
+        } else {
+            target_reg
+        };
+
+        bytecode = bytecode.add(Operation::new(Instruction::Concat, vec![
+            Operand::Reg(acc_reg), Operand::Reg(part_regs[0]), Operand::Reg(part_regs[1])]));
+
+        for &part_reg in part_regs[2..].iter() {
+            bytecode = bytecode.add(Operation::new(Instruction::Concat, vec![
+                Operand::Reg(acc_reg), Operand::Reg(acc_reg), Operand::Reg(part_reg)]));
+        }
+
+        if acc_reg != target_reg {
+            bytecode = bytecode.add_bytecode(self.compile_operand_assignment(target_reg, Operand::Reg(acc_reg))?);
+        }
+
+        Ok(bytecode)
+    }
+
     fn compile_logical_expr(&mut self, logical: &LogicalExpr, target_reg: Reg) -> BytecodeResult {
         let (left_bc, _) = self.maybe_compile_expr(logical.left.borrow(), Some(target_reg))?;
         let (right_bc, _) = self.maybe_compile_expr(logical.right.borrow(), Some(target_reg))?;
@@ -788,10 +1858,43 @@ impl BytecodeCompiler {
     }
 
     fn compile_member_expr_access(&mut self, member: &MemberExpr, target_reg: Reg) -> BytecodeResult {
+        if let Some(symbol) = well_known_symbol(member) {
+            if self.is_builtin("Symbol") {
+                return Ok(Bytecode::new().add(Operation::new(Instruction::LoadWellKnownSymbol, vec![
+                    Operand::Reg(target_reg), symbol.into()])));
+            }
+        }
+
+        // obj[Symbol.iterator] needs no register for the key
+        if let (true, Expr::Member(key)) = (member.computed, member.property.borrow()) {
+            if let Some(symbol) = well_known_symbol(key).filter(|_| self.is_builtin("Symbol")) {
+                let (obj_bc, obj_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+                return Ok(obj_bc.add(Operation::new(Instruction::PropAccess, vec![
+                    Operand::Reg(target_reg), Operand::Reg(obj_reg), Operand::symbol_key(symbol)])));
+            }
+        }
+
+        if let (false, Expr::Ident(prop)) = (member.computed, member.property.borrow()) {
+            if prop == "length" {
+                let instruction = if is_string_expr(member.object.borrow()) {
+                    Instruction::GetLength
+                } else {
+                    Instruction::GetArrayLength
+                };
+                let (obj_bc, obj_reg) = self.maybe_compile_expr(member.object.borrow(), None)?;
+                return Ok(obj_bc.add(Operation::new(instruction, vec![Operand::Reg(target_reg), Operand::Reg(obj_reg)])));
+            }
+        }
+
         let (member_bc, obj_reg, prop_reg) = self.compile_member_expr(member)?;
+        let instruction = if member.computed && is_index_expr(member.property.borrow()) {
+            Instruction::ArrayGet
+        } else {
+            Instruction::PropAccess
+        };
 
         Ok(member_bc
-            .add(Operation::new(Instruction::PropAccess, vec![
+            .add(Operation::new(instruction, vec![
                     Operand::Reg(target_reg), Operand::Reg(obj_reg), Operand::Reg(prop_reg)
                 ]
             )))
@@ -799,6 +1902,13 @@ impl BytecodeCompiler {
 
     fn compile_update_expr(&mut self, update: &UpdateExpr, _target_reg: Reg) -> BytecodeResult {
         if update.prefix {
+            if let (Expr::Ident(ident), false) = (update.argument.borrow(), self.with_objects.is_empty()) {
+                let (arg_bc, arg_reg) = self.compile_with_name_read(ident, None)?;
+                return Ok(arg_bc
+                    .add(self.isa.update_op(&update.operator, arg_reg))
+                    .add_bytecode(self.compile_with_name_write(ident, arg_reg)?));
+            }
+
             let (arg_bc, arg_reg) = self.maybe_compile_expr(update.argument.borrow(), None)?;
             Ok(arg_bc.add(self.isa.update_op(&update.operator, arg_reg)))
         } else {
@@ -833,29 +1943,58 @@ impl BytecodeCompiler {
         };
 
         self.scopes.enter_new_scope()?;
+        let outer_eval_possible = std::mem::replace(&mut self.eval_possible, contains_direct_eval(&func.body));
+        let outer_scope_depth = std::mem::replace(&mut self.scope_depth, 0);
+        let outer_try_depth = std::mem::replace(&mut self.try_depth, 0);
+        let outer_with_objects = std::mem::take(&mut self.with_objects);
+
+        let (params, rest_param) = match func.params.split_last() {
+            Some((FunctionArg::Pat(Pat::RestElement(rest_pat)), params)) => match rest_pat.borrow() {
+                Pat::Identifier(ident) => (params, Some(ident)),
+                _ => { return Err(CompilerError::Custom("Only identifiers are accepted as rest arguments".into())); }
+            },
+            _ => (func.params.as_slice(), None)
+        };
 
-        let arg_regs = func.params.iter().map(|param| {
-            match param {
-                FunctionArg::Expr(expr) => match expr {
-                    Expr::Ident(ident) => self.scopes.add_decl(ident.to_string(), DeclarationType::Function),
-                    _ => Err(CompilerError::Custom("Only identifiers are accepted as function arguments".into()))
-                },
-                FunctionArg::Pat(pat) => match pat {
-                    Pat::Identifier(ident) => self.scopes.add_decl(ident.to_string(), DeclarationType::Function),
-                    _ => Err(CompilerError::Custom("Only identifiers are accepted as function arguments".into()))
-                }
-            }
+        let params = params.iter().map(function_param).collect::<CompilerResult<Vec<_>>>()?;
+
+        let arg_regs = params.iter().map(|(ident, _)| {
+            self.scopes.add_decl(ident.to_string(), DeclarationType::Function)
         }).collect::<CompilerResult<Vec<Register>>>()?;
 
-        self.functions.push(BytecodeFunction::new_phantom(func_ident, arg_regs));
+        let rest_arg_reg = match rest_param {
+            Some(ident) => Some(self.scopes.add_decl(ident.to_string(), DeclarationType::Function)?),
+            None => None
+        };
 
-        let mut func_bc = func.body.iter().map(|part| self.compile_program_part(&part))
-                                   .collect::<BytecodeResult>()?;
+        self.functions.push(BytecodeFunction::new_phantom(func_ident, arg_regs.clone(), rest_arg_reg));
+
+        let rest_arg_bc = match rest_arg_reg {
+            Some(reg) if arg_regs.len() <= 255 => Bytecode::new()
+                .add(Operation::new(Instruction::CollectRestArgs, vec![Operand::Reg(reg),
+                                                                       Operand::ShortNum(arg_regs.len() as u8)])),
+            Some(_) => { return Err(CompilerError::Custom("Rest arguments may only follow up to 255 arguments".into())); },
+            None => Bytecode::new()
+        };
+
+        let default_params_bc = params.iter().zip(arg_regs.iter())
+            .filter_map(|((_, default), &reg)| default.map(|default| (default, reg)))
+            .map(|(default, reg)| self.compile_default_param(default, reg))
+            .collect::<BytecodeResult>()?;
+
+        let mut func_bc = rest_arg_bc.add_bytecode(default_params_bc).add_bytecode(
+            func.body.iter().map(|part| self.compile_program_part(&part)).collect::<BytecodeResult>()?
+        );
 
         if !func_bc.last_op_is_return() {
             func_bc = func_bc.add_bytecode(self.compile_return_stmt(&None)?)
         }
 
+        self.eval_possible = outer_eval_possible;
+        self.scope_depth = outer_scope_depth;
+        self.try_depth = outer_try_depth;
+        self.with_objects = outer_with_objects;
+
         let func_scope = self.scopes.leave_current_scope()?;
         let used_decls = func_scope.used_decls.into_iter().map(|used_decl| used_decl.register).collect();
 
@@ -866,6 +2005,34 @@ impl BytecodeCompiler {
         Ok(Bytecode::new())
     }
 
+    fn compile_default_param(&mut self, default: &Expr, param_reg: Reg) -> BytecodeResult {
+        match default {
+            Expr::Literal(lit) if !matches!(lit, Literal::Template(_)) => {},
+            Expr::Ident(_) => {},
+            _ => {
+                // The default value might have side effects, thus it must only be evaluated if needed
+                let is_undefined_reg = self.scopes.reserve_register()?;
+                let void0_reg = self.isa.common_literal_reg(&CommonLiteral::Void0);
+                let after_default_label = self.label_generator.generate_label();
+                let (default_bc, _) = self.maybe_compile_expr(default, Some(param_reg))?;
+
+                return Ok(Bytecode::new()
+                    .add(Operation::new(Instruction::CompStrictEqual, vec![Operand::Reg(is_undefined_reg),
+                                                                           Operand::Reg(param_reg),
+                                                                           Operand::Reg(void0_reg)]))
+                    .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(is_undefined_reg),
+                                                                       Operand::branch_addr(after_default_label)]))
+                    .add_bytecode(default_bc)
+                    .add_label(after_default_label));
+            }
+        }
+
+        let (default_bc, default_reg) = self.maybe_compile_expr(default, None)?;
+
+        Ok(default_bc.add(Operation::new(Instruction::DefaultParam, vec![Operand::Reg(param_reg),
+                                                                          Operand::Reg(default_reg)])))
+    }
+
     fn finalize_label_addresses(&self, mut bc: Bytecode, offset: usize) -> BytecodeResult {
         let mut offset_counter = offset;
         let label_offsets: HashMap<Label, usize> = bc.elements.iter().filter_map(|element| {
@@ -907,29 +2074,44 @@ impl BytecodeCompiler {
         }).collect::<BytecodeResult>()?;
 
         let mut complete_bytecode = main.add_bytecode(functions_bytecode);
+        let void0_reg = self.isa.common_literal_reg(&CommonLiteral::Void0);
+        let trash_reg = self.isa.reserved_reg(&ReservedeRegister::TrashRegister);
 
         // Patch bytecode function argument lists
         for cmd in complete_bytecode.commands_iter_mut() {
-            if let Instruction::CallBytecodeFunc = cmd.instruction {
-                let target_func = cmd.operands.get(0).expect("Failed to retrieve bytecode functions token");
-                let args = cmd.operands.get(2).expect("Failed to retrieve bytecode functions argument list");
+            let args_index = match cmd.instruction {
+                Instruction::CallBytecodeFunc => 2,
+                Instruction::TailCall => 1,
+                _ => continue
+            };
 
-                let func = match target_func {
-                    Operand::FunctionAddr(token) => functions_and_offsets.get(&token.ident).ok_or(
-                        CompilerError::Custom(format!("Found unknown function ident {}", token.ident))
-                    )?.1,
-                    _ => { return Err(CompilerError::Custom(
-                        "Bytecode function name should be a function address token".into())) }
-                };
+            let target_func = cmd.operands.get(0).expect("Failed to retrieve bytecode functions token");
+            let args = cmd.operands.get(args_index).expect("Failed to retrieve bytecode functions argument list");
 
-                if let Operand::FunctionArguments(arg_regs) = args {
-                    cmd.operands[2] = Operand::RegistersArray(
-                        func.arguments.iter().zip(arg_regs.args.iter()).map(|(&a, &b)| vec![a, b]).flatten().collect()
-                    );
-                } else {
-                    return Err(CompilerError::Custom(
-                        "Bytecode function argument should be a bytecode func args placeholder".into()))
+            let func = match target_func {
+                Operand::FunctionAddr(token) => functions_and_offsets.get(&token.ident).ok_or(
+                    CompilerError::Custom(format!("Found unknown function ident {}", token.ident))
+                )?.1,
+                _ => { return Err(CompilerError::Custom(
+                    "Bytecode function name should be a function address token".into())) }
+            };
+
+            if let Operand::FunctionArguments(arg_regs) = args {
+                // Missing arguments are undefined
+                let mut arg_pairs: Vec<Register> = func.arguments.iter()
+                    .zip(arg_regs.args.iter().chain(std::iter::repeat(&void0_reg)))
+                    .map(|(&a, &b)| vec![a, b]).flatten().collect();
+
+                // Additional arguments are only passed for the rest argument
+                if func.rest_argument.is_some() {
+                    arg_pairs.extend(arg_regs.args.iter().skip(func.arguments.len())
+                                                       .flat_map(|&arg| vec![trash_reg, arg]));
                 }
+
+                cmd.operands[args_index] = Operand::RegistersArray(arg_pairs);
+            } else {
+                return Err(CompilerError::Custom(
+                    "Bytecode function argument should be a bytecode func args placeholder".into()))
             }
         }
 
@@ -948,6 +2130,151 @@ impl BytecodeCompiler {
     }
 }
 
+/// Checks whether a member expression is ``console.assert``
+fn is_console_method(member: &MemberExpr, method: &str) -> bool {
+    match (member.object.borrow(), member.property.borrow()) {
+        (Expr::Ident(object), Expr::Ident(property)) => !member.computed && object == "console" && property == method,
+        _ => false
+    }
+}
+
+/// Returns the memory ordering a call of the member expression requires, if it is
+/// ``Atomics.load``, ``Atomics.store`` or ``Atomics.wait``
+fn atomics_ordering(member: &MemberExpr) -> Option<MemoryOrdering> {
+    match (member.object.borrow(), member.property.borrow()) {
+        (Expr::Ident(object), Expr::Ident(property)) if !member.computed && object == "Atomics" => match property.as_str() {
+            "load" => Some(MemoryOrdering::Acquire),
+            "store" => Some(MemoryOrdering::Release),
+            "wait" => Some(MemoryOrdering::SequentiallyConsistent),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+/// Returns the well-known symbol the member expression accesses, if it is e.g. ``Symbol.iterator``
+fn well_known_symbol(member: &MemberExpr) -> Option<WellKnownSymbol> {
+    match (member.object.borrow(), member.property.borrow()) {
+        (Expr::Ident(object), Expr::Ident(property)) if !member.computed && object == "Symbol" => {
+            WellKnownSymbol::from_name(property)
+        },
+        _ => None
+    }
+}
+
+fn is_eval_ident(maybe_expr: Option<&Expr>) -> bool {
+    matches!(maybe_expr, Some(Expr::Ident(ident)) if ident == "eval")
+}
+
+/// Checks whether a script or function body might call ``eval`` directly.
+///
+/// Nested functions are not searched, since they track their scopes on their own.
+fn contains_direct_eval(parts: &[ProgramPart]) -> bool {
+    parts.iter().any(|part| match part {
+        ProgramPart::Decl(Decl::Variable(_, decls)) |
+        ProgramPart::Stmt(Stmt::For(ForStmt { init: Some(LoopInit::Variable(_, decls)), .. })) if var_decls_contain_direct_eval(decls) => true,
+        ProgramPart::Stmt(stmt) => stmt_contains_direct_eval(stmt),
+        _ => false
+    })
+}
+
+fn var_decls_contain_direct_eval(decls: &[VariableDecl]) -> bool {
+    decls.iter().any(|decl| decl.init.iter().any(expr_contains_direct_eval))
+}
+
+fn stmt_contains_direct_eval(stmt: &Stmt) -> bool {
+    let expr = expr_contains_direct_eval;
+    let maybe_expr = |maybe: &Option<Expr>| maybe.iter().any(expr_contains_direct_eval);
+
+    match stmt {
+        Stmt::Expr(expression) |
+        Stmt::Throw(expression) => expr(expression),
+        Stmt::Return(ret) => maybe_expr(ret),
+        Stmt::Block(block) => contains_direct_eval(block),
+        Stmt::With(with) => expr(&with.object) || stmt_contains_direct_eval(&with.body),
+        Stmt::Labeled(labeled) => stmt_contains_direct_eval(&labeled.body),
+        Stmt::If(if_stmt) => expr(&if_stmt.test) || stmt_contains_direct_eval(&if_stmt.consequent) ||
+                             if_stmt.alternate.iter().any(|alt| stmt_contains_direct_eval(alt)),
+        Stmt::Switch(switch) => expr(&switch.discriminant) || switch.cases.iter().any(|case| {
+            maybe_expr(&case.test) || contains_direct_eval(&case.consequent)
+        }),
+        Stmt::Try(try_stmt) => contains_direct_eval(&try_stmt.block) ||
+                               try_stmt.handler.iter().any(|handler| contains_direct_eval(&handler.body)) ||
+                               try_stmt.finalizer.iter().any(|finalizer| contains_direct_eval(finalizer)),
+        Stmt::While(while_stmt) => expr(&while_stmt.test) || stmt_contains_direct_eval(&while_stmt.body),
+        Stmt::DoWhile(dowhile_stmt) => expr(&dowhile_stmt.test) || stmt_contains_direct_eval(&dowhile_stmt.body),
+        Stmt::For(for_stmt) => {
+            let init = match &for_stmt.init {
+                Some(LoopInit::Variable(_, decls)) => var_decls_contain_direct_eval(decls),
+                Some(LoopInit::Expr(init)) => expr(init),
+                None => false
+            };
+            init || maybe_expr(&for_stmt.test) || maybe_expr(&for_stmt.update) || stmt_contains_direct_eval(&for_stmt.body)
+        },
+        Stmt::ForIn(for_in) => expr(&for_in.right) || stmt_contains_direct_eval(&for_in.body),
+        Stmt::ForOf(for_of) => expr(&for_of.right) || stmt_contains_direct_eval(&for_of.body),
+        Stmt::Var(decls) => var_decls_contain_direct_eval(decls),
+        Stmt::Empty |
+        Stmt::Debugger |
+        Stmt::Break(_) |
+        Stmt::Continue(_) => false
+    }
+}
+
+fn expr_contains_direct_eval(expr: &Expr) -> bool {
+    let any = |exprs: &[Expr]| exprs.iter().any(expr_contains_direct_eval);
+
+    match expr {
+        Expr::Call(call) => is_eval_ident(Some(call.callee.borrow())) ||
+                            expr_contains_direct_eval(&call.callee) || any(&call.arguments),
+        Expr::New(new) => expr_contains_direct_eval(&new.callee) || any(&new.arguments),
+        Expr::Array(elements) => elements.iter().flatten().any(expr_contains_direct_eval),
+        Expr::Object(props) => props.iter().any(|prop| match prop {
+            ObjectProperty::Property(Property { value: PropertyValue::Expr(value), .. }) => expr_contains_direct_eval(value),
+            ObjectProperty::Spread(value) => expr_contains_direct_eval(value),
+            _ => false
+        }),
+        Expr::Assignment(assign) => {
+            let left = match &assign.left {
+                AssignmentLeft::Expr(left) => expr_contains_direct_eval(left),
+                AssignmentLeft::Pat(_) => false
+            };
+            left || expr_contains_direct_eval(&assign.right)
+        },
+        Expr::Binary(BinaryExpr { left, right, .. }) |
+        Expr::Logical(LogicalExpr { left, right, .. }) => expr_contains_direct_eval(left) || expr_contains_direct_eval(right),
+        Expr::Conditional(cond) => expr_contains_direct_eval(&cond.test) || expr_contains_direct_eval(&cond.consequent) ||
+                                   expr_contains_direct_eval(&cond.alternate),
+        Expr::Member(member) => expr_contains_direct_eval(&member.object) || expr_contains_direct_eval(&member.property),
+        Expr::Sequence(exprs) => any(exprs),
+        Expr::Literal(Literal::Template(template)) => any(&template.expressions),
+        Expr::TaggedTemplate(tagged) => expr_contains_direct_eval(&tagged.tag) || any(&tagged.quasi.expressions),
+        Expr::Unary(UnaryExpr { argument, .. }) |
+        Expr::Update(UpdateExpr { argument, .. }) |
+        Expr::Await(argument) |
+        Expr::Spread(argument) => expr_contains_direct_eval(argument),
+        Expr::Yield(yield_expr) => yield_expr.argument.iter().any(|argument| expr_contains_direct_eval(argument)),
+        _ => false
+    }
+}
+
+/// Checks whether an expression is known to evaluate to an array index at compile time
+fn is_index_expr(expr: &Expr) -> bool {
+    matches!(expr, Expr::Literal(Literal::Number(_)))
+}
+
+/// Checks whether an expression is known to evaluate to a string at compile time
+fn is_string_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(Literal::String(_)) |
+        Expr::Literal(Literal::Template(_)) => true,
+        // Adding anything to a string results in a string
+        Expr::Binary(bin) => bin.operator == BinaryOperator::Plus &&
+                             (is_string_expr(bin.left.borrow()) || is_string_expr(bin.right.borrow())),
+        _ => false
+    }
+}
+
 #[test]
 fn test_bytecode_compile_var_decl() {
     assert_eq!(BytecodeCompiler::new().compile_var_decl(&VariableKind::Var, &vec![
@@ -0,0 +1,38 @@
+/// Options that change how JavaScript code is compiled
+///
+/// ```
+/// use jsyc_compiler::{BytecodeCompiler, CompilerConfig};
+///
+/// let config = CompilerConfig { strict_arity: true, ..CompilerConfig::default() };
+/// let compiler = BytecodeCompiler::with_config(config);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompilerConfig {
+    /// Check the argument count when calling bytecode functions
+    ///
+    /// If set, each call of a bytecode function is preceded by a
+    /// [CheckArity](../bytecode/enum.Instruction.html#Instruction::CheckArity) operation, which throws
+    /// a ``TypeError`` if the number of arguments does not match the number of declared parameters.
+    pub strict_arity: bool,
+
+    /// Drop all assertions
+    ///
+    /// ``console.assert(condition, "message")`` calls are compiled into
+    /// [Assert](../bytecode/enum.Instruction.html#Instruction::Assert) operations. If set, these calls
+    /// are removed entirely and their arguments are not evaluated.
+    pub strip_asserts: bool,
+
+    /// Reject ``with`` statements
+    ///
+    /// ``with`` statements are deprecated and only compiled with a
+    /// [DeprecatedSyntax](../error/enum.CompilerWarning.html#CompilerWarning::DeprecatedSyntax) warning.
+    /// If set, compiling them fails with [UnsupportedSyntax](../error/enum.CompilerError.html#CompilerError::UnsupportedSyntax).
+    pub disallow_with: bool,
+
+    /// Compile ``console.log`` calls into debug output instructions
+    ///
+    /// If set, ``console.log(...)`` calls are compiled into
+    /// [ConsoleLog](../bytecode/enum.Instruction.html#Instruction::ConsoleLog) operations. Otherwise, they
+    /// remain regular function calls.
+    pub debug_output: bool,
+}
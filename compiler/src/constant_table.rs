@@ -0,0 +1,178 @@
+use crate::bytecode::{Bytecode, Operation, Instruction, Operand, ToBytes};
+use crate::error::{CompilerError, CompilerResult};
+
+use std::collections::HashMap;
+
+
+/// Holds every distinct numeric and string literal of a bytecode exactly once
+///
+/// Constants are loaded by their index through [LoadConst](../bytecode/enum.Instruction.html#Instruction::LoadConst)
+/// operations. The table is encoded as the number of constants as 2 byte big-endian number, followed by
+/// the constants. Each constant is encoded as the opcode of the operation that would load it, followed by
+/// its operand, e.g. ``LoadString`` followed by the encoded string. The table holds at most 65535
+/// constants, so that their number fits into the header.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConstantTable {
+    constants: Vec<Operand>,
+    indices: HashMap<Vec<u8>, u16>
+}
+
+impl ConstantTable {
+    pub fn new() -> Self {
+        ConstantTable {
+            constants: vec![],
+            indices: HashMap::new()
+        }
+    }
+
+    /// Returns the index of the constant, adding it to the table if it is not part of it yet.
+    ///
+    /// Returns ``None`` if the operand is no numeric or string literal, or if the table already
+    /// holds 65535 constants.
+    pub fn insert(&mut self, constant: &Operand) -> Option<u16> {
+        let key = encode_constant(constant).ok()?;
+        if let Some(&index) = self.indices.get(&key) {
+            return Some(index);
+        }
+
+        if self.constants.len() >= u16::MAX as usize {
+            return None;
+        }
+
+        let index = self.constants.len() as u16;
+        self.constants.push(constant.clone());
+        self.indices.insert(key, index);
+        Some(index)
+    }
+
+    /// Returns the constant at the given index
+    pub fn get(&self, index: u16) -> Option<&Operand> {
+        self.constants.get(index as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.constants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constants.is_empty()
+    }
+}
+
+impl Default for ConstantTable {
+    fn default() -> Self {
+        ConstantTable::new()
+    }
+}
+
+impl ToBytes for ConstantTable {
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        let mut bytes = Operand::u16(self.constants.len() as u16).to_bytes()?;
+        for constant in self.constants.iter() {
+            bytes.append(&mut encode_constant(constant)?);
+        }
+        Ok(bytes)
+    }
+
+    fn length_in_bytes(&self) -> usize {
+        2 + self.constants.iter().fold(0, |acc, constant| acc + 1 + constant.length_in_bytes())
+    }
+}
+
+/// Returns the instruction that loads a literal operand
+fn load_instruction(constant: &Operand) -> Option<Instruction> {
+    match constant {
+        Operand::String(_) => Some(Instruction::LoadString),
+        Operand::FloatNum(_) => Some(Instruction::LoadFloatNum),
+        Operand::LongNum(_) => Some(Instruction::LoadLongNum),
+        Operand::ShortNum16(_) => Some(Instruction::LoadNum16),
+        Operand::ShortNum(_) => Some(Instruction::LoadNum),
+        _ => None
+    }
+}
+
+fn encode_constant(constant: &Operand) -> CompilerResult<Vec<u8>> {
+    let instruction = load_instruction(constant).ok_or_else(|| {
+        CompilerError::Custom(format!("The operand {} is no constant", constant))
+    })?;
+
+    let mut bytes = vec![instruction.to_byte()];
+    bytes.append(&mut constant.to_bytes()?);
+    Ok(bytes)
+}
+
+impl Bytecode {
+    /// Moves the literals of all load operations into a [constant table](struct.ConstantTable.html)
+    /// and replaces the operations by [LoadConst](enum.Instruction.html#Instruction::LoadConst) operations.
+    ///
+    /// Loads of literals which are not longer than the 2 byte index, like ``LoadNum``, are kept, since
+    /// ``LoadConst`` would not be shorter. Once the table is full, the remaining loads are kept as well.
+    pub fn build_constant_table(self) -> (ConstantTable, Bytecode) {
+        let mut table = ConstantTable::new();
+        let mut bytecode = self;
+
+        for cmd in bytecode.commands_iter_mut() {
+            let (reg, constant) = match cmd.operands.as_slice() {
+                [Operand::Reg(reg), constant] => (*reg, constant),
+                _ => continue
+            };
+
+            if load_instruction(constant) != Some(cmd.instruction.clone()) || constant.length_in_bytes() <= 2 {
+                continue;
+            }
+
+            if let Some(index) = table.insert(constant) {
+                *cmd = Operation::new(Instruction::LoadConst, vec![Operand::Reg(reg), Operand::u16(index)]);
+            }
+        }
+
+        (table, bytecode)
+    }
+}
+
+
+#[test]
+fn test_constant_table() {
+    let mut table = ConstantTable::new();
+    assert!(table.is_empty());
+    assert_eq!(table.insert(&Operand::String("a".into())), Some(0));
+    assert_eq!(table.insert(&Operand::FloatNum(0.5)), Some(1));
+    assert_eq!(table.insert(&Operand::String("a".into())), Some(0));
+    assert_eq!(table.insert(&Operand::Reg(0)), None);
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.get(1), Some(&Operand::FloatNum(0.5)));
+    assert_eq!(table.get(2), None);
+
+    let encoded = table.to_bytes().unwrap();
+    assert_eq!(&encoded[..6], &[0, 2, Instruction::LoadString.to_byte(), 0, 1, b'a']);
+    assert_eq!(encoded[6], Instruction::LoadFloatNum.to_byte());
+    assert_eq!(encoded.len(), table.length_in_bytes());
+
+    // The number of constants must fit into the header
+    let mut table = ConstantTable::new();
+    for i in 0..u16::MAX {
+        table.insert(&Operand::String(i.to_string())).unwrap();
+    }
+    assert_eq!(table.insert(&Operand::String("full".into())), None);
+    assert_eq!(table.to_bytes().unwrap()[..2], [0xff, 0xff]);
+}
+
+#[test]
+fn test_build_constant_table() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(0), Operand::String("hello".into())]))
+        .add(Operation::new(Instruction::LoadFloatNum, vec![Operand::Reg(1), Operand::FloatNum(1.5)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(2), Operand::ShortNum(7)]))
+        .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(3), Operand::String("hello".into())]))
+        .add(Operation::new(Instruction::Assert, vec![Operand::Reg(0), Operand::String("message".into())]));
+
+    let (table, with_table) = bytecode.clone().build_constant_table();
+    assert_eq!(table.len(), 2);
+    assert_eq!(with_table, Bytecode::new()
+        .add(Operation::new(Instruction::LoadConst, vec![Operand::Reg(0), Operand::ShortNum16(0)]))
+        .add(Operation::new(Instruction::LoadConst, vec![Operand::Reg(1), Operand::ShortNum16(1)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(2), Operand::ShortNum(7)]))
+        .add(Operation::new(Instruction::LoadConst, vec![Operand::Reg(3), Operand::ShortNum16(0)]))
+        .add(Operation::new(Instruction::Assert, vec![Operand::Reg(0), Operand::String("message".into())])));
+    assert!(with_table.length_in_bytes() < bytecode.length_in_bytes());
+}
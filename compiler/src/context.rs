@@ -0,0 +1,94 @@
+use crate::bytecode::Bytecode;
+#[cfg(test)]
+use crate::bytecode::Instruction;
+use crate::compiler::BytecodeCompiler;
+use crate::error::CompilerResult;
+use crate::jshelper::JSSourceCode;
+#[cfg(test)]
+use crate::interpreter::BytecodeInterpreter;
+#[cfg(test)]
+use crate::value::JsValue;
+
+
+/// Compiles JavaScript source code piece by piece, like a REPL does
+///
+/// All pieces are compiled by the same compiler, so variables and functions declared by one piece
+/// are visible in the following ones. The bytecode of a piece must run on the virtual machine which
+/// ran the bytecode of the previous pieces, as variables are kept in its registers.
+///
+/// ```
+/// use jsyc_compiler::{BytecodeInterpreter, CompilerContext, JsValue};
+///
+/// let mut context = CompilerContext::new();
+/// let mut interpreter = BytecodeInterpreter::new();
+/// interpreter.run(&context.eval_incremental("var x = 5;").unwrap()).unwrap();
+/// assert_eq!(interpreter.run(&context.eval_incremental("x + 1").unwrap()), Ok(JsValue::Number(6.0)));
+/// ```
+#[derive(Clone)]
+pub struct CompilerContext {
+    compiler: BytecodeCompiler
+}
+
+impl CompilerContext {
+    /// Creates a context without any declarations
+    pub fn new() -> Self {
+        CompilerContext::with_compiler(BytecodeCompiler::new())
+    }
+
+    /// Creates a context which compiles with the given compiler, e.g. one with a custom configuration
+    /// or declared dependencies
+    pub fn with_compiler(compiler: BytecodeCompiler) -> Self {
+        CompilerContext { compiler }
+    }
+
+    /// Compiles the next piece of source code
+    ///
+    /// The bytecode contains all functions declared so far. If the compilation fails, the declarations
+    /// of the piece are discarded, so the context stays as it was before.
+    pub fn eval_incremental(&mut self, source: &str) -> CompilerResult<Bytecode> {
+        let previous = self.compiler.clone();
+        let result = self.compiler.compile(&JSSourceCode::from_str(source));
+        if result.is_err() {
+            self.compiler = previous;
+        }
+        result
+    }
+}
+
+impl Default for CompilerContext {
+    fn default() -> Self {
+        CompilerContext::new()
+    }
+}
+
+
+#[test]
+fn test_eval_incremental() {
+    let mut context = CompilerContext::new();
+    let mut interpreter = BytecodeInterpreter::new();
+
+    assert_eq!(interpreter.run(&context.eval_incremental("let x = 5;").unwrap()), Ok(JsValue::Number(5.0)));
+    assert_eq!(interpreter.run(&context.eval_incremental("x + 1").unwrap()), Ok(JsValue::Number(6.0)));
+    assert_eq!(interpreter.run(&context.eval_incremental("var y = x * 2; y - x").unwrap()), Ok(JsValue::Number(5.0)));
+    assert_eq!(interpreter.run(&context.eval_incremental("x = y; x").unwrap()), Ok(JsValue::Number(10.0)));
+
+    // Functions of previous pieces are part of the bytecode of the following pieces
+    context.eval_incremental("function inc(a) { return a + 1; }").unwrap();
+    let bytecode = context.eval_incremental("var z = inc(x);").unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::CallBytecodeFunc).is_some());
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ReturnBytecodeFunc).is_some());
+}
+
+#[test]
+fn test_eval_incremental_failure() {
+    let mut context = CompilerContext::new();
+    let mut interpreter = BytecodeInterpreter::new();
+
+    interpreter.run(&context.eval_incremental("var x = 1;").unwrap()).unwrap();
+    assert!(context.eval_incremental("var y = 2; var z = class {};").is_err());
+
+    // The declaration of y is discarded, thus y is an external declaration now
+    let bytecode = context.eval_incremental("var z = x + 1; y; z").unwrap();
+    assert!(context.compiler.decl_dependencies().decls_decps.contains_key("y"));
+    assert_eq!(interpreter.run(&bytecode), Ok(JsValue::Number(2.0)));
+}
@@ -0,0 +1,270 @@
+use crate::bytecode::{Bytecode, Operation, Instruction, Operand, OperandKind, crc32,
+                      BYTECODE_MAGIC, BYTECODE_VERSION};
+use crate::error::DecodeError;
+
+
+/// Reads values from encoded bytecode
+struct BytecodeReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    /// The strings of the string pool, if string operands are references into it
+    strings: Option<Vec<String>>
+}
+
+impl<'a> BytecodeReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BytecodeReader { bytes, offset: 0, strings: None }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], DecodeError> {
+        let bytes = self.bytes.get(self.offset..self.offset + count).ok_or(
+            DecodeError::UnexpectedEnd { offset: self.bytes.len() }
+        )?;
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    fn read_byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, DecodeError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from(bytes[0]) << 8 | u16::from(bytes[1]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(self.read_bytes(4)?.iter().fold(0, |num, &byte| num << 8 | u32::from(byte)))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(self.read_bytes(8)?.iter().fold(0, |num, &byte| num << 8 | u64::from(byte)))
+    }
+
+    fn read_string(&mut self) -> Result<String, DecodeError> {
+        let offset = self.offset;
+        let length = self.read_u16()? as usize;
+        let bytes = self.read_bytes(length)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidString { offset })
+    }
+
+    fn read_string_pool(&mut self) -> Result<Vec<String>, DecodeError> {
+        let count = self.read_u16()?;
+        (0..count).map(|_| self.read_string()).collect()
+    }
+
+    fn read_string_operand(&mut self) -> Result<String, DecodeError> {
+        if self.strings.is_none() {
+            return self.read_string();
+        }
+
+        let offset = self.offset;
+        let index = self.read_u16()?;
+        self.strings.iter().flatten().nth(index as usize).cloned().ok_or(
+            DecodeError::InvalidStringRef { index, offset })
+    }
+
+    fn read_operand(&mut self, kind: OperandKind) -> Result<Operand, DecodeError> {
+        Ok(match kind {
+            OperandKind::String => Operand::String(self.read_string_operand()?),
+            OperandKind::FloatNum => Operand::FloatNum(f64::from_bits(self.read_u64()?)),
+            OperandKind::LongNum => Operand::LongNum(self.read_u32()? as i32),
+            OperandKind::ShortNum => Operand::ShortNum(self.read_byte()?),
+            OperandKind::ShortNum16 => Operand::ShortNum16(self.read_u16()?),
+            OperandKind::Reg => Operand::Reg(self.read_byte()?),
+            OperandKind::RegistersArray => {
+                let length = self.read_byte()? as usize;
+                Operand::RegistersArray(self.read_bytes(length)?.to_vec())
+            },
+            OperandKind::RegEx => {
+                let pattern = self.read_string()?;
+                Operand::RegEx(pattern, self.read_byte()?)
+            },
+            OperandKind::PropertyKey => {
+                let offset = self.offset;
+                match self.read_byte()? {
+                    0 => Operand::Reg(self.read_byte()?),
+                    1 => Operand::SymbolKey(self.read_u32()?),
+                    tag => { return Err(DecodeError::InvalidPropertyKey { tag, offset }); }
+                }
+            }
+        })
+    }
+
+    fn read_operation(&mut self) -> Result<Operation, DecodeError> {
+        let offset = self.offset;
+        let opcode = self.read_byte()?;
+        let instruction = Instruction::from_byte(opcode).ok_or(
+            DecodeError::UnknownOpcode { opcode, offset }
+        )?;
+
+        let operands = instruction.expected_operand_types().iter()
+                                  .map(|&kind| self.read_operand(kind))
+                                  .collect::<Result<Vec<Operand>, DecodeError>>()?;
+
+        Ok(Operation::new(instruction, operands))
+    }
+}
+
+impl Bytecode {
+    /// Decodes encoded bytecode
+    ///
+    /// The decoded bytecode does not contain labels. All addresses are
+    /// [LongNum](../bytecode/enum.Operand.html#Operand::LongNum) operands.
+    pub fn decode(bytes: &[u8]) -> Result<Bytecode, DecodeError> {
+        let mut reader = BytecodeReader::new(bytes);
+        let mut bytecode = Bytecode::new();
+
+        while !reader.is_at_end() {
+            bytecode = bytecode.add(reader.read_operation()?);
+        }
+
+        Ok(bytecode)
+    }
+
+    /// Decodes bytecode that was encoded by [encode_with_string_pool](struct.Bytecode.html#method.encode_with_string_pool)
+    ///
+    /// The string references are replaced by the strings of the pool, thus the decoded bytecode
+    /// contains [String](../bytecode/enum.Operand.html#Operand::String) operands.
+    pub fn decode_with_string_pool(bytes: &[u8]) -> Result<Bytecode, DecodeError> {
+        let mut reader = BytecodeReader::new(bytes);
+        let strings = reader.read_string_pool()?;
+
+        let mut reader = BytecodeReader::new(&bytes[reader.offset..]);
+        reader.strings = Some(strings);
+        let mut bytecode = Bytecode::new();
+
+        while !reader.is_at_end() {
+            bytecode = bytecode.add(reader.read_operation()?);
+        }
+
+        Ok(bytecode)
+    }
+
+    /// Decodes bytecode that was encoded by [encode_with_checksum](struct.Bytecode.html#method.encode_with_checksum)
+    ///
+    /// Fails with ``ChecksumMismatch`` if the bytecode does not match the checksum in its header.
+    pub fn decode_verified(bytes: &[u8]) -> Result<Bytecode, DecodeError> {
+        let mut reader = BytecodeReader::new(bytes);
+        let expected = reader.read_u32()?;
+        let actual = crc32(&bytes[4..]);
+
+        if expected != actual {
+            return Err(DecodeError::ChecksumMismatch { expected, actual });
+        }
+
+        Bytecode::decode(&bytes[4..])
+    }
+
+    /// Decodes base64-encoded bytecode that was encoded by
+    /// [encode_versioned](struct.Bytecode.html#method.encode_versioned)
+    ///
+    /// Fails if the magic number is missing or if the format version is not the current one.
+    pub fn decode_versioned(encoded: &str) -> Result<Bytecode, DecodeError> {
+        let bytes = base64::decode(encoded).map_err(|_| DecodeError::InvalidBase64)?;
+        let mut reader = BytecodeReader::new(&bytes);
+
+        if reader.read_bytes(BYTECODE_MAGIC.len()).map_or(true, |magic| magic != BYTECODE_MAGIC) {
+            return Err(DecodeError::InvalidMagic);
+        }
+
+        let version = reader.read_u16()?;
+        if version != BYTECODE_VERSION {
+            return Err(DecodeError::UnsupportedVersion { version });
+        }
+
+        Bytecode::decode(&bytes[reader.offset..])
+    }
+}
+
+
+#[test]
+fn test_decode() {
+    use crate::bytecode::ToBytes;
+
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(0), Operand::String("test".into())]))
+        .add(Operation::new(Instruction::LoadFloatNum, vec![Operand::Reg(1), Operand::FloatNum(-12.5)]))
+        .add(Operation::new(Instruction::LoadLongNum, vec![Operand::Reg(2), Operand::LongNum(-1000)]))
+        .add(Operation::new(Instruction::LoadNum16, vec![Operand::Reg(2), Operand::ShortNum16(1000)]))
+        .add(Operation::new(Instruction::CreateRegExp, vec![Operand::Reg(3), Operand::RegEx("a+".into(), 3)]))
+        .add(Operation::new(Instruction::CallFunc, vec![Operand::Reg(4), Operand::Reg(5), Operand::Reg(6),
+                                                        Operand::RegistersArray(vec![0, 1])]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::LongNum(0)]))
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    assert_eq!(Bytecode::decode(&bytecode.to_bytes().unwrap()), Ok(bytecode));
+    assert_eq!(Bytecode::decode(&[]), Ok(Bytecode::new()));
+}
+
+#[test]
+fn test_decode_errors() {
+    assert_eq!(Bytecode::decode(&[0]), Err(DecodeError::UnknownOpcode { opcode: 0, offset: 0 }));
+    assert_eq!(Bytecode::decode(&[16, 250]), Err(DecodeError::UnknownOpcode { opcode: 250, offset: 1 }));
+    assert_eq!(Bytecode::decode(&[1, 0, 0, 5, 0x61]), Err(DecodeError::UnexpectedEnd { offset: 5 }));
+    assert_eq!(Bytecode::decode(&[1, 0, 0, 1, 0xff]), Err(DecodeError::InvalidString { offset: 2 }));
+}
+
+#[test]
+fn test_decode_with_string_pool() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(0), Operand::String("hello".into())]))
+        .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(0), Operand::LongNum(33)]))
+        .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(1), Operand::String("hello".into())]))
+        .add(Operation::new(Instruction::Assert, vec![Operand::Reg(1), Operand::String("world".into())]))
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    // The jump target moves to the Exit
+    let encoded = bytecode.encode_with_string_pool().unwrap();
+    assert_eq!(Bytecode::decode_with_string_pool(&encoded), Ok(Bytecode::new()
+        .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(0), Operand::String("hello".into())]))
+        .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(0), Operand::LongNum(18)]))
+        .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(1), Operand::String("hello".into())]))
+        .add(Operation::new(Instruction::Assert, vec![Operand::Reg(1), Operand::String("world".into())]))
+        .add(Operation::new(Instruction::Exit, vec![]))));
+
+    // The pool holds a single string
+    assert_eq!(Bytecode::decode_with_string_pool(&[0, 1, 0, 1, b'a', 1, 0, 0, 1]),
+               Err(DecodeError::InvalidStringRef { index: 1, offset: 2 }));
+    assert_eq!(Bytecode::decode_with_string_pool(&[0, 1]), Err(DecodeError::UnexpectedEnd { offset: 2 }));
+}
+
+#[test]
+fn test_decode_verified() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(10)]))
+        .add(Operation::new(Instruction::Exit, vec![]));
+
+    let mut encoded = bytecode.encode_with_checksum().unwrap();
+    assert_eq!(Bytecode::decode_verified(&encoded), Ok(bytecode.clone()));
+
+    encoded[6] = 11;
+    assert_eq!(Bytecode::decode_verified(&encoded), Err(DecodeError::ChecksumMismatch {
+        expected: bytecode.checksum().unwrap(),
+        actual: crc32(&encoded[4..])
+    }));
+
+    assert_eq!(Bytecode::decode_verified(&[0, 0]), Err(DecodeError::UnexpectedEnd { offset: 2 }));
+}
+
+#[test]
+fn test_decode_versioned() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(10)]));
+
+    let encoded = bytecode.encode_versioned().unwrap();
+    assert_eq!(base64::decode(&encoded).unwrap(), vec![b'J', b'S', b'Y', b'C', 0, 2, 2, 0, 10]);
+    assert_eq!(Bytecode::decode_versioned(&encoded), Ok(bytecode));
+
+    assert_eq!(Bytecode::decode_versioned("%%%"), Err(DecodeError::InvalidBase64));
+    assert_eq!(Bytecode::decode_versioned(&base64::encode(&[2, 0, 10])), Err(DecodeError::InvalidMagic));
+    assert_eq!(Bytecode::decode_versioned(&base64::encode(b"JSYC\x00\x03")),
+               Err(DecodeError::UnsupportedVersion { version: 3 }));
+    assert_eq!(Bytecode::decode_versioned(&base64::encode(b"JSYC\x00")),
+               Err(DecodeError::UnexpectedEnd { offset: 5 }));
+}
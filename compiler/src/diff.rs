@@ -0,0 +1,163 @@
+use crate::bytecode::{Bytecode, BytecodeElement};
+#[cfg(test)]
+use crate::bytecode::{Operation, Instruction, Operand};
+
+
+/// A difference between two bytecodes, as returned by [Bytecode::diff](../bytecode/struct.Bytecode.html#method.diff)
+#[derive(Debug, PartialEq, Clone)]
+pub enum BytecodeDiff {
+    /// The element only exists in the new bytecode
+    Added(BytecodeElement),
+    /// The element only exists in the old bytecode
+    Removed(BytecodeElement),
+    /// The element of the old bytecode was replaced by the element of the new bytecode
+    Changed(BytecodeElement, BytecodeElement)
+}
+
+enum Edit<'a> {
+    Equal,
+    Insert(&'a BytecodeElement),
+    Delete(&'a BytecodeElement)
+}
+
+/// Computes the shortest edit script from ``old`` to ``new`` with the Myers diff algorithm
+fn shortest_edit<'a>(old: &'a [BytecodeElement], new: &'a [BytecodeElement]) -> Vec<Edit<'a>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let offset = max as usize;
+    let index = |k: isize| (k + offset as isize) as usize;
+
+    // v[k] is the furthest x reached on diagonal k, trace holds v before each round d
+    let mut v = vec![0isize; 2 * offset + 2];
+    let mut trace = vec![];
+
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) { k + 1 } else { k - 1 };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert(&new[prev_y as usize]));
+            } else {
+                edits.push(Edit::Delete(&old[prev_x as usize]));
+            }
+        }
+
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    edits
+}
+
+impl Bytecode {
+    /// Compares this (old) bytecode with another (new) bytecode.
+    ///
+    /// Uses the Myers diff algorithm on the elements. Within a run of differing elements,
+    /// removed and added elements are paired up as ``Changed``.
+    pub fn diff(&self, other: &Bytecode) -> Vec<BytecodeDiff> {
+        let mut diffs = vec![];
+        let mut removed = vec![];
+        let mut added = vec![];
+
+        let mut flush = |removed: &mut Vec<&BytecodeElement>, added: &mut Vec<&BytecodeElement>| {
+            let changed = removed.len().min(added.len());
+            diffs.extend(removed.iter().zip(added.iter()).map(|(old, new)| {
+                BytecodeDiff::Changed((*old).clone(), (*new).clone())
+            }));
+            diffs.extend(removed.drain(..).skip(changed).map(|old| BytecodeDiff::Removed(old.clone())));
+            diffs.extend(added.drain(..).skip(changed).map(|new| BytecodeDiff::Added(new.clone())));
+        };
+
+        for edit in shortest_edit(&self.elements, &other.elements) {
+            match edit {
+                Edit::Equal => flush(&mut removed, &mut added),
+                Edit::Delete(element) => removed.push(element),
+                Edit::Insert(element) => added.push(element)
+            }
+        }
+        flush(&mut removed, &mut added);
+
+        diffs
+    }
+}
+
+
+#[cfg(test)]
+fn load_num(reg: u8, num: u8) -> BytecodeElement {
+    BytecodeElement::Operation(Operation::new(Instruction::LoadNum, vec![Operand::Reg(reg), Operand::ShortNum(num)]))
+}
+
+#[test]
+fn test_diff_identical() {
+    let bytecode = Bytecode::new().add_label(0).extend_with_labels(vec![load_num(0, 1), load_num(1, 2)]);
+    assert_eq!(bytecode.diff(&bytecode), vec![]);
+    assert_eq!(Bytecode::new().diff(&Bytecode::new()), vec![]);
+}
+
+#[test]
+fn test_diff_insertion() {
+    let old = Bytecode::new().extend_with_labels(vec![load_num(0, 1), load_num(2, 3)]);
+    let new = Bytecode::new().extend_with_labels(vec![load_num(0, 1), load_num(1, 2), load_num(2, 3)]);
+    assert_eq!(old.diff(&new), vec![BytecodeDiff::Added(load_num(1, 2))]);
+    assert_eq!(Bytecode::new().diff(&old), vec![BytecodeDiff::Added(load_num(0, 1)),
+                                               BytecodeDiff::Added(load_num(2, 3))]);
+}
+
+#[test]
+fn test_diff_deletion() {
+    let old = Bytecode::new().extend_with_labels(vec![load_num(0, 1), load_num(1, 2), load_num(2, 3)]);
+    let new = Bytecode::new().extend_with_labels(vec![load_num(0, 1), load_num(2, 3)]);
+    assert_eq!(old.diff(&new), vec![BytecodeDiff::Removed(load_num(1, 2))]);
+}
+
+#[test]
+fn test_diff_change() {
+    let old = Bytecode::new().extend_with_labels(vec![load_num(0, 1), load_num(1, 2), load_num(2, 3)]);
+    let new = Bytecode::new().extend_with_labels(vec![load_num(0, 1), load_num(1, 5), load_num(2, 3)]);
+    assert_eq!(old.diff(&new), vec![BytecodeDiff::Changed(load_num(1, 2), load_num(1, 5))]);
+}
+
+#[test]
+fn test_diff_swap() {
+    let old = Bytecode::new().extend_with_labels(vec![load_num(0, 1), load_num(1, 2)]);
+    let new = Bytecode::new().extend_with_labels(vec![load_num(1, 2), load_num(0, 1)]);
+    assert_eq!(old.diff(&new), vec![BytecodeDiff::Removed(load_num(0, 1)),
+                                    BytecodeDiff::Added(load_num(0, 1))]);
+}
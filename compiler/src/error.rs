@@ -1,3 +1,4 @@
+use crate::bytecode::Instruction;
 use ressa::Error as RessaError;
 use std::{error};
 
@@ -6,6 +7,19 @@ use std::{error};
 pub enum CompilerError {
     Parser(RessaError),
     Unsupported(String),
+    /// A string which is too long to be encoded. Holds the length of the string in bytes.
+    StringTooLong(usize),
+    /// A registers array (e.g. the arguments of a call) with more than 255 elements.
+    /// Holds the number of elements.
+    TooManyArguments(usize),
+    /// More distinct strings than a [string pool](../string_pool/struct.StringPool.html) can hold.
+    /// Holds the number of strings.
+    TooManyStrings(usize),
+    /// A function or branch address which was not resolved before encoding the bytecode.
+    /// Holds the unresolved operand.
+    UnresolvedAddress(String),
+    /// Syntax which is supported, but disabled by the [configuration](../config/struct.CompilerConfig.html)
+    UnsupportedSyntax(String),
     Custom(String)
 }
 
@@ -25,8 +39,13 @@ impl CompilerError {
     pub fn is_unsupported_feature(&self) -> bool {
         match self {
             CompilerError::Parser(_) |
+            CompilerError::StringTooLong(_) |
+            CompilerError::TooManyArguments(_) |
+            CompilerError::TooManyStrings(_) |
+            CompilerError::UnresolvedAddress(_) |
             CompilerError::Custom(_) => false,
-            CompilerError::Unsupported(_) => true
+            CompilerError::Unsupported(_) |
+            CompilerError::UnsupportedSyntax(_) => true
         }
     }
 }
@@ -35,6 +54,14 @@ impl std::fmt::Display for CompilerError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             CompilerError::Parser(ressa_error) => write!(f, "{}", ressa_error),
+            CompilerError::StringTooLong(len) => write!(f, "The string is too long ({} bytes). Encoded strings may only have {} bytes",
+                                                        len, u16::MAX),
+            CompilerError::TooManyArguments(count) => write!(f, "Too many arguments ({}). Registers arrays may only have {} elements",
+                                                             count, u8::MAX),
+            CompilerError::TooManyStrings(count) => write!(f, "Too many strings ({}). String pools may only have {} strings",
+                                                           count, u16::MAX),
+            CompilerError::UnresolvedAddress(operand) => write!(f, "The address {} was not resolved before encoding", operand),
+            CompilerError::UnsupportedSyntax(syntax) => write!(f, "{} are disabled by the configuration", syntax),
             CompilerError::Unsupported(string) |
             CompilerError::Custom(string) => write!(f, "{}", string)
         }
@@ -45,6 +72,11 @@ impl std::error::Error for CompilerError {
     fn description(&self) -> &str {
         match *self {
             CompilerError::Parser(_) => "An error during the parsing process",
+            CompilerError::StringTooLong(_) => "A string is too long to be encoded",
+            CompilerError::TooManyArguments(_) => "A registers array has too many elements to be encoded",
+            CompilerError::TooManyStrings(_) => "A string pool has too many strings to be encoded",
+            CompilerError::UnresolvedAddress(_) => "An address was not resolved before encoding",
+            CompilerError::UnsupportedSyntax(_) => "The syntax is disabled by the configuration",
             CompilerError::Unsupported(ref s) |
             CompilerError::Custom(ref s) => s.as_str(),
         }
@@ -58,6 +90,87 @@ impl std::error::Error for CompilerError {
     }
 }
 
+/// Represents code which compiles, but might not behave as expected
+///
+/// Warnings can be retrieved through [warnings](../compiler/struct.BytecodeCompiler.html#method.warnings)
+/// after the compilation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompilerWarning {
+    /// Syntax which is deprecated and forbidden in strict mode code. Holds a description of the syntax.
+    DeprecatedSyntax(String),
+}
+
+impl std::fmt::Display for CompilerWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CompilerWarning::DeprecatedSyntax(syntax) => write!(f, "{} are deprecated", syntax),
+        }
+    }
+}
+
+/// Represents errors that occur while decoding bytecode
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    /// The bytecode ended in the middle of an operation
+    UnexpectedEnd { offset: usize },
+    /// The byte at ``offset`` is not the opcode of a known instruction
+    UnknownOpcode { opcode: u8, offset: usize },
+    /// The string starting at ``offset`` is not valid UTF-8
+    InvalidString { offset: usize },
+    /// The checksum in the header does not match the checksum of the bytecode
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// The input is not valid base64
+    InvalidBase64,
+    /// The input does not start with the bytecode magic number
+    InvalidMagic,
+    /// The bytecode was encoded in an incompatible format version
+    UnsupportedVersion { version: u16 },
+    /// The property key at ``offset`` is neither a register nor a symbol key
+    InvalidPropertyKey { tag: u8, offset: usize },
+    /// The string reference at ``offset`` points behind the end of the string pool
+    InvalidStringRef { index: u16, offset: usize },
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd { offset } => write!(f, "Unexpected end of bytecode at offset {}", offset),
+            DecodeError::UnknownOpcode { opcode, offset } => write!(f, "Unknown opcode {} at offset {}", opcode, offset),
+            DecodeError::InvalidString { offset } => write!(f, "Invalid string at offset {}", offset),
+            DecodeError::ChecksumMismatch { expected, actual } =>
+                write!(f, "Checksum mismatch: expected {:#010x} but got {:#010x}", expected, actual),
+            DecodeError::InvalidBase64 => write!(f, "Invalid base64"),
+            DecodeError::InvalidMagic => write!(f, "Missing bytecode magic number"),
+            DecodeError::UnsupportedVersion { version } => write!(f, "Unsupported bytecode version {}", version),
+            DecodeError::InvalidPropertyKey { tag, offset } => write!(f, "Invalid property key {} at offset {}", tag, offset),
+            DecodeError::InvalidStringRef { index, offset } => write!(f, "Invalid string reference {} at offset {}", index, offset),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Represents errors that occur while [interpreting](../interpreter/struct.BytecodeInterpreter.html) bytecode
+#[derive(Debug, PartialEq)]
+pub enum RuntimeError {
+    /// The interpreter does not implement the instruction yet
+    UnsupportedInstruction(Instruction),
+    /// The operands do not match the ones the instruction expects
+    InvalidOperands(Instruction),
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RuntimeError::UnsupportedInstruction(instruction) =>
+                write!(f, "The instruction {} is not supported by the interpreter", instruction.to_str()),
+            RuntimeError::InvalidOperands(instruction) => write!(f, "Invalid operands for {}", instruction.to_str()),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
 impl From<RessaError> for CompilerError {
     fn from(err: RessaError) -> CompilerError {
         CompilerError::Parser(err)
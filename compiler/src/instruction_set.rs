@@ -20,7 +20,7 @@ macro_rules! make_enum_helper{
 
             pub fn enum_iterator() -> std::slice::Iter<'static, $name> {
                 const VARINTS: [$name; $name::enum_size()] = [$($name::$variants,)*];
-                VARINTS.into_iter()
+                VARINTS.iter()
             }
 
             pub fn variant_index(&self) -> usize {
@@ -88,6 +88,16 @@ impl CommonLiteral {
             _ => panic!("")
         }
     }
+
+    /// Returns the register the virtual machine initializes with this literal
+    pub fn default_reg(&self) -> Register {
+        match self {
+            CommonLiteral::Num0 => 255,
+            CommonLiteral::Num1 => 254,
+            CommonLiteral::Void0 => 253,
+            CommonLiteral::__VarinatsCountHelper__ => panic!("CommonLiteral::__VarinatsCountHelper__")
+        }
+    }
 }
 
 /// Represents common literals
@@ -127,6 +137,23 @@ enum ReservedeRegister {
     TrashRegister
 });
 
+impl ReservedeRegister {
+    /// Returns the register the virtual machine uses for this reserved register
+    pub fn default_reg(&self) -> Register {
+        match self {
+            ReservedeRegister::BytecodePointer => 200,
+            ReservedeRegister::BytecodeFuncReturn => 201,
+            ReservedeRegister::TrashRegister => 202,
+            ReservedeRegister::__VarinatsCountHelper__ => panic!("ReservedeRegister::__VarinatsCountHelper__")
+        }
+    }
+
+    /// Returns whether the given register is a reserved register
+    pub fn is_reserved(reg: Register) -> bool {
+        ReservedeRegister::enum_iterator().any(|mp_reg| mp_reg.default_reg() == reg)
+    }
+}
+
 /// Represents a set of reserved registers
 ///
 /// Reserved register are registers used by the virtual machine for special purposes.
@@ -141,12 +168,7 @@ impl ReservedeRegisters {
     pub fn new(scope: &mut Scope) -> CompilerResult<Self> {
         Ok(ReservedeRegisters {
             regs: ReservedeRegister::enum_iterator().map(|mp_reg| {
-                match mp_reg {
-                    ReservedeRegister::BytecodePointer => scope.try_reserve_specific_reg(200),
-                    ReservedeRegister::BytecodeFuncReturn => scope.try_reserve_specific_reg(201),
-                    ReservedeRegister::TrashRegister => scope.try_reserve_specific_reg(202),
-                    ReservedeRegister::__VarinatsCountHelper__ => panic!("ReservedeRegister::__VarinatsCountHelper__")
-                }
+                scope.try_reserve_specific_reg(mp_reg.default_reg())
             }).collect::<CompilerResult<Vec<Register>>>()?
         })
     }
@@ -157,6 +179,34 @@ impl ReservedeRegisters {
     }
 }
 
+/// A builtin collection a value is known to be, used to pick specialized instructions for its methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinCollection {
+    Array,
+    Set,
+    Map,
+    WeakMap
+}
+
+impl BuiltinCollection {
+    /// Returns the collection created by the constructor with the given name
+    pub fn from_constructor(constructor: &str) -> Option<Self> {
+        match constructor {
+            "Set" => Some(BuiltinCollection::Set),
+            "Map" => Some(BuiltinCollection::Map),
+            "WeakMap" => Some(BuiltinCollection::WeakMap),
+            _ => None
+        }
+    }
+}
+
+/// A builtin type other than a collection a value is known to be, used to pick specialized instructions for its methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinType {
+    String,
+    RegExp
+}
+
 
 #[derive(Clone)]
 pub struct InstructionSet
@@ -185,13 +235,23 @@ impl InstructionSet {
         self.reserved_regs.reg(common_lit)
     }
 
-    pub fn load_op(&self, left: Reg, right: Operand) -> Operation {
+    /// Returns the operation loading an operand into a register. Fails for symbol keys that are no
+    /// [well-known symbol](../bytecode/enum.WellKnownSymbol.html).
+    pub fn load_op(&self, left: Reg, right: Operand) -> CompilerResult<Operation> {
         let instruction = match right {
-            Operand::String(_) => Instruction::LoadString,
+            Operand::String(_) |
+            Operand::StringRef(_) => Instruction::LoadString,
             Operand::FloatNum(_) => Instruction::LoadFloatNum,
             Operand::LongNum(_) => Instruction::LoadLongNum,
             Operand::ShortNum(_) => Instruction::LoadNum,
+            Operand::ShortNum16(_) => Instruction::LoadNum16,
             Operand::Reg(_) => Instruction::Copy,
+            Operand::RegEx(_, _) => Instruction::CreateRegExp,
+            Operand::SymbolKey(index) => {
+                let symbol = WellKnownSymbol::from_index(index)
+                    .ok_or_else(|| CompilerError::Custom(format!("{} is no well-known symbol", right)))?;
+                return Ok(Operation::new(Instruction::LoadWellKnownSymbol, vec![Operand::Reg(left), symbol.into()]));
+            },
             Operand::RegistersArray(_) => unimplemented!("Register Arrays are not yet implement as seperte load operation"),
             Operand::FunctionAddr(_) |
             Operand::BranchAddr(_) |
@@ -199,7 +259,7 @@ impl InstructionSet {
             Operand::BytecodeEnd => unimplemented!("...")
         };
 
-        Operation::new(instruction, vec![Operand::Reg(left), right])
+        Ok(Operation::new(instruction, vec![Operand::Reg(left), right]))
     }
 
     pub fn assignment_op(&self, op: &AssignmentOperator, rd: Reg, rs: Reg) -> Operation {
@@ -245,9 +305,8 @@ impl InstructionSet {
                 Operand::Reg(rs)
                 ]
             ),
-            UnaryOperator::Plus => Operation::new(Instruction::Add, vec![
+            UnaryOperator::Plus => Operation::new(Instruction::ToNumber, vec![
                 Operand::Reg(rd),
-                Operand::Reg(self.common_literal_reg(&CommonLiteral::Num0)),
                 Operand::Reg(rs)
                 ]
             ),
@@ -260,6 +319,162 @@ impl InstructionSet {
         })
     }
 
+    /// Returns the specialized instruction for a method call of a value of a builtin type, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, receiver, args...``.
+    pub fn method_call_op(&self, receiver: BuiltinType, method: &str, args_count: usize) -> Option<Instruction> {
+        match (receiver, method, args_count) {
+            (BuiltinType::RegExp, "test", 1) => Some(Instruction::RegExpTest),
+            (BuiltinType::RegExp, "exec", 1) => Some(Instruction::RegExpExec),
+            (BuiltinType::String, "charAt", 1) => Some(Instruction::StringCharAt),
+            (BuiltinType::String, "charCodeAt", 1) => Some(Instruction::StringCharCodeAt),
+            (BuiltinType::String, "repeat", 1) => Some(Instruction::StringRepeat),
+            (BuiltinType::String, "trim", 0) => Some(Instruction::StringTrim),
+            (BuiltinType::String, "trimStart", 0) => Some(Instruction::StringTrimStart),
+            (BuiltinType::String, "trimEnd", 0) => Some(Instruction::StringTrimEnd),
+            (BuiltinType::String, "match", 1) => Some(Instruction::StringMatch),
+            (BuiltinType::String, "matchAll", 1) => Some(Instruction::StringMatchAll),
+            (BuiltinType::String, "replace", 2) => Some(Instruction::StringReplace),
+            (BuiltinType::String, "replaceAll", 2) => Some(Instruction::StringReplaceAll),
+            _ => None
+        }
+    }
+
+    /// Returns the instruction for a method call of a value of unknown type, if there is one
+    ///
+    /// The returned instruction looks the method up at runtime and expects its operands in the order
+    /// ``dst, receiver, args...``.
+    pub fn generic_method_call_op(&self, method: &str, args_count: usize) -> Option<Instruction> {
+        match (method, args_count) {
+            ("apply", 2) => Some(Instruction::CallApply),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a method call of a builtin collection, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, receiver, args...``, except for
+    /// ``ArrayConcat``, which expects ``dst, receiver`` followed by a registers array of any length.
+    pub fn collection_method_op(&self, collection: BuiltinCollection, method: &str, args_count: usize) -> Option<Instruction> {
+        match (collection, method, args_count) {
+            (BuiltinCollection::Array, "concat", _) => Some(Instruction::ArrayConcat),
+            (BuiltinCollection::Set, "add", 1) => Some(Instruction::SetAdd),
+            (BuiltinCollection::Set, "has", 1) => Some(Instruction::SetHas),
+            (BuiltinCollection::Set, "delete", 1) => Some(Instruction::SetDelete),
+            (BuiltinCollection::Map, "get", 1) => Some(Instruction::MapGet),
+            (BuiltinCollection::Map, "set", 2) => Some(Instruction::MapSet),
+            (BuiltinCollection::Map, "has", 1) => Some(Instruction::MapHas),
+            (BuiltinCollection::WeakMap, "get", 1) => Some(Instruction::WeakMapGet),
+            (BuiltinCollection::WeakMap, "set", 2) => Some(Instruction::WeakMapSet),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a call of a function of ``Math``, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, args...``, except for
+    /// ``MathMin`` and ``MathMax``, which expect ``dst`` followed by a registers array of any length.
+    pub fn math_call_op(&self, func: &str, args_count: usize) -> Option<Instruction> {
+        match (func, args_count) {
+            ("floor", 1) => Some(Instruction::MathFloor),
+            ("ceil", 1) => Some(Instruction::MathCeil),
+            ("round", 1) => Some(Instruction::MathRound),
+            ("abs", 1) => Some(Instruction::MathAbs),
+            ("sqrt", 1) => Some(Instruction::MathSqrt),
+            ("pow", 2) => Some(Instruction::MathPow),
+            ("min", _) => Some(Instruction::MathMin),
+            ("max", _) => Some(Instruction::MathMax),
+            ("random", 0) => Some(Instruction::MathRandom),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a call of a function of ``Date``, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, args...``.
+    pub fn date_call_op(&self, func: &str, args_count: usize) -> Option<Instruction> {
+        match (func, args_count) {
+            ("now", 0) => Some(Instruction::DateNow),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a call of a function of ``JSON``, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, args...``, including the
+    /// optional arguments.
+    pub fn json_call_op(&self, func: &str, args_count: usize) -> Option<Instruction> {
+        match (func, args_count) {
+            ("stringify", 1..=3) => Some(Instruction::JSONStringify),
+            ("parse", 1..=2) => Some(Instruction::JSONParse),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a call of a function of ``Object``, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, args...``, except for
+    /// ``ObjectAssign``, which expects ``dst, target`` followed by a registers array of the sources.
+    pub fn object_call_op(&self, func: &str, args_count: usize) -> Option<Instruction> {
+        match (func, args_count) {
+            ("assign", count) if count > 0 => Some(Instruction::ObjectAssign),
+            ("keys", 1) => Some(Instruction::ObjectKeys),
+            ("values", 1) => Some(Instruction::ObjectValues),
+            ("entries", 1) => Some(Instruction::ObjectEntries),
+            ("freeze", 1) => Some(Instruction::ObjectFreeze),
+            ("isFrozen", 1) => Some(Instruction::ObjectIsFrozen),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a call of a function of ``Array``, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, args...``, including the
+    /// optional arguments.
+    pub fn array_call_op(&self, func: &str, args_count: usize) -> Option<Instruction> {
+        match (func, args_count) {
+            ("from", 1..=2) => Some(Instruction::ArrayFrom),
+            ("isArray", 1) => Some(Instruction::ArrayIsArray),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a call of a function of ``Promise``, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, args...``.
+    pub fn promise_call_op(&self, func: &str, args_count: usize) -> Option<Instruction> {
+        match (func, args_count) {
+            ("all", 1) => Some(Instruction::PromiseAll),
+            ("race", 1) => Some(Instruction::PromiseRace),
+            ("any", 1) => Some(Instruction::PromiseAny),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a ``new`` expression of a builtin constructor, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, initial_values``.
+    pub fn new_op(&self, constructor: &str, args_count: usize) -> Option<Instruction> {
+        match (constructor, args_count) {
+            ("Set", 0..=1) => Some(Instruction::NewSet),
+            ("Map", 0..=1) => Some(Instruction::NewMap),
+            ("WeakMap", 0..=1) => Some(Instruction::NewWeakMap),
+            _ => None
+        }
+    }
+
+    /// Returns the specialized instruction for a call of a global builtin function, if there is one
+    ///
+    /// The returned instruction expects its operands in the order ``dst, args...``.
+    pub fn builtin_call_op(&self, func: &str, args_count: usize) -> Option<Instruction> {
+        match (func, args_count) {
+            ("String", 1) => Some(Instruction::TypeCoerceToString),
+            ("Number", 1) => Some(Instruction::TypeCoerceToNumber),
+            ("parseFloat", 1) => Some(Instruction::ParseFloat),
+            _ => None
+        }
+    }
+
     pub fn binary_op(&self, op: &BinaryOperator, rd: Reg, r0: Reg, r1: Reg) -> CompilerResult<Operation> {
         let instr = match op {
             BinaryOperator::Equal => Instruction::CompEqual,
@@ -290,3 +505,25 @@ impl InstructionSet {
         Ok(Operation::new(instr, vec![Operand::Reg(rd), Operand::Reg(r0), Operand::Reg(r1)]))
     }
 }
+
+
+#[test]
+fn test_collection_method_op() {
+    let isa = InstructionSet::default(&mut Scope::new());
+    assert_eq!(isa.collection_method_op(BuiltinCollection::Map, "get", 1), Some(Instruction::MapGet));
+    assert_eq!(isa.collection_method_op(BuiltinCollection::WeakMap, "get", 1), Some(Instruction::WeakMapGet));
+    assert_eq!(isa.collection_method_op(BuiltinCollection::WeakMap, "set", 2), Some(Instruction::WeakMapSet));
+    assert_eq!(isa.collection_method_op(BuiltinCollection::WeakMap, "has", 1), None);
+    assert_eq!(isa.collection_method_op(BuiltinCollection::Set, "get", 1), None);
+    assert_eq!(isa.collection_method_op(BuiltinCollection::Array, "concat", 3), Some(Instruction::ArrayConcat));
+}
+
+#[test]
+fn test_load_symbol_key() {
+    let isa = InstructionSet::default(&mut Scope::new());
+    assert_eq!(isa.load_op(0, Operand::symbol_key(WellKnownSymbol::ToStringTag)).unwrap(),
+               Operation::new(Instruction::LoadWellKnownSymbol, vec![Operand::Reg(0), Operand::ShortNum(11)]));
+    // Indices which are no well-known symbol are not truncated to a valid one
+    assert!(isa.load_op(0, Operand::SymbolKey(256)).is_err());
+    assert!(isa.load_op(0, Operand::SymbolKey(13)).is_err());
+}
@@ -0,0 +1,375 @@
+use crate::bytecode::{Bytecode, BytecodeElement, Operation, Instruction, Operand};
+use crate::error::RuntimeError;
+use crate::instruction_set::{CommonLiteral, InstructionSet};
+use crate::scope::{Register, Scope};
+use crate::value::JsValue;
+#[cfg(test)]
+use crate::compiler::BytecodeCompiler;
+#[cfg(test)]
+use crate::jshelper::JSSourceCode;
+
+use std::convert::TryFrom;
+
+
+/// Executes bytecode without a JavaScript engine
+///
+/// The interpreter only supports a subset of the instructions: loading literals, copying
+/// registers, arithmetic, comparisons, explicit type coercions, some string methods and some ``Math`` functions. It is meant for testing the compiler.
+///
+/// ```
+/// use jsyc_compiler::{BytecodeCompiler, BytecodeInterpreter, JSSourceCode, JsValue};
+///
+/// let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("1 + 2")).unwrap();
+/// assert_eq!(BytecodeInterpreter::new().run(&bytecode), Ok(JsValue::Number(3.0)));
+/// ```
+pub struct BytecodeInterpreter {
+    registers: [JsValue; 256]
+}
+
+impl BytecodeInterpreter {
+    /// Creates an interpreter whose registers hold the common literals like the virtual machine
+    pub fn new() -> Self {
+        let mut registers: [JsValue; 256] = std::array::from_fn(|_| JsValue::Undefined);
+        let isa = InstructionSet::default(&mut Scope::new());
+
+        registers[isa.common_literal_reg(&CommonLiteral::Num0) as usize] = JsValue::Number(0.0);
+        registers[isa.common_literal_reg(&CommonLiteral::Num1) as usize] = JsValue::Number(1.0);
+        registers[isa.common_literal_reg(&CommonLiteral::Void0) as usize] = JsValue::Undefined;
+
+        BytecodeInterpreter { registers }
+    }
+
+    /// Returns the value of a register
+    pub fn register(&self, reg: Register) -> &JsValue {
+        &self.registers[reg as usize]
+    }
+
+    /// Runs the bytecode until its end or an ``Exit`` instruction.
+    ///
+    /// Returns the completion value, i.e. the value last written to a register, like ``eval`` does.
+    pub fn run(&mut self, bc: &Bytecode) -> Result<JsValue, RuntimeError> {
+        let mut completion = JsValue::Undefined;
+
+        for element in bc.elements.iter() {
+            let cmd = match element {
+                BytecodeElement::Operation(cmd) => cmd,
+                BytecodeElement::Label(_) => continue
+            };
+
+            match cmd.instruction {
+                Instruction::Exit => break,
+                Instruction::Nop => {},
+                _ => {
+                    let (dst, value) = self.execute(cmd)?;
+                    self.registers[dst as usize] = value.clone();
+                    completion = value;
+                }
+            }
+        }
+
+        Ok(completion)
+    }
+
+    /// Executes a command and returns its destination register and the value to store in it
+    fn execute(&self, cmd: &Operation) -> Result<(Register, JsValue), RuntimeError> {
+        let invalid = || RuntimeError::InvalidOperands(cmd.instruction.clone());
+        let reg = |index: usize| match cmd.operands.get(index) {
+            Some(Operand::Reg(reg)) => Ok(*reg),
+            _ => Err(invalid())
+        };
+        let value = |index: usize| reg(index).map(|reg| &self.registers[reg as usize]);
+
+        let result = match cmd.instruction {
+            Instruction::LoadString |
+            Instruction::LoadNum |
+            Instruction::LoadNum16 |
+            Instruction::LoadLongNum |
+            Instruction::LoadFloatNum => match cmd.operands.get(1) {
+                Some(operand) => JsValue::try_from(operand.clone()).map_err(|_| invalid())?,
+                None => { return Err(invalid()); }
+            },
+            Instruction::Copy => value(1)?.clone(),
+            Instruction::TypeCoerceToString => JsValue::Str(value(1)?.to_string()),
+            Instruction::TypeCoerceToNumber => JsValue::Number(value(1)?.to_number()),
+            Instruction::GetLength => match value(1)? {
+                JsValue::Str(string) => JsValue::Number(string.encode_utf16().count() as f64),
+                _ => { return Err(invalid()); }
+            },
+            Instruction::StringIndexOf => match (value(1)?, value(2)?) {
+                (JsValue::Str(string), JsValue::Str(needle)) => JsValue::Number(index_of(string, needle, value(3)?)),
+                _ => { return Err(invalid()); }
+            },
+            Instruction::MathFloor => JsValue::Number(value(1)?.to_number().floor()),
+            Instruction::MathCeil => JsValue::Number(value(1)?.to_number().ceil()),
+            Instruction::MathRound => JsValue::Number(round(value(1)?.to_number())),
+            Instruction::MathAbs => JsValue::Number(value(1)?.to_number().abs()),
+            Instruction::MathSqrt => JsValue::Number(value(1)?.to_number().sqrt()),
+            Instruction::MathPow => JsValue::Number(pow(value(1)?.to_number(), value(2)?.to_number())),
+            Instruction::MathMin |
+            Instruction::MathMax => match cmd.operands.get(1) {
+                Some(Operand::RegistersArray(regs)) => {
+                    let nums = regs.iter().map(|reg| self.registers[*reg as usize].to_number());
+                    JsValue::Number(min_max(nums, cmd.instruction == Instruction::MathMax))
+                },
+                _ => { return Err(invalid()); }
+            },
+            Instruction::StringCharAt |
+            Instruction::StringCharCodeAt => match value(1)? {
+                JsValue::Str(string) => {
+                    let unit = char_code_at(string, value(2)?);
+                    match (&cmd.instruction, unit) {
+                        (Instruction::StringCharAt, Some(unit)) => JsValue::Str(String::from_utf16_lossy(&[unit])),
+                        (Instruction::StringCharAt, None) => JsValue::Str(String::new()),
+                        (_, Some(unit)) => JsValue::Number(f64::from(unit)),
+                        (_, None) => JsValue::Number(f64::NAN)
+                    }
+                },
+                _ => { return Err(invalid()); }
+            },
+            Instruction::StringSlice => match (value(1)?, cmd.operands.get(4)) {
+                (JsValue::Str(string), Some(Operand::ShortNum(kind))) => {
+                    JsValue::Str(slice_string(string, value(2)?, value(3)?, *kind == 1))
+                },
+                _ => { return Err(invalid()); }
+            },
+
+            Instruction::Add => value(1)? + value(2)?,
+            Instruction::Concat => JsValue::Str(format!("{}{}", value(1)?, value(2)?)),
+            Instruction::Minus => value(1)? - value(2)?,
+            Instruction::Mul => value(1)? * value(2)?,
+            Instruction::Div => value(1)? / value(2)?,
+            Instruction::AddImmediate => match cmd.operands.get(2) {
+                Some(Operand::ShortNum(num)) => value(1)? + &JsValue::Number(f64::from(*num)),
+                _ => { return Err(invalid()); }
+            },
+
+            Instruction::CompEqual => JsValue::Bool(value(1)?.loose_equals(value(2)?)),
+            Instruction::CompNotEqual => JsValue::Bool(!value(1)?.loose_equals(value(2)?)),
+            Instruction::CompStrictEqual => JsValue::Bool(value(1)?.strict_equals(value(2)?)),
+            Instruction::CompStrictNotEqual => JsValue::Bool(!value(1)?.strict_equals(value(2)?)),
+            Instruction::CompLessThan => JsValue::Bool(value(1)?.less_than(value(2)?)),
+            Instruction::CompGreaterThan => JsValue::Bool(value(2)?.less_than(value(1)?)),
+            Instruction::CompLessThanEqual => JsValue::Bool(is_less_or_equal(value(1)?, value(2)?)),
+            Instruction::CompGreaterThanEqual => JsValue::Bool(is_less_or_equal(value(2)?, value(1)?)),
+
+            _ => { return Err(RuntimeError::UnsupportedInstruction(cmd.instruction.clone())); }
+        };
+
+        Ok((reg(0)?, result))
+    }
+}
+
+impl Default for BytecodeInterpreter {
+    fn default() -> Self {
+        BytecodeInterpreter::new()
+    }
+}
+
+/// Evaluates ``string.slice(start, end)`` or, if ``is_substring`` is set, ``string.substring(start, end)``
+fn slice_string(string: &str, start: &JsValue, end: &JsValue, is_substring: bool) -> String {
+    let units: Vec<u16> = string.encode_utf16().collect();
+    let len = units.len() as f64;
+
+    let to_index = |value: &JsValue, default: f64| {
+        let num = match value {
+            JsValue::Undefined => default,
+            value => value.to_number().trunc()
+        };
+        let num = if num.is_nan() { 0.0 } else { num };
+        let num = if num < 0.0 && !is_substring { len + num } else { num };
+        num.max(0.0).min(len) as usize
+    };
+
+    let (start, end) = (to_index(start, 0.0), to_index(end, len));
+    let (start, end) = if is_substring && start > end { (end, start) } else { (start, end.max(start)) };
+
+    String::from_utf16_lossy(&units[start..end])
+}
+
+/// Evaluates ``string.indexOf(needle, start)``
+fn index_of(string: &str, needle: &str, start: &JsValue) -> f64 {
+    let units: Vec<u16> = string.encode_utf16().collect();
+    let needle: Vec<u16> = needle.encode_utf16().collect();
+
+    let start = start.to_number().trunc();
+    let start = if start.is_nan() { 0 } else { start.max(0.0).min(units.len() as f64) as usize };
+
+    if needle.is_empty() {
+        return start as f64;
+    }
+
+    units[start..].windows(needle.len())
+        .position(|window| window == needle.as_slice())
+        .map_or(-1.0, |index| (start + index) as f64)
+}
+
+/// Evaluates ``Math.round(num)``, which rounds halves up unlike ``f64::round``
+fn round(num: f64) -> f64 {
+    let rounded = num.round();
+    if num - rounded == 0.5 { rounded + 1.0 } else { rounded }
+}
+
+/// Evaluates ``Math.pow(base, exponent)``, which unlike ``f64::powf`` is ``NaN`` for a base of 1 or -1
+/// and an infinite or ``NaN`` exponent
+fn pow(base: f64, exponent: f64) -> f64 {
+    if base.abs() == 1.0 && !exponent.is_finite() { f64::NAN } else { base.powf(exponent) }
+}
+
+/// Evaluates ``Math.min(nums...)`` or ``Math.max(nums...)``, which are ``NaN`` if any number is ``NaN``
+/// and consider -0 to be smaller than +0
+fn min_max(nums: impl Iterator<Item = f64>, max: bool) -> f64 {
+    let start = if max { f64::NEG_INFINITY } else { f64::INFINITY };
+    nums.fold(start, |acc, num| {
+        if acc.is_nan() || num.is_nan() {
+            f64::NAN
+        } else if num == acc {
+            // Only differs for zeros
+            if num.is_sign_negative() != max { num } else { acc }
+        } else if (num > acc) == max {
+            num
+        } else {
+            acc
+        }
+    })
+}
+
+/// Returns the UTF-16 code unit at an index, or ``None`` if it is out of bounds
+fn char_code_at(string: &str, index: &JsValue) -> Option<u16> {
+    let index = index.to_number().trunc();
+    let index = if index.is_nan() { 0.0 } else { index };
+
+    if index < 0.0 {
+        return None;
+    }
+    string.encode_utf16().nth(index as usize)
+}
+
+/// Evaluates ``left <= right``, which is false if one side is ``NaN``
+fn is_less_or_equal(left: &JsValue, right: &JsValue) -> bool {
+    match (left.to_primitive(), right.to_primitive()) {
+        (JsValue::Str(left), JsValue::Str(right)) => left <= right,
+        (left, right) => left.to_number() <= right.to_number()
+    }
+}
+
+
+#[cfg(test)]
+fn compile_and_run(js_code: &str) -> Result<JsValue, RuntimeError> {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).unwrap();
+    BytecodeInterpreter::new().run(&bytecode)
+}
+
+#[test]
+fn test_run_arithmetic() {
+    assert_eq!(compile_and_run("1 + 2"), Ok(JsValue::Number(3.0)));
+    assert_eq!(compile_and_run("var a = 300; var b = a - 1.5 * 2"), Ok(JsValue::Number(297.0)));
+    assert_eq!(compile_and_run("var a = 10 / 4"), Ok(JsValue::Number(2.5)));
+    assert_eq!(compile_and_run("var a = 'a' + 1"), Ok(JsValue::Str("a1".into())));
+    assert_eq!(compile_and_run("var a = 1; a += 1000"), Ok(JsValue::Number(1001.0)));
+}
+
+#[test]
+fn test_run_comparisons() {
+    assert_eq!(compile_and_run("var a = 3; var b = a * 2 < 7"), Ok(JsValue::Bool(true)));
+    assert_eq!(compile_and_run("var a = 3 >= 4"), Ok(JsValue::Bool(false)));
+    assert_eq!(compile_and_run("var a = '1' == 1"), Ok(JsValue::Bool(true)));
+    assert_eq!(compile_and_run("var a = '1' === 1"), Ok(JsValue::Bool(false)));
+    assert_eq!(compile_and_run("var a = 'b' > 'a'"), Ok(JsValue::Bool(true)));
+}
+
+#[test]
+fn test_run_coercions() {
+    assert_eq!(compile_and_run("var a = String(1 + 2)"), Ok(JsValue::Str("3".into())));
+    assert_eq!(compile_and_run("var a = Number('4') + 1"), Ok(JsValue::Number(5.0)));
+    assert_eq!(compile_and_run("var a = Number(String(0.5))"), Ok(JsValue::Number(0.5)));
+}
+
+#[test]
+fn test_run_string_length() {
+    assert_eq!(compile_and_run("var a = 'abc'.length"), Ok(JsValue::Number(3.0)));
+    assert_eq!(compile_and_run("var a = 'a' + 1.5; var b = ('x' + a).length"), Ok(JsValue::Number(5.0)));
+    assert_eq!(compile_and_run("var a = '\u{1F600}'.length"), Ok(JsValue::Number(2.0)));
+}
+
+#[test]
+fn test_run_string_slice() {
+    let slice = |js_code: &str| compile_and_run(&format!("const s = 'abcdef'; var r = {}", js_code));
+
+    assert_eq!(slice("s.slice(1, 3)"), Ok(JsValue::Str("bc".into())));
+    assert_eq!(slice("s.substring(1, 3)"), Ok(JsValue::Str("bc".into())));
+    assert_eq!(slice("s.slice(2)"), Ok(JsValue::Str("cdef".into())));
+
+    // Negative indices
+    assert_eq!(slice("s.slice(-2)"), Ok(JsValue::Str("ef".into())));
+    assert_eq!(slice("s.slice(1, -1)"), Ok(JsValue::Str("bcde".into())));
+    assert_eq!(slice("s.substring(-2, 2)"), Ok(JsValue::Str("ab".into())));
+
+    // Out of range indices
+    assert_eq!(slice("s.slice(4, 100)"), Ok(JsValue::Str("ef".into())));
+    assert_eq!(slice("s.slice(-100, 1)"), Ok(JsValue::Str("a".into())));
+    assert_eq!(slice("s.substring(100)"), Ok(JsValue::Str("".into())));
+
+    // Reversed arguments
+    assert_eq!(slice("s.slice(3, 1)"), Ok(JsValue::Str("".into())));
+    assert_eq!(slice("s.substring(3, 1)"), Ok(JsValue::Str("bc".into())));
+}
+
+#[test]
+fn test_run_string_index_of() {
+    let index_of = |js_code: &str| compile_and_run(&format!("const s = 'abcabc'; var r = {}", js_code));
+
+    assert_eq!(index_of("s.indexOf('c')"), Ok(JsValue::Number(2.0)));
+    assert_eq!(index_of("s.indexOf('x')"), Ok(JsValue::Number(-1.0)));
+    assert_eq!(index_of("s.indexOf('bc', 2)"), Ok(JsValue::Number(4.0)));
+    assert_eq!(index_of("s.indexOf('a', 100)"), Ok(JsValue::Number(-1.0)));
+    assert_eq!(index_of("s.indexOf('')"), Ok(JsValue::Number(0.0)));
+    assert_eq!(index_of("s.indexOf('', 3)"), Ok(JsValue::Number(3.0)));
+    assert_eq!(index_of("s.indexOf('', 100)"), Ok(JsValue::Number(6.0)));
+    assert_eq!(index_of("s.indexOf('abcabcabc')"), Ok(JsValue::Number(-1.0)));
+}
+
+#[test]
+fn test_run_string_char_at() {
+    let char_at = |js_code: &str| compile_and_run(&format!("const s = 'abc'; var r = {}", js_code));
+
+    assert_eq!(char_at("s.charAt(1)"), Ok(JsValue::Str("b".into())));
+    assert_eq!(char_at("s.charAt(3)"), Ok(JsValue::Str("".into())));
+    assert_eq!(char_at("s.charAt(-1)"), Ok(JsValue::Str("".into())));
+    assert_eq!(char_at("s.charAt(1.7)"), Ok(JsValue::Str("b".into())));
+    assert_eq!(char_at("s.charCodeAt(0)"), Ok(JsValue::Number(97.0)));
+    assert!(matches!(char_at("s.charCodeAt(5)"), Ok(JsValue::Number(num)) if num.is_nan()));
+}
+
+#[test]
+fn test_run_math() {
+    assert_eq!(compile_and_run("var a = Math.floor(-1.5)"), Ok(JsValue::Number(-2.0)));
+    assert_eq!(compile_and_run("var a = Math.ceil(1.2)"), Ok(JsValue::Number(2.0)));
+    assert_eq!(compile_and_run("var a = Math.round(2.5)"), Ok(JsValue::Number(3.0)));
+    assert_eq!(compile_and_run("var a = Math.round(-2.5)"), Ok(JsValue::Number(-2.0)));
+    assert_eq!(compile_and_run("var a = Math.round(-2.6)"), Ok(JsValue::Number(-3.0)));
+    assert_eq!(compile_and_run("var a = Math.abs(-3) + Math.abs('4')"), Ok(JsValue::Number(7.0)));
+    assert_eq!(compile_and_run("var a = Math.sqrt(16) + Math.pow(2, 10)"), Ok(JsValue::Number(1028.0)));
+    assert!(matches!(compile_and_run("var a = Math.pow(1, 0 / 0)"), Ok(JsValue::Number(num)) if num.is_nan()));
+    assert_eq!(compile_and_run("var a = Math.min(3, 1, 2) + Math.max(3, 1, 2)"), Ok(JsValue::Number(4.0)));
+    assert_eq!(compile_and_run("var a = Math.min()"), Ok(JsValue::Number(f64::INFINITY)));
+    assert_eq!(compile_and_run("var a = Math.max()"), Ok(JsValue::Number(f64::NEG_INFINITY)));
+    assert!(matches!(compile_and_run("var a = Math.max(1, 'x')"), Ok(JsValue::Number(num)) if num.is_nan()));
+    assert!(min_max(vec![0.0, -0.0].into_iter(), false).is_sign_negative());
+    assert!(min_max(vec![-0.0, 0.0].into_iter(), true).is_sign_positive());
+}
+
+#[test]
+fn test_run_unsupported() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(1)]))
+        .add(Operation::new(Instruction::Throw, vec![Operand::Reg(0)]));
+
+    let mut interpreter = BytecodeInterpreter::new();
+    assert_eq!(interpreter.run(&bytecode), Err(RuntimeError::UnsupportedInstruction(Instruction::Throw)));
+    assert_eq!(interpreter.register(0), &JsValue::Number(1.0));
+
+    assert_eq!(BytecodeInterpreter::new().run(&Bytecode::new()
+                .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::ShortNum(1), Operand::Reg(2)]))),
+               Err(RuntimeError::InvalidOperands(Instruction::Add)));
+    assert_eq!(BytecodeInterpreter::new().run(&Bytecode::new()), Ok(JsValue::Undefined));
+}
@@ -3,15 +3,36 @@ extern crate resast;
 extern crate base64;
 
 pub mod error;
+pub mod config;
 pub mod bytecode;
+pub mod decoder;
 pub mod jshelper;
 pub mod compiler;
+pub mod context;
 pub mod scope;
 pub mod instruction_set;
+pub mod optimization;
+pub mod pass;
+pub mod cfg;
+pub mod constant_table;
+pub mod diff;
+pub mod string_pool;
+pub mod value;
+pub mod interpreter;
 
-pub use crate::bytecode::{Bytecode, BytecodeElement, Operation, Instruction, Operand, ToBytes};
+pub use crate::bytecode::{Bytecode, BytecodeElement, Operation, Instruction, MemoryOrdering, Operand, OperandKind, StringSliceKind,
+                            ToBytes, WellKnownSymbol};
+pub use crate::cfg::{BasicBlock, ControlFlowGraph};
+pub use crate::constant_table::{ConstantTable};
 pub use crate::compiler::{BytecodeCompiler, DeclDepencies};
-pub use crate::error::{CompilerResult, CompilerError};
+pub use crate::context::{CompilerContext};
+pub use crate::diff::{BytecodeDiff};
+pub use crate::config::{CompilerConfig};
+pub use crate::error::{CompilerResult, CompilerError, CompilerWarning, DecodeError, RuntimeError};
 pub use crate::instruction_set::{InstructionSet};
+pub use crate::interpreter::{BytecodeInterpreter};
 pub use crate::jshelper::{JSSourceCode, JSAst};
-pub use crate::scope::{Register};
+pub use crate::pass::{Pass, PassManager};
+pub use crate::scope::{Register, SymbolTable};
+pub use crate::string_pool::{StringPool};
+pub use crate::value::{JsValue};
@@ -0,0 +1,866 @@
+use crate::bytecode::{Bytecode, BytecodeElement, Operation, Instruction, Operand, Label};
+use crate::scope::Register;
+use crate::instruction_set::{CommonLiteral, ReservedeRegister};
+use crate::error::{CompilerError, CompilerResult};
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+
+/// Maps the index of an [operation](../bytecode/struct.Operation.html) to the registers that are
+/// live right after it was executed
+///
+/// The index counts operations only, labels are skipped.
+pub type LivenessMap = HashMap<usize, HashSet<Register>>;
+
+/// The maximal [number of instructions](../bytecode/struct.Bytecode.html#method.instruction_count)
+/// of a function body that is [inlined](../bytecode/struct.Bytecode.html#method.inline_function)
+pub const INLINE_THRESHOLD: usize = 16;
+
+
+/// Returns all registers referenced by an operand
+fn operand_registers(operand: &Operand) -> Vec<Register> {
+    match operand {
+        Operand::Reg(reg) => vec![*reg],
+        Operand::RegistersArray(regs) => regs.clone(),
+        Operand::FunctionArguments(args) => args.args.clone(),
+        _ => vec![]
+    }
+}
+
+/// Replaces all registers referenced by an operand according to ``mapping``
+fn rename_operand_registers(operand: &mut Operand, mapping: &HashMap<Register, Register>) {
+    let rename = |reg: &mut Register| if let Some(new_reg) = mapping.get(reg) { *reg = *new_reg; };
+
+    match operand {
+        Operand::Reg(reg) => rename(reg),
+        Operand::RegistersArray(regs) => regs.iter_mut().for_each(rename),
+        Operand::FunctionArguments(args) => args.args.iter_mut().for_each(rename),
+        _ => {}
+    }
+}
+
+/// Counts how often each register is referenced in the bytecode
+fn count_register_uses(bytecode: &Bytecode) -> HashMap<Register, usize> {
+    let mut uses = HashMap::new();
+
+    for element in bytecode.elements.iter() {
+        if let BytecodeElement::Operation(cmd) = element {
+            for reg in cmd.operands.iter().flat_map(operand_registers) {
+                *uses.entry(reg).or_insert(0) += 1;
+            }
+        }
+    }
+
+    uses
+}
+
+/// Returns whether an operation calls a bytecode function for the elements of an array
+///
+/// The parameter registers of the callback are passed as a registers array.
+fn calls_bytecode_callback(op: &Operation) -> bool {
+    matches!(op.instruction,
+        Instruction::ArraySort |
+        Instruction::ArrayMap |
+        Instruction::ArrayFilter |
+        Instruction::ArrayReduce |
+        Instruction::ArrayFind |
+        Instruction::ArrayFindIndex |
+        Instruction::ArrayEvery |
+        Instruction::ArraySome |
+        Instruction::ArrayForEach |
+        Instruction::ArrayFlatMap)
+}
+
+/// Returns whether an operation may run arbitrary bytecode or leaves the current function
+///
+/// Such operations might read any register.
+fn reads_all_registers(op: &Operation) -> bool {
+    calls_bytecode_callback(op) || matches!(op.instruction,
+        Instruction::CallFunc |
+        Instruction::CallApply |
+        Instruction::CallBytecodeFunc |
+        Instruction::ReturnBytecodeFunc |
+        Instruction::TailCall |
+        Instruction::Try)
+}
+
+/// Returns whether the first operand of an operation is always written by it
+fn writes_first_operand(op: &Operation) -> bool {
+    !matches!(op.instruction,
+        Instruction::CallBytecodeFunc |
+        Instruction::ReturnBytecodeFunc |
+        Instruction::TailCall |
+        Instruction::PropertySet |
+        Instruction::WithStatement |
+        Instruction::Assert |
+        Instruction::ConsoleLog |
+        Instruction::ArraySet |
+        Instruction::ArrayForEach |
+        Instruction::Try |
+        Instruction::Throw |
+        Instruction::Exit |
+        Instruction::JumpCond |
+        Instruction::Jump |
+        Instruction::JumpCondNeg)
+}
+
+/// Returns whether an operation reads its first operand before it writes it
+///
+/// ``DefaultParam`` only overwrites the parameter if it is ``undefined``.
+fn reads_first_operand(op: &Operation) -> bool {
+    matches!(op.instruction, Instruction::DefaultParam)
+}
+
+/// Returns the registers written by an operation
+fn defined_registers(op: &Operation) -> Vec<Register> {
+    match op.operands.first() {
+        Some(Operand::Reg(reg)) if writes_first_operand(op) => vec![*reg],
+        _ => vec![]
+    }
+}
+
+/// Returns the registers read by an operation
+fn used_registers(op: &Operation) -> Vec<Register> {
+    if reads_all_registers(op) {
+        return (0..=Register::MAX).collect();
+    }
+
+    let skip = if writes_first_operand(op) && !reads_first_operand(op) { 1 } else { 0 };
+    op.operands.iter().skip(skip).flat_map(operand_registers).collect()
+}
+
+/// Returns whether the execution may continue with the next operation
+fn falls_through(op: &Operation) -> bool {
+    !matches!(op.instruction,
+        Instruction::Jump |
+        Instruction::Exit |
+        Instruction::Throw |
+        Instruction::ReturnBytecodeFunc |
+        Instruction::TailCall)
+}
+
+/// Returns the labels an operation may branch to
+fn branch_targets(op: &Operation) -> impl Iterator<Item = Label> + '_ {
+    op.operands.iter().filter_map(|operand| match operand {
+        Operand::BranchAddr(token) => Some(token.label),
+        _ => None
+    })
+}
+
+impl Bytecode {
+    /// Computes the registers that are live after each [operation](struct.Operation.html)
+    ///
+    /// This is a backwards dataflow analysis over the operations of this bytecode. It is
+    /// conservative: Calls, returns and ``Try`` operations are assumed to read every register
+    /// and since any operation might throw, the handlers of all ``Try`` operations are treated
+    /// as successors of every operation. Since the branch targets are resolved by their labels,
+    /// the analysis must run before the label addresses are finalized.
+    pub fn compute_liveness(&self) -> LivenessMap {
+        let mut ops = vec![];
+        let mut label_targets = HashMap::new();
+
+        for element in self.elements.iter() {
+            match element {
+                BytecodeElement::Operation(op) => ops.push(op),
+                BytecodeElement::Label(label) => { label_targets.insert(*label, ops.len()); }
+            }
+        }
+
+        let handlers: Vec<usize> = ops.iter()
+            .filter(|op| op.instruction == Instruction::Try)
+            .flat_map(|op| branch_targets(op))
+            .filter_map(|label| label_targets.get(&label).cloned())
+            .collect();
+
+        let successors: Vec<Vec<usize>> = ops.iter().enumerate().map(|(idx, op)| {
+            let mut succs: Vec<usize> = branch_targets(op)
+                .filter_map(|label| label_targets.get(&label).cloned())
+                .chain(handlers.iter().cloned())
+                .collect();
+            if falls_through(op) {
+                succs.push(idx + 1);
+            }
+            succs.into_iter().filter(|&succ| succ < ops.len()).collect()
+        }).collect();
+
+        let defs: Vec<Vec<Register>> = ops.iter().map(|op| defined_registers(op)).collect();
+        let uses: Vec<Vec<Register>> = ops.iter().map(|op| used_registers(op)).collect();
+
+        let mut live_in: Vec<HashSet<Register>> = vec![HashSet::new(); ops.len()];
+        let mut live_out: Vec<HashSet<Register>> = vec![HashSet::new(); ops.len()];
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for idx in (0..ops.len()).rev() {
+                let out: HashSet<Register> = successors[idx].iter()
+                    .flat_map(|&succ| live_in[succ].iter().cloned())
+                    .collect();
+
+                let mut inp: HashSet<Register> = out.iter()
+                    .filter(|reg| !defs[idx].contains(reg))
+                    .cloned()
+                    .collect();
+                inp.extend(uses[idx].iter().cloned());
+
+                if inp != live_in[idx] || out != live_out[idx] {
+                    changed = true;
+                    live_in[idx] = inp;
+                    live_out[idx] = out;
+                }
+            }
+        }
+
+        live_out.into_iter().enumerate().collect()
+    }
+
+    /// Replaces all ``Copy`` and ``Load*`` operations that write a dead register with ``Nop``
+    ///
+    /// A register is dead if it is not live after the operation according to ``liveness``,
+    /// which must have been [computed](struct.Bytecode.html#method.compute_liveness) for this
+    /// bytecode. Stores to reserved registers are kept, since they are read by the virtual machine.
+    /// Returns the number of eliminated stores.
+    pub fn eliminate_dead_stores(&mut self, liveness: &LivenessMap) -> usize {
+        let mut eliminated = 0;
+
+        for (idx, op) in self.commands_iter_mut().enumerate() {
+            if !is_store(op) {
+                continue;
+            }
+
+            let is_dead = match (op.operands.first(), liveness.get(&idx)) {
+                (Some(Operand::Reg(reg)), Some(live)) => !live.contains(reg) && !ReservedeRegister::is_reserved(*reg),
+                _ => false
+            };
+
+            if is_dead {
+                *op = Operation::new(Instruction::Nop, vec![]);
+                eliminated += 1;
+            }
+        }
+
+        eliminated
+    }
+
+    /// Reduces the number of used registers by letting registers with disjoint lifetimes share a register
+    ///
+    /// Builds an interference graph from ``liveness``, which must have been
+    /// [computed](struct.Bytecode.html#method.compute_liveness) for this bytecode, and colors it
+    /// greedily. Registers that are read before they are written, reserved registers and registers
+    /// passed between bytecode functions keep their number. Only registers already referenced by
+    /// this bytecode are assigned, thus the bytecode must not share registers with other
+    /// bytecode that is not part of it.
+    pub fn coalesce_registers(&self, liveness: &LivenessMap) -> Bytecode {
+        let ops: Vec<&Operation> = self.elements.iter().filter_map(|element| match element {
+            BytecodeElement::Operation(op) => Some(op),
+            BytecodeElement::Label(_) => None
+        }).collect();
+
+        let referenced: BTreeSet<Register> = ops.iter()
+            .flat_map(|op| op.operands.iter().flat_map(operand_registers))
+            .collect();
+        let defined: HashSet<Register> = ops.iter().flat_map(|op| defined_registers(op)).collect();
+
+        let mut pinned: HashSet<Register> = referenced.iter()
+            .filter(|reg| !defined.contains(reg) || ReservedeRegister::is_reserved(**reg))
+            .cloned()
+            .collect();
+
+        if let Some(first_op) = ops.first() {
+            let first_defs = defined_registers(first_op);
+            pinned.extend(used_registers(first_op));
+            pinned.extend(liveness.get(&0).into_iter().flatten().filter(|reg| !first_defs.contains(reg)));
+        }
+
+        for op in ops.iter().filter(|op| calls_bytecode_callback(op) || matches!(op.instruction,
+                Instruction::CallBytecodeFunc |
+                Instruction::ReturnBytecodeFunc |
+                Instruction::TailCall |
+                Instruction::BytecodeFuncCallback |
+                Instruction::Try)) {
+            pinned.extend(op.operands.iter().flat_map(operand_registers));
+        }
+
+        let mut interferences: HashMap<Register, HashSet<Register>> = HashMap::new();
+        for (idx, op) in ops.iter().enumerate() {
+            for def in defined_registers(op) {
+                for &live in liveness.get(&idx).into_iter().flatten().filter(|&&live| live != def) {
+                    interferences.entry(def).or_default().insert(live);
+                    interferences.entry(live).or_default().insert(def);
+                }
+            }
+        }
+
+        let mut coloring: HashMap<Register, Register> = pinned.iter().map(|&reg| (reg, reg)).collect();
+        for &reg in referenced.iter().filter(|reg| !pinned.contains(reg)) {
+            let taken: HashSet<Register> = interferences.get(&reg).into_iter().flatten()
+                .filter_map(|neighbour| coloring.get(neighbour).cloned())
+                .collect();
+
+            let color = referenced.iter().find(|color| !taken.contains(color)).cloned().unwrap_or(reg);
+            coloring.insert(reg, color);
+        }
+
+        let mut bytecode = self.clone();
+        for op in bytecode.commands_iter_mut() {
+            for operand in op.operands.iter_mut() {
+                rename_operand_registers(operand, &coloring);
+            }
+        }
+
+        bytecode
+    }
+
+    /// Replaces ``LoadNum`` + ``Add`` sequences with a single [AddImmediate](enum.Instruction.html#Instruction::AddImmediate)
+    ///
+    /// ``LoadNum tmp, imm; Add dst, src, tmp`` becomes ``AddImmediate dst, src, imm``, if ``tmp``
+    /// is used nowhere else. Since the compiler loads ``1`` from the register holding this common
+    /// literal, ``Add dst, src, <1>`` like in ``i += 1`` becomes ``AddImmediate dst, src, 1``.
+    /// Since this changes the length of the bytecode, this pass must run before the label addresses
+    /// are finalized.
+    pub fn peephole_add_immediate(self) -> Bytecode {
+        let reg_uses = count_register_uses(&self);
+
+        let mut elements = Vec::with_capacity(self.elements.len());
+        let mut iter = self.elements.into_iter().peekable();
+
+        while let Some(element) = iter.next() {
+            if let BytecodeElement::Operation(add) = &element {
+                if let Some(fused) = add_one_immediate(add) {
+                    elements.push(BytecodeElement::Operation(fused));
+                    continue;
+                }
+            }
+
+            if let (BytecodeElement::Operation(load), Some(BytecodeElement::Operation(add))) = (&element, iter.peek()) {
+                if let Some(fused) = fuse_add_immediate(load, add, &reg_uses) {
+                    iter.next();
+                    elements.push(BytecodeElement::Operation(fused));
+                    continue;
+                }
+            }
+
+            elements.push(element);
+        }
+
+        Bytecode { elements }
+    }
+
+    /// Replaces all calls of the bytecode function ``name`` with copies of its ``body``
+    ///
+    /// ``CallBytecodeFunc`` copies the arguments into the parameter registers and restores all
+    /// registers the function does not declare as modified when it returns. The inlined copy gets
+    /// the same semantics by renaming the registers the body writes to registers this bytecode and
+    /// the body do not reference. Each ``ReturnBytecodeFunc`` is replaced by a copy of the returned
+    /// value to the return register of the call and a jump past the inlined body.
+    ///
+    /// Only leaf functions with at most [INLINE_THRESHOLD](constant.INLINE_THRESHOLD.html) instructions
+    /// are inlined, otherwise the bytecode is returned unchanged. A function is a leaf if its body
+    /// contains no calls, callbacks, ``Try`` operations or rest arguments.
+    ///
+    /// The calls are found by their [function address](enum.Operand.html#Operand::FunctionAddr), while
+    /// the parameter registers are only known after the argument lists are patched to pairs of
+    /// parameter and argument register. Thus this must run in between and fails on argument placeholders.
+    /// Since new registers are picked from the unreferenced ones, ``self`` must be the complete bytecode.
+    pub fn inline_function(self, name: &str, body: &Bytecode) -> CompilerResult<Bytecode> {
+        if body.instruction_count() > INLINE_THRESHOLD || !is_leaf_function(body) {
+            return Ok(self);
+        }
+
+        let is_target = |cmd: &Operation| cmd.instruction == Instruction::CallBytecodeFunc && matches!(
+            cmd.operands.first(), Some(Operand::FunctionAddr(token)) if token.ident == name);
+
+        let calls = self.find_all_commands(is_target);
+        if calls.is_empty() {
+            return Ok(self);
+        }
+
+        let mut params: HashSet<Register> = HashSet::new();
+        for (_, call) in calls.iter() {
+            match call.operands.get(2) {
+                Some(Operand::RegistersArray(pairs)) => {
+                    params.extend(pairs.chunks(2).map(|pair| pair[0]));
+                },
+                _ => { return Err(CompilerError::Custom(format!(
+                    "The argument lists of calls to {} must be patched before inlining it", name))); }
+            }
+        }
+
+        let modified: HashSet<Register> = body.elements.iter().filter_map(|element| match element {
+            BytecodeElement::Operation(cmd) if cmd.instruction == Instruction::ReturnBytecodeFunc => cmd.operands.get(1),
+            _ => None
+        }).flat_map(operand_registers).collect();
+
+        let referenced: HashSet<Register> = self.elements.iter().chain(body.elements.iter())
+            .filter_map(|element| match element {
+                BytecodeElement::Operation(cmd) => Some(cmd),
+                BytecodeElement::Label(_) => None
+            })
+            .flat_map(|cmd| cmd.operands.iter().flat_map(operand_registers))
+            .collect();
+
+        let local: BTreeSet<Register> = body.elements.iter()
+            .filter_map(|element| match element {
+                BytecodeElement::Operation(cmd) => Some(defined_registers(cmd)),
+                BytecodeElement::Label(_) => None
+            })
+            .flatten()
+            .chain(params.iter().cloned())
+            .filter(|reg| !modified.contains(reg) && !ReservedeRegister::is_reserved(*reg))
+            .collect();
+
+        let mut free_regs = (0..=Register::MAX)
+            .filter(|reg| !referenced.contains(reg) && !ReservedeRegister::is_reserved(*reg));
+        let mut renaming: HashMap<Register, Register> = HashMap::new();
+        for &reg in local.iter() {
+            let new_reg = free_regs.next().ok_or_else(|| CompilerError::Custom(format!(
+                "Not enough free registers to inline {}", name)))?;
+            renaming.insert(reg, new_reg);
+        }
+
+        let mut next_label = self.elements.iter().chain(body.elements.iter())
+            .filter_map(|element| match element {
+                BytecodeElement::Label(label) => Some(*label),
+                BytecodeElement::Operation(cmd) => cmd.branch_target()
+            })
+            .max()
+            .map_or(0, |label| label + 1);
+
+        let body_label_count = body.elements.iter().filter_map(|element| match element {
+            BytecodeElement::Label(label) => Some(*label),
+            BytecodeElement::Operation(cmd) => cmd.branch_target()
+        }).collect::<HashSet<Label>>().len() as Label;
+
+        let mut elements = Vec::with_capacity(self.elements.len());
+        for element in self.elements.into_iter() {
+            let call = match element {
+                BytecodeElement::Operation(cmd) if is_target(&cmd) => cmd,
+                element => { elements.push(element); continue; }
+            };
+
+            let (ret_reg, pairs) = match call.operands.as_slice() {
+                [_, Operand::Reg(ret_reg), Operand::RegistersArray(pairs)] => (*ret_reg, pairs),
+                _ => { return Err(CompilerError::Custom(format!("Malformed call to {}", name))); }
+            };
+
+            for pair in pairs.chunks(2) {
+                if let (Some(param), Some(&arg)) = (renaming.get(&pair[0]), pair.get(1)) {
+                    elements.push(BytecodeElement::Operation(
+                        Operation::new(Instruction::Copy, vec![Operand::Reg(*param), Operand::Reg(arg)])));
+                }
+            }
+
+            let label_offset = next_label;
+            let end_label = label_offset + body_label_count;
+            let mut body_labels: HashMap<Label, Label> = HashMap::new();
+            let mut rename_label = |label: Label| {
+                let count = body_labels.len() as Label;
+                *body_labels.entry(label).or_insert(label_offset + count)
+            };
+
+            let mut body_elements = body.elements.iter().peekable();
+            while let Some(element) = body_elements.next() {
+                let mut cmd = match element {
+                    BytecodeElement::Label(label) => {
+                        elements.push(BytecodeElement::Label(rename_label(*label)));
+                        continue;
+                    },
+                    BytecodeElement::Operation(cmd) => cmd.clone()
+                };
+
+                for operand in cmd.operands.iter_mut() {
+                    rename_operand_registers(operand, &renaming);
+                    if let Operand::BranchAddr(token) = operand {
+                        token.label = rename_label(token.label);
+                    }
+                }
+
+                if cmd.instruction != Instruction::ReturnBytecodeFunc {
+                    elements.push(BytecodeElement::Operation(cmd));
+                    continue;
+                }
+
+                if let Some(value) = cmd.operands.first() {
+                    elements.push(BytecodeElement::Operation(
+                        Operation::new(Instruction::Copy, vec![Operand::Reg(ret_reg), value.clone()])));
+                }
+                if body_elements.peek().is_some() {
+                    elements.push(BytecodeElement::Operation(
+                        Operation::new(Instruction::Jump, vec![Operand::branch_addr(end_label)])));
+                }
+            }
+
+            elements.push(BytecodeElement::Label(end_label));
+            next_label = end_label + 1;
+        }
+
+        Ok(Bytecode { elements })
+    }
+}
+
+/// Returns whether a function body neither calls other code nor depends on being run by ``CallBytecodeFunc``
+fn is_leaf_function(body: &Bytecode) -> bool {
+    body.find_command(|cmd| cmd.is_call() || calls_bytecode_callback(cmd) || matches!(cmd.instruction,
+        Instruction::BytecodeFuncCallback |
+        Instruction::CollectRestArgs |
+        Instruction::Try)).is_none()
+}
+
+fn add_one_immediate(add: &Operation) -> Option<Operation> {
+    if add.instruction != Instruction::Add {
+        return None;
+    }
+
+    match add.operands.as_slice() {
+        [Operand::Reg(dst), Operand::Reg(src), Operand::Reg(rhs)] if *rhs == CommonLiteral::Num1.default_reg() => {
+            Some(Operation::new(Instruction::AddImmediate,
+                                vec![Operand::Reg(*dst), Operand::Reg(*src), Operand::ShortNum(1)]))
+        },
+        _ => None
+    }
+}
+
+/// Returns whether an operation only stores a value in a register
+fn is_store(op: &Operation) -> bool {
+    matches!(op.instruction,
+        Instruction::Copy |
+        Instruction::LoadString |
+        Instruction::LoadFloatNum |
+        Instruction::LoadLongNum |
+        Instruction::LoadNum |
+        Instruction::LoadArray)
+}
+
+fn fuse_add_immediate(load: &Operation, add: &Operation, reg_uses: &HashMap<Register, usize>) -> Option<Operation> {
+    if load.instruction != Instruction::LoadNum || add.instruction != Instruction::Add {
+        return None;
+    }
+
+    match (load.operands.as_slice(), add.operands.as_slice()) {
+        ([Operand::Reg(tmp), Operand::ShortNum(imm)], [Operand::Reg(dst), Operand::Reg(src), Operand::Reg(rhs)])
+            if tmp == rhs && tmp != src && reg_uses.get(tmp) == Some(&2) => {
+            Some(Operation::new(Instruction::AddImmediate,
+                                vec![Operand::Reg(*dst), Operand::Reg(*src), Operand::ShortNum(*imm)]))
+        },
+        _ => None
+    }
+}
+
+
+#[test]
+fn test_peephole_add_immediate() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(0), Operand::Reg(1)]));
+
+    assert_eq!(bytecode.peephole_add_immediate(), Bytecode::new()
+        .add(Operation::new(Instruction::AddImmediate, vec![Operand::Reg(0), Operand::Reg(0), Operand::ShortNum(5)])));
+
+    // The loaded register is used elsewhere
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(0), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(2), Operand::Reg(1)]));
+    assert_eq!(bytecode.clone().peephole_add_immediate(), bytecode);
+
+    // The immediate is the left-hand side, which is not equivalent for strings
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(1), Operand::Reg(0)]));
+    assert_eq!(bytecode.clone().peephole_add_immediate(), bytecode);
+
+    // A label between both operations might be a jump target
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(5)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(0), Operand::Reg(1)]));
+    assert_eq!(bytecode.clone().peephole_add_immediate(), bytecode);
+}
+
+#[test]
+fn test_peephole_add_immediate_compiled() {
+    use crate::compiler::BytecodeCompiler;
+    use crate::jshelper::JSSourceCode;
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var i = 0; i += 1; i += 5; i = 1 + i;")).unwrap();
+    assert_eq!(bytecode.peephole_add_immediate(), Bytecode::new()
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(0), Operand::Reg(255)]))
+        .add(Operation::new(Instruction::AddImmediate, vec![Operand::Reg(0), Operand::Reg(0), Operand::ShortNum(1)]))
+        .add(Operation::new(Instruction::AddImmediate, vec![Operand::Reg(0), Operand::Reg(0), Operand::ShortNum(5)]))
+        // The immediate is the left-hand side
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(254), Operand::Reg(0)])));
+}
+
+#[cfg(test)]
+fn regs(regs: &[Register]) -> HashSet<Register> {
+    regs.iter().cloned().collect()
+}
+
+#[test]
+fn test_liveness_straight_line() {
+    // r0 = 1; r1 = 2; r2 = r0 + r1; r0 = r2 - r2
+    let liveness = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(1)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(2), Operand::Reg(0), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::Minus, vec![Operand::Reg(0), Operand::Reg(2), Operand::Reg(2)]))
+        .compute_liveness();
+
+    assert_eq!(liveness.len(), 4);
+    assert_eq!(liveness[&0], regs(&[0]));
+    assert_eq!(liveness[&1], regs(&[0, 1]));
+    assert_eq!(liveness[&2], regs(&[2]));
+    assert_eq!(liveness[&3], regs(&[]));
+}
+
+#[test]
+fn test_liveness_loop() {
+    // r0 = 0; loop: r1 = r0 < r2; if !r1 goto end; r0 = r0 + r3; goto loop; end: r4 = r0
+    let liveness = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(0)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::CompLessThan, vec![Operand::Reg(1), Operand::Reg(0), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(1), Operand::branch_addr(1)]))
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(0), Operand::Reg(3)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(0)]))
+        .add_label(1)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(4), Operand::Reg(0)]))
+        .compute_liveness();
+
+    assert_eq!(liveness[&0], regs(&[0, 2, 3]));
+    assert_eq!(liveness[&1], regs(&[0, 1, 2, 3]));
+    assert_eq!(liveness[&2], regs(&[0, 2, 3]));
+    assert_eq!(liveness[&3], regs(&[0, 2, 3]));
+    assert_eq!(liveness[&4], regs(&[0, 2, 3]));
+    assert_eq!(liveness[&5], regs(&[]));
+}
+
+#[test]
+fn test_eliminate_dead_stores() {
+    // var a = 5; var b = 6; var c = b; obj[prop] = c;
+    let mut bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(6)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(2), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::PropertySet, vec![Operand::Reg(3), Operand::Reg(4), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::LoadLongNum, vec![Operand::Reg(200), Operand::BytecodeEnd]));
+
+    let liveness = bytecode.compute_liveness();
+    assert_eq!(bytecode.eliminate_dead_stores(&liveness), 1);
+    assert_eq!(bytecode, Bytecode::new()
+        .add(Operation::new(Instruction::Nop, vec![]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(6)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(2), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::PropertySet, vec![Operand::Reg(3), Operand::Reg(4), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::LoadLongNum, vec![Operand::Reg(200), Operand::BytecodeEnd])));
+
+    // Nothing is left to eliminate
+    let liveness = bytecode.compute_liveness();
+    assert_eq!(bytecode.eliminate_dead_stores(&liveness), 0);
+}
+
+#[test]
+fn test_eliminate_dead_stores_default_param() {
+    // function f(x = 5) { return x; } f(a);
+    let mut bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(3), Operand::Reg(0)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(4), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::DefaultParam, vec![Operand::Reg(3), Operand::Reg(4)]))
+        .add(Operation::new(Instruction::ReturnBytecodeFunc, vec![Operand::Reg(3), Operand::RegistersArray(vec![])]));
+
+    // The passed argument is only replaced if it is undefined
+    let liveness = bytecode.compute_liveness();
+    assert!(liveness[&1].contains(&3));
+    assert_eq!(bytecode.eliminate_dead_stores(&liveness), 0);
+}
+
+#[test]
+fn test_eliminate_dead_stores_array_set() {
+    // var a = [1]; a[0] = 2;
+    let mut bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadArray, vec![Operand::Reg(0), Operand::RegistersArray(vec![254])]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::ArraySet, vec![Operand::Reg(0), Operand::Reg(255), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::LoadLongNum, vec![Operand::Reg(200), Operand::BytecodeEnd]));
+
+    // The array is read by ArraySet, not written
+    let liveness = bytecode.compute_liveness();
+    assert!(liveness[&0].contains(&0));
+    assert_eq!(bytecode.eliminate_dead_stores(&liveness), 0);
+}
+
+#[test]
+fn test_eliminate_dead_stores_assert() {
+    // var c = true; console.assert(c, "m");
+    let mut bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(1)]))
+        .add(Operation::new(Instruction::Assert, vec![Operand::Reg(0), Operand::String("m".into())]))
+        .add(Operation::new(Instruction::LoadLongNum, vec![Operand::Reg(200), Operand::BytecodeEnd]));
+
+    let liveness = bytecode.compute_liveness();
+    assert_eq!(bytecode.eliminate_dead_stores(&liveness), 0);
+}
+
+#[test]
+fn test_eliminate_dead_stores_with_statement() {
+    // var o = [1]; with (o) { }
+    let mut bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadArray, vec![Operand::Reg(0), Operand::RegistersArray(vec![254])]))
+        .add(Operation::new(Instruction::WithStatement, vec![Operand::Reg(0)]))
+        .add(Operation::new(Instruction::LeaveScope, vec![]));
+
+    let liveness = bytecode.compute_liveness();
+    assert_eq!(bytecode.eliminate_dead_stores(&liveness), 0);
+}
+
+#[test]
+fn test_eliminate_dead_stores_callback() {
+    // var k = 2; var m = arr.map(function(x) { return x * k; });
+    let mut bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::ArrayMap, vec![Operand::Reg(2), Operand::Reg(0),
+                                                         Operand::function_addr("f".into()), Operand::RegistersArray(vec![3])]))
+        .add(Operation::new(Instruction::Exit, vec![]))
+        .add(Operation::new(Instruction::Mul, vec![Operand::Reg(4), Operand::Reg(3), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::ReturnBytecodeFunc, vec![Operand::Reg(4), Operand::RegistersArray(vec![])]));
+
+    // The callback might read any register
+    let liveness = bytecode.compute_liveness();
+    assert!(liveness[&0].contains(&1));
+    assert_eq!(bytecode.eliminate_dead_stores(&liveness), 0);
+}
+
+#[test]
+fn test_eliminate_dead_stores_console_log() {
+    // var x = 5; console.log(x);
+    let mut bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::ConsoleLog, vec![Operand::RegistersArray(vec![0])]));
+
+    // The only operand is read, not written
+    let liveness = bytecode.compute_liveness();
+    assert!(liveness[&0].contains(&0));
+    assert_eq!(bytecode.eliminate_dead_stores(&liveness), 0);
+}
+
+#[test]
+fn test_coalesce_registers() {
+    // obj[prop] = 1; obj[prop] = 2;
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(1)]))
+        .add(Operation::new(Instruction::PropertySet, vec![Operand::Reg(5), Operand::Reg(6), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(2), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::PropertySet, vec![Operand::Reg(5), Operand::Reg(6), Operand::Reg(2)]));
+
+    let liveness = bytecode.compute_liveness();
+    assert_eq!(bytecode.coalesce_registers(&liveness), Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(1)]))
+        .add(Operation::new(Instruction::PropertySet, vec![Operand::Reg(5), Operand::Reg(6), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::PropertySet, vec![Operand::Reg(5), Operand::Reg(6), Operand::Reg(1)])));
+
+    // Overlapping registers are kept apart
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(1)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(2), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::PropertySet, vec![Operand::Reg(5), Operand::Reg(1), Operand::Reg(2)]));
+
+    let liveness = bytecode.compute_liveness();
+    assert_eq!(bytecode.coalesce_registers(&liveness), bytecode);
+}
+
+#[test]
+fn test_inline_function() {
+    use crate::interpreter::BytecodeInterpreter;
+    use crate::value::JsValue;
+
+    // function double(x) { return x + x; } var a = 5; var b = double(a);
+    let body = Bytecode::new()
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(4), Operand::Reg(3), Operand::Reg(3)]))
+        .add(Operation::new(Instruction::ReturnBytecodeFunc, vec![Operand::Reg(4), Operand::RegistersArray(vec![])]));
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::CallBytecodeFunc, vec![Operand::function_addr("double".into()),
+                                                                 Operand::Reg(1), Operand::RegistersArray(vec![3, 0])]))
+        .add(Operation::new(Instruction::Exit, vec![]));
+    let program = bytecode.clone().add_bytecode(body.clone());
+
+    let inlined = program.inline_function("double", &body).unwrap();
+    assert_eq!(inlined, Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(2), Operand::Reg(0)]))
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(5), Operand::Reg(2), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(5)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::Exit, vec![]))
+        .add_bytecode(body.clone()));
+
+    let mut interpreter = BytecodeInterpreter::new();
+    interpreter.run(&inlined).unwrap();
+    assert_eq!(interpreter.register(1), &JsValue::Number(10.0));
+    assert_eq!(interpreter.register(3), &JsValue::Undefined);
+
+    // Other functions are not touched
+    assert_eq!(bytecode.clone().inline_function("other", &body).unwrap(), bytecode);
+
+    // The argument lists must be patched
+    let unpatched = Bytecode::new()
+        .add(Operation::new(Instruction::CallBytecodeFunc, vec![Operand::function_addr("double".into()),
+                                                                 Operand::Reg(1), Operand::bc_func_args(vec![0])]));
+    assert!(unpatched.inline_function("double", &body).is_err());
+}
+
+#[test]
+fn test_inline_function_early_return() {
+    // function abs(x) { if (x < 0) { return 0 - x; } return x; } var b = abs(a); ...
+    let body = Bytecode::new()
+        .add(Operation::new(Instruction::CompLessThan, vec![Operand::Reg(4), Operand::Reg(3), Operand::Reg(255)]))
+        .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(4), Operand::branch_addr(0)]))
+        .add(Operation::new(Instruction::Minus, vec![Operand::Reg(4), Operand::Reg(255), Operand::Reg(3)]))
+        .add(Operation::new(Instruction::ReturnBytecodeFunc, vec![Operand::Reg(4), Operand::RegistersArray(vec![])]))
+        .add_label(0)
+        .add(Operation::new(Instruction::ReturnBytecodeFunc, vec![Operand::Reg(3), Operand::RegistersArray(vec![])]));
+    let call = Operation::new(Instruction::CallBytecodeFunc, vec![Operand::function_addr("abs".into()),
+                                                                  Operand::Reg(1), Operand::RegistersArray(vec![3, 0])]);
+    let bytecode = Bytecode::new()
+        .add_label(0)
+        .add(call.clone())
+        .add(call)
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(0)]));
+
+    let inlined = bytecode.clone().inline_function("abs", &body).unwrap();
+    let inlined_abs = |label: Label| Bytecode::new()
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(2), Operand::Reg(0)]))
+        .add(Operation::new(Instruction::CompLessThan, vec![Operand::Reg(5), Operand::Reg(2), Operand::Reg(255)]))
+        .add(Operation::new(Instruction::JumpCondNeg, vec![Operand::Reg(5), Operand::branch_addr(label)]))
+        .add(Operation::new(Instruction::Minus, vec![Operand::Reg(5), Operand::Reg(255), Operand::Reg(2)]))
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(5)]))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(label + 1)]))
+        .add_label(label)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(1), Operand::Reg(2)]))
+        .add_label(label + 1);
+
+    assert_eq!(inlined, Bytecode::new()
+        .add_label(0)
+        .add_bytecode(inlined_abs(1))
+        .add_bytecode(inlined_abs(3))
+        .add(Operation::new(Instruction::Jump, vec![Operand::branch_addr(0)])));
+}
+
+#[test]
+fn test_inline_function_heuristic() {
+    let call = Bytecode::new()
+        .add(Operation::new(Instruction::CallBytecodeFunc, vec![Operand::function_addr("f".into()),
+                                                                 Operand::Reg(1), Operand::RegistersArray(vec![])]));
+    let ret = Operation::new(Instruction::ReturnBytecodeFunc, vec![Operand::Reg(253), Operand::RegistersArray(vec![])]);
+
+    let large = Bytecode::new()
+        .extend((0..INLINE_THRESHOLD).map(|_| Operation::new(Instruction::Nop, vec![])))
+        .add(ret.clone());
+    assert_eq!(large.instruction_count(), INLINE_THRESHOLD + 1);
+    assert_eq!(call.clone().inline_function("f", &large).unwrap(), call);
+
+    let non_leaf = call.clone().add(ret);
+    assert_eq!(call.clone().inline_function("f", &non_leaf).unwrap(), call);
+}
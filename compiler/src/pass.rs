@@ -0,0 +1,163 @@
+use crate::bytecode::{Bytecode, BytecodeElement, Instruction};
+#[cfg(test)]
+use crate::bytecode::{Operation, Operand, ToBytes};
+
+
+/// A transformation of bytecode, usually an optimization
+///
+/// Passes change the length of the bytecode, thus they must run before the label addresses are finalized.
+pub trait Pass {
+    /// Returns a short name of the pass, e.g. for debugging output
+    fn name(&self) -> &'static str;
+
+    /// Transforms the bytecode
+    fn run(&self, bc: Bytecode) -> Bytecode;
+}
+
+/// Replaces ``LoadNum`` + ``Add`` sequences, see [peephole_add_immediate](../bytecode/struct.Bytecode.html#method.peephole_add_immediate)
+pub struct PeepholeAddImmediate;
+
+impl Pass for PeepholeAddImmediate {
+    fn name(&self) -> &'static str {
+        "peephole-add-immediate"
+    }
+
+    fn run(&self, bc: Bytecode) -> Bytecode {
+        bc.peephole_add_immediate()
+    }
+}
+
+/// Replaces dead stores with ``Nop``, see [eliminate_dead_stores](../bytecode/struct.Bytecode.html#method.eliminate_dead_stores)
+pub struct DeadStoreElimination;
+
+impl Pass for DeadStoreElimination {
+    fn name(&self) -> &'static str {
+        "dead-store-elimination"
+    }
+
+    fn run(&self, mut bc: Bytecode) -> Bytecode {
+        let liveness = bc.compute_liveness();
+        bc.eliminate_dead_stores(&liveness);
+        bc
+    }
+}
+
+/// Removes all ``Nop`` operations
+pub struct RemoveNops;
+
+impl Pass for RemoveNops {
+    fn name(&self) -> &'static str {
+        "remove-nops"
+    }
+
+    fn run(&self, bc: Bytecode) -> Bytecode {
+        Bytecode {
+            elements: bc.elements.into_iter().filter(|element| match element {
+                BytecodeElement::Operation(op) => op.instruction != Instruction::Nop,
+                BytecodeElement::Label(_) => true
+            }).collect()
+        }
+    }
+}
+
+/// Lets registers share a register, see [coalesce_registers](../bytecode/struct.Bytecode.html#method.coalesce_registers)
+pub struct RegisterCoalescing;
+
+impl Pass for RegisterCoalescing {
+    fn name(&self) -> &'static str {
+        "register-coalescing"
+    }
+
+    fn run(&self, bc: Bytecode) -> Bytecode {
+        let liveness = bc.compute_liveness();
+        bc.coalesce_registers(&liveness)
+    }
+}
+
+/// Runs a sequence of [passes](trait.Pass.html)
+///
+/// ```
+/// use jsyc_compiler::{Bytecode, PassManager};
+/// use jsyc_compiler::pass::{PeepholeAddImmediate, RemoveNops};
+///
+/// let manager = PassManager::new().add_pass(PeepholeAddImmediate).add_pass(RemoveNops);
+/// assert_eq!(manager.run_all(Bytecode::new()), Bytecode::new());
+/// ```
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>
+}
+
+impl PassManager {
+    /// Creates a manager without any passes
+    pub fn new() -> Self {
+        PassManager {
+            passes: vec![]
+        }
+    }
+
+    /// Creates a manager running the standard optimizations: the peephole optimizations first,
+    /// then dead store elimination and register coalescing.
+    pub fn new_default_pipeline() -> Self {
+        PassManager::new()
+            .add_pass(PeepholeAddImmediate)
+            .add_pass(DeadStoreElimination)
+            .add_pass(RemoveNops)
+            .add_pass(RegisterCoalescing)
+    }
+
+    /// Appends a pass, it runs after all passes added so far
+    #[must_use = "returns a new PassManager with the pass added"]
+    pub fn add_pass(mut self, pass: impl Pass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Returns the names of all passes in the order they run
+    pub fn pass_names(&self) -> Vec<&'static str> {
+        self.passes.iter().map(|pass| pass.name()).collect()
+    }
+
+    /// Runs all passes in the order they were added
+    pub fn run_all(&self, bc: Bytecode) -> Bytecode {
+        self.passes.iter().fold(bc, |bc, pass| pass.run(bc))
+    }
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        PassManager::new()
+    }
+}
+
+
+#[test]
+fn test_remove_nops() {
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::Nop, vec![]))
+        .add_label(0)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(0), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::Nop, vec![]));
+
+    assert_eq!(RemoveNops.run(bytecode), Bytecode::new()
+        .add_label(0)
+        .add(Operation::new(Instruction::Copy, vec![Operand::Reg(0), Operand::Reg(1)])));
+}
+
+#[test]
+fn test_default_pipeline() {
+    // var b = 7; var a = x + 5; obj[prop] = a;
+    let bytecode = Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(3), Operand::ShortNum(7)]))
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(2), Operand::Reg(1)]))
+        .add(Operation::new(Instruction::PropertySet, vec![Operand::Reg(4), Operand::Reg(5), Operand::Reg(0)]));
+
+    let manager = PassManager::new_default_pipeline();
+    assert_eq!(manager.pass_names(), vec!["peephole-add-immediate", "dead-store-elimination", "remove-nops",
+                                          "register-coalescing"]);
+
+    let optimized = manager.run_all(bytecode.clone());
+    assert_eq!(PassManager::new().run_all(bytecode.clone()), bytecode);
+    assert!(optimized.length_in_bytes() < bytecode.length_in_bytes());
+    assert_eq!(optimized.elements.len(), 2);
+}
@@ -133,6 +133,77 @@ impl Scope {
 }
 
 
+/// Maps identifiers to their registers, with one table per scope
+///
+/// Lookups fall back to the parent table, i.e. the enclosing scope, if an identifier is not
+/// defined in the current scope. Unlike [Scope](struct.Scope.html), which manages the registers
+/// during compilation, the table only records the bindings, e.g. for debugging tools.
+///
+/// ```
+/// use jsyc_compiler::SymbolTable;
+///
+/// let mut table = SymbolTable::new();
+/// table.define("a", 0);
+/// table.push_scope();
+/// table.define("a", 1);
+/// assert_eq!(table.lookup("a"), Some(1));
+/// table.pop_scope();
+/// assert_eq!(table.lookup("a"), Some(0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SymbolTable
+{
+    bindings: HashMap<String, Register>,
+    parent: Option<Box<SymbolTable>>
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        SymbolTable {
+            bindings: HashMap::new(),
+            parent: None
+        }
+    }
+
+    /// Returns the register of the identifier in the innermost scope defining it
+    pub fn lookup(&self, name: &str) -> Option<Register> {
+        match self.bindings.get(name) {
+            Some(&reg) => Some(reg),
+            None => self.parent.as_ref().and_then(|parent| parent.lookup(name))
+        }
+    }
+
+    /// Binds the identifier to a register in the current scope, shadowing bindings of enclosing scopes
+    pub fn define(&mut self, name: &str, reg: Register) {
+        self.bindings.insert(name.to_string(), reg);
+    }
+
+    /// Enters a new scope, whose parent is the current scope
+    pub fn push_scope(&mut self) {
+        let parent = std::mem::take(self);
+        self.parent = Some(Box::new(parent));
+    }
+
+    /// Leaves the current scope and returns its bindings.
+    ///
+    /// Returns ``None`` and keeps the bindings if the current scope is the outermost scope.
+    pub fn pop_scope(&mut self) -> Option<HashMap<String, Register>> {
+        let parent = self.parent.take()?;
+        Some(std::mem::replace(self, *parent).bindings)
+    }
+
+    /// Returns the bindings of the current scope, without the ones of enclosing scopes
+    pub fn bindings(&self) -> &HashMap<String, Register> {
+        &self.bindings
+    }
+
+    /// Returns the table of the enclosing scope
+    pub fn parent(&self) -> Option<&SymbolTable> {
+        self.parent.as_ref().map(|parent| parent.as_ref())
+    }
+}
+
+
 #[derive(Debug, Clone)]
 pub struct Scopes
 {
@@ -235,6 +306,23 @@ impl Scopes
         Ok(scope)
     }
 
+    /// Returns the identifiers declared in the entered scopes, the innermost scope being the current
+    /// scope of the table
+    pub fn symbol_table(&self) -> SymbolTable {
+        let mut table = SymbolTable::new();
+        for (i, scope) in self.scopes.iter().enumerate() {
+            if i > 0 {
+                table.push_scope();
+            }
+            for name in scope.new_decls.iter() {
+                if let Some(decl) = scope.decls.get(name) {
+                    table.define(name, decl.register);
+                }
+            }
+        }
+        table
+    }
+
     pub fn leave_current_block_scope(&mut self) -> CompilerResult<Scope> {
         let scope = self.scopes.pop().ok_or(
             CompilerError::Custom("Cannot leave inexisting scope".into())
@@ -276,3 +364,32 @@ fn test_scopes() {
 
     assert!(scopes.current_scope().is_err());
 }
+
+#[test]
+fn test_symbol_table() {
+    let mut table = SymbolTable::new();
+    table.define("a", 0);
+    table.define("b", 1);
+
+    table.push_scope();
+        table.define("b", 2);
+        assert_eq!(table.lookup("a"), Some(0));
+        assert_eq!(table.lookup("b"), Some(2));
+        assert_eq!(table.parent().unwrap().lookup("b"), Some(1));
+    assert_eq!(table.pop_scope().unwrap().get("b"), Some(&2));
+
+    assert_eq!(table.lookup("b"), Some(1));
+    assert_eq!(table.lookup("c"), None);
+    assert_eq!(table.pop_scope(), None);
+    assert_eq!(table.bindings().len(), 2);
+
+    let mut scopes = Scopes::new();
+    let r0 = scopes.add_var_decl("globalVar".into()).unwrap();
+    scopes.enter_new_scope().unwrap();
+    let r1 = scopes.add_var_decl("testVar".into()).unwrap();
+
+    let table = scopes.symbol_table();
+    assert_eq!(table.bindings().len(), 1);
+    assert_eq!(table.lookup("testVar"), Some(r1));
+    assert_eq!(table.lookup("globalVar"), Some(r0));
+}
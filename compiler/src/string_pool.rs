@@ -0,0 +1,189 @@
+use crate::bytecode::{Bytecode, Operation, Instruction, Operand, ToBytes};
+use crate::error::{CompilerError, CompilerResult};
+use crate::instruction_set::ReservedeRegister;
+
+use std::collections::HashMap;
+
+
+/// Holds every distinct string of a bytecode exactly once
+///
+/// Strings are referenced by their index through [StringRef](../bytecode/enum.Operand.html#Operand::StringRef)
+/// operands. The pool is encoded as header in front of the bytecode: the number of strings as 2 byte
+/// big-endian number, followed by the strings encoded like [String](../bytecode/enum.Operand.html#Operand::String) operands.
+/// The pool holds at most 65535 strings, so that their number fits into the header.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StringPool {
+    strings: Vec<String>,
+    indices: HashMap<String, u16>
+}
+
+impl StringPool {
+    pub fn new() -> Self {
+        StringPool {
+            strings: vec![],
+            indices: HashMap::new()
+        }
+    }
+
+    /// Returns the index of the string, adding it to the pool if it is not part of it yet.
+    ///
+    /// Fails if the pool already holds 65535 strings.
+    pub fn intern(&mut self, string: &str) -> CompilerResult<u16> {
+        if let Some(&index) = self.indices.get(string) {
+            return Ok(index);
+        }
+
+        if self.strings.len() >= u16::MAX as usize {
+            return Err(CompilerError::TooManyStrings(self.strings.len() + 1));
+        }
+
+        let index = self.strings.len() as u16;
+        self.strings.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+        Ok(index)
+    }
+
+    /// Returns the string at the given index
+    pub fn get(&self, index: u16) -> Option<&str> {
+        self.strings.get(index as usize).map(|string| string.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+impl Default for StringPool {
+    fn default() -> Self {
+        StringPool::new()
+    }
+}
+
+impl ToBytes for StringPool {
+    fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        let mut bytes = Operand::u16(self.strings.len() as u16).to_bytes()?;
+        for string in self.strings.iter() {
+            bytes.append(&mut Operand::String(string.to_string()).to_bytes()?);
+        }
+        Ok(bytes)
+    }
+
+    fn length_in_bytes(&self) -> usize {
+        2 + self.strings.iter().fold(0, |acc, string| acc + 2 + string.len())
+    }
+}
+
+impl Bytecode {
+    /// Moves all [string operands](../bytecode/enum.Operand.html#Operand::String) into a
+    /// [string pool](struct.StringPool.html) and replaces them by
+    /// [references](../bytecode/enum.Operand.html#Operand::StringRef) into it.
+    ///
+    /// The references are shorter than most strings, thus the operations behind them move. Addresses
+    /// that were already resolved are moved accordingly.
+    pub fn intern_strings(self) -> CompilerResult<(StringPool, Bytecode)> {
+        let mut pool = StringPool::new();
+        let mut bytecode = self;
+
+        // The byte offset of each operation and the number of bytes saved in front of it
+        let mut shifts = vec![];
+        let (mut offset, mut saved) = (0, 0);
+
+        for cmd in bytecode.commands_iter_mut() {
+            shifts.push((offset, saved));
+            offset += cmd.length_in_bytes();
+
+            for operand in cmd.operands.iter_mut() {
+                if let Operand::String(string) = operand {
+                    let string_ref = Operand::StringRef(pool.intern(string)?);
+                    saved += operand.length_in_bytes() - string_ref.length_in_bytes();
+                    *operand = string_ref;
+                }
+            }
+        }
+
+        let move_addr = |addr: i32| {
+            let saved_before = shifts.iter().find(|(offset, _)| *offset >= addr as usize)
+                .map_or(saved, |&(_, saved)| saved);
+            addr - saved_before as i32
+        };
+
+        for cmd in bytecode.commands_iter_mut().filter(|cmd| has_address_operands(cmd)) {
+            for operand in cmd.operands.iter_mut() {
+                if let Operand::LongNum(addr) = operand {
+                    *addr = move_addr(*addr);
+                }
+            }
+        }
+
+        Ok((pool, bytecode))
+    }
+
+    /// Encodes the bytecode with its strings [interned](struct.Bytecode.html#method.intern_strings),
+    /// prefixed by the encoded string pool. Use [decode_with_string_pool](struct.Bytecode.html#method.decode_with_string_pool)
+    /// to decode it.
+    pub fn encode_with_string_pool(self) -> CompilerResult<Vec<u8>> {
+        self.assert_no_unresolved_tokens()?;
+        let (pool, bytecode) = self.intern_strings()?;
+        let mut encoded = pool.to_bytes()?;
+        encoded.append(&mut bytecode.to_bytes()?);
+        Ok(encoded)
+    }
+}
+
+/// Returns whether the [LongNum](../bytecode/enum.Operand.html#Operand::LongNum) operands of an
+/// operation are addresses
+///
+/// ``LoadLongNum`` only loads an address if it writes the bytecode pointer, i.e. it jumps.
+fn has_address_operands(cmd: &Operation) -> bool {
+    match (&cmd.instruction, cmd.operands.first()) {
+        (Instruction::LoadLongNum, Some(Operand::Reg(reg))) => *reg == ReservedeRegister::BytecodePointer.default_reg(),
+        _ => true
+    }
+}
+
+
+#[test]
+fn test_string_pool() {
+    let mut pool = StringPool::new();
+    assert!(pool.is_empty());
+    assert_eq!(pool.intern("a").unwrap(), 0);
+    assert_eq!(pool.intern("b").unwrap(), 1);
+    assert_eq!(pool.intern("a").unwrap(), 0);
+    assert_eq!(pool.len(), 2);
+    assert_eq!(pool.get(1), Some("b"));
+    assert_eq!(pool.get(2), None);
+
+    assert_eq!(pool.to_bytes().unwrap(), vec![0, 2, 0, 1, b'a', 0, 1, b'b']);
+    assert_eq!(pool.length_in_bytes(), 8);
+
+    // The number of strings must fit into the header
+    let mut pool = StringPool::new();
+    for i in 0..u16::MAX {
+        pool.intern(&i.to_string()).unwrap();
+    }
+    assert!(matches!(pool.intern("full"), Err(CompilerError::TooManyStrings(65536))));
+    assert_eq!(pool.to_bytes().unwrap()[..2], [0xff, 0xff]);
+}
+
+#[test]
+fn test_intern_strings() {
+    let load_hello = |reg| Operation::new(Instruction::LoadString, vec![Operand::Reg(reg), Operand::String("hello".into())]);
+    let bytecode = Bytecode::new()
+        .extend((0..10).map(load_hello))
+        .add(Operation::new(Instruction::Assert, vec![Operand::Reg(0), Operand::String("world".into())]));
+
+    let (pool, interned) = bytecode.clone().intern_strings().unwrap();
+    assert_eq!(pool.len(), 2);
+    assert_eq!(interned.elements.len(), bytecode.elements.len());
+    assert_eq!(interned.collect_string_constants(), Vec::<String>::new());
+    assert_eq!(interned.find_command(|cmd| cmd.instruction == Instruction::Assert).unwrap().1.operands[1],
+               Operand::StringRef(1));
+
+    let encoded = bytecode.encode_with_string_pool().unwrap();
+    assert_eq!(encoded.windows(5).filter(|window| window == b"hello").count(), 1);
+    assert_eq!(encoded.len(), pool.length_in_bytes() + interned.length_in_bytes());
+}
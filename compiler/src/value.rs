@@ -0,0 +1,365 @@
+use crate::bytecode::Operand;
+use crate::error::CompilerError;
+use crate::instruction_set::{CommonLiteral, InstructionSet};
+use crate::scope::Scope;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ops::{Add, Sub, Mul, Div};
+
+
+/// Represents a JavaScript value at runtime, as used by the [interpreter](../interpreter/struct.BytecodeInterpreter.html)
+///
+/// Objects and arrays are compared structurally, since the interpreter has no notion of references.
+/// The ``Display`` implementation converts the value to a string like JavaScript's ``String(value)``.
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    Object(HashMap<String, JsValue>),
+    Array(Vec<JsValue>)
+}
+
+impl JsValue {
+    /// Converts the value to a number like JavaScript's ``Number(value)``
+    pub fn to_number(&self) -> f64 {
+        match self {
+            JsValue::Undefined => f64::NAN,
+            JsValue::Null => 0.0,
+            JsValue::Bool(bool) => *bool as u8 as f64,
+            JsValue::Number(num) => *num,
+            JsValue::Str(string) => string_to_number(string),
+            JsValue::Object(_) |
+            JsValue::Array(_) => self.to_primitive().to_number()
+        }
+    }
+
+    /// Converts the value to a boolean like JavaScript's ``Boolean(value)``
+    pub fn to_bool(&self) -> bool {
+        match self {
+            JsValue::Undefined |
+            JsValue::Null => false,
+            JsValue::Bool(bool) => *bool,
+            JsValue::Number(num) => *num != 0.0 && !num.is_nan(),
+            JsValue::Str(string) => !string.is_empty(),
+            JsValue::Object(_) |
+            JsValue::Array(_) => true
+        }
+    }
+
+    /// Converts objects and arrays to their string representation. Primitives are returned as they are.
+    pub fn to_primitive(&self) -> JsValue {
+        match self {
+            JsValue::Object(_) |
+            JsValue::Array(_) => JsValue::Str(self.to_string()),
+            _ => self.clone()
+        }
+    }
+
+    fn is_nullish(&self) -> bool {
+        matches!(self, JsValue::Undefined | JsValue::Null)
+    }
+
+    fn is_object(&self) -> bool {
+        matches!(self, JsValue::Object(_) | JsValue::Array(_))
+    }
+
+    /// Compares two values like JavaScript's ``===``
+    pub fn strict_equals(&self, other: &JsValue) -> bool {
+        self == other
+    }
+
+    /// Compares two values like JavaScript's ``==``
+    pub fn loose_equals(&self, other: &JsValue) -> bool {
+        match (self, other) {
+            (left, right) if left.is_nullish() || right.is_nullish() => left.is_nullish() && right.is_nullish(),
+            (JsValue::Str(left), JsValue::Str(right)) => left == right,
+            (left, right) if left.is_object() && right.is_object() => left == right,
+            (left, right) if left.is_object() || right.is_object() => left.to_primitive().loose_equals(&right.to_primitive()),
+            _ => self.to_number() == other.to_number()
+        }
+    }
+
+    /// Compares two values like JavaScript's ``<``. Strings are compared lexicographically,
+    /// everything else numerically. Comparisons with ``NaN`` are always false.
+    pub fn less_than(&self, other: &JsValue) -> bool {
+        match (self.to_primitive(), other.to_primitive()) {
+            (JsValue::Str(left), JsValue::Str(right)) => left < right,
+            (left, right) => left.to_number() < right.to_number()
+        }
+    }
+}
+
+/// Parses a string like JavaScript's ``StringToNumber``. Besides decimal literals this accepts
+/// ``Infinity`` with an optional sign and unsigned ``0x``, ``0o`` and ``0b`` integer literals.
+fn string_to_number(string: &str) -> f64 {
+    let trimmed = string.trim();
+    let radix_literal = |prefixes: [&str; 2], radix: u32| {
+        prefixes.iter().find_map(|prefix| trimmed.strip_prefix(prefix)).map(|digits| {
+            if digits.is_empty() {
+                return f64::NAN;
+            }
+            digits.chars().try_fold(0.0, |num, digit| digit.to_digit(radix).map(|digit| num * f64::from(radix) + f64::from(digit)))
+                .unwrap_or(f64::NAN)
+        })
+    };
+
+    match trimmed {
+        "" => 0.0,
+        "Infinity" | "+Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        _ => radix_literal(["0x", "0X"], 16)
+            .or_else(|| radix_literal(["0o", "0O"], 8))
+            .or_else(|| radix_literal(["0b", "0B"], 2))
+            .unwrap_or_else(|| {
+                // Rust also parses "inf" and "NaN", which are not numeric literals in JavaScript
+                if trimmed.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')) {
+                    trimmed.parse().unwrap_or(f64::NAN)
+                } else {
+                    f64::NAN
+                }
+            })
+    }
+}
+
+/// Formats a number like JavaScript's ``Number::toString``. Numbers below ``1e-6`` or from ``1e21`` on
+/// use the exponential notation.
+fn number_to_string(num: f64) -> String {
+    if num.is_nan() {
+        return "NaN".into();
+    }
+    if num == 0.0 {
+        return "0".into();
+    }
+    if num < 0.0 {
+        return format!("-{}", number_to_string(-num));
+    }
+    if num.is_infinite() {
+        return "Infinity".into();
+    }
+
+    // The exponential formatting yields the shortest digits that round-trip
+    let formatted = format!("{:e}", num);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let sign = if n - 1 < 0 { "-" } else { "+" };
+        match k {
+            1 => format!("{}e{}{}", digits, sign, (n - 1).abs()),
+            _ => format!("{}.{}e{}{}", &digits[..1], &digits[1..], sign, (n - 1).abs())
+        }
+    }
+}
+
+impl std::fmt::Display for JsValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            JsValue::Undefined => write!(f, "undefined"),
+            JsValue::Null => write!(f, "null"),
+            JsValue::Bool(bool) => write!(f, "{}", bool),
+            JsValue::Number(num) => write!(f, "{}", number_to_string(*num)),
+            JsValue::Str(string) => write!(f, "{}", string),
+            JsValue::Object(_) => write!(f, "[object Object]"),
+            JsValue::Array(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    if !element.is_nullish() {
+                        write!(f, "{}", element)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Add for &JsValue {
+    type Output = JsValue;
+
+    /// Concatenates the values if one of them is a string (after converting objects), otherwise adds them
+    fn add(self, other: &JsValue) -> JsValue {
+        match (self.to_primitive(), other.to_primitive()) {
+            (left @ JsValue::Str(_), right) |
+            (left, right @ JsValue::Str(_)) => JsValue::Str(format!("{}{}", left, right)),
+            (left, right) => JsValue::Number(left.to_number() + right.to_number())
+        }
+    }
+}
+
+impl Sub for &JsValue {
+    type Output = JsValue;
+
+    fn sub(self, other: &JsValue) -> JsValue {
+        JsValue::Number(self.to_number() - other.to_number())
+    }
+}
+
+impl Mul for &JsValue {
+    type Output = JsValue;
+
+    fn mul(self, other: &JsValue) -> JsValue {
+        JsValue::Number(self.to_number() * other.to_number())
+    }
+}
+
+impl Div for &JsValue {
+    type Output = JsValue;
+
+    fn div(self, other: &JsValue) -> JsValue {
+        JsValue::Number(self.to_number() / other.to_number())
+    }
+}
+
+impl TryFrom<JsValue> for Operand {
+    type Error = CompilerError;
+
+    /// Converts the value to the smallest operand representing it
+    ///
+    /// Booleans become ``ShortNum`` operands like in [from_literal](../bytecode/enum.Operand.html#method.from_literal)
+    /// and ``undefined`` becomes the register of the common literal ``void 0``. Fails for ``null``, objects
+    /// and arrays, which have no literal operands.
+    fn try_from(value: JsValue) -> Result<Operand, CompilerError> {
+        match value {
+            JsValue::Undefined => {
+                Ok(Operand::Reg(InstructionSet::default(&mut Scope::new()).common_literal_reg(&CommonLiteral::Void0)))
+            },
+            JsValue::Bool(bool) => Ok(Operand::ShortNum(bool as u8)),
+            JsValue::Number(num) => Ok(Operand::from_number(num)),
+            JsValue::Str(string) => Ok(Operand::String(string)),
+            JsValue::Null |
+            JsValue::Object(_) |
+            JsValue::Array(_) => Err(CompilerError::Custom(format!("The value {} has no literal operand", value)))
+        }
+    }
+}
+
+impl TryFrom<Operand> for JsValue {
+    type Error = CompilerError;
+
+    /// Converts a literal operand to its value. All numeric operands become numbers.
+    /// Fails for registers, addresses and all other operands without a literal value.
+    fn try_from(operand: Operand) -> Result<JsValue, CompilerError> {
+        match operand {
+            Operand::String(string) => Ok(JsValue::Str(string)),
+            Operand::FloatNum(num) => Ok(JsValue::Number(num)),
+            Operand::LongNum(num) => Ok(JsValue::Number(f64::from(num))),
+            Operand::ShortNum(num) => Ok(JsValue::Number(f64::from(num))),
+            Operand::ShortNum16(num) => Ok(JsValue::Number(f64::from(num))),
+            _ => Err(CompilerError::Custom(format!("The operand {} has no literal value", operand)))
+        }
+    }
+}
+
+
+#[test]
+fn test_to_number() {
+    assert!(JsValue::Undefined.to_number().is_nan());
+    assert_eq!(JsValue::Null.to_number(), 0.0);
+    assert_eq!(JsValue::Bool(true).to_number(), 1.0);
+    assert_eq!(JsValue::Str(" 12.5 ".into()).to_number(), 12.5);
+    assert_eq!(JsValue::Str("".into()).to_number(), 0.0);
+    assert!(JsValue::Str("abc".into()).to_number().is_nan());
+    assert_eq!(JsValue::Array(vec![]).to_number(), 0.0);
+    assert_eq!(JsValue::Array(vec![JsValue::Str("7".into())]).to_number(), 7.0);
+    assert!(JsValue::Object(HashMap::new()).to_number().is_nan());
+    assert_eq!(JsValue::Str("-Infinity".into()).to_number(), f64::NEG_INFINITY);
+    assert!(JsValue::Str("inf".into()).to_number().is_nan());
+    assert!(JsValue::Str("NaN".into()).to_number().is_nan());
+    assert_eq!(JsValue::Str("0x10".into()).to_number(), 16.0);
+    assert_eq!(JsValue::Str("0o17".into()).to_number(), 15.0);
+    assert_eq!(JsValue::Str("0b101".into()).to_number(), 5.0);
+    assert!(JsValue::Str("0x".into()).to_number().is_nan());
+    assert!(JsValue::Str("-0x10".into()).to_number().is_nan());
+    assert_eq!(JsValue::Str("1e3".into()).to_number(), 1000.0);
+}
+
+#[test]
+fn test_to_bool() {
+    assert!(!JsValue::Number(f64::NAN).to_bool());
+    assert!(!JsValue::Str("".into()).to_bool());
+    assert!(JsValue::Str("0".into()).to_bool());
+    assert!(JsValue::Array(vec![]).to_bool());
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(JsValue::Number(3.0).to_string(), "3");
+    assert_eq!(JsValue::Number(-0.5).to_string(), "-0.5");
+    assert_eq!(JsValue::Number(f64::NAN).to_string(), "NaN");
+    assert_eq!(JsValue::Number(f64::NEG_INFINITY).to_string(), "-Infinity");
+    assert_eq!(JsValue::Number(-0.0).to_string(), "0");
+    assert_eq!(JsValue::Number(123.456).to_string(), "123.456");
+    assert_eq!(JsValue::Number(0.000001).to_string(), "0.000001");
+    assert_eq!(JsValue::Number(1e-7).to_string(), "1e-7");
+    assert_eq!(JsValue::Number(1.5e-10).to_string(), "1.5e-10");
+    assert_eq!(JsValue::Number(1e20).to_string(), "100000000000000000000");
+    assert_eq!(JsValue::Number(1e21).to_string(), "1e+21");
+    assert_eq!(JsValue::Number(-1.25e22).to_string(), "-1.25e+22");
+    assert_eq!(JsValue::Undefined.to_string(), "undefined");
+    assert_eq!(JsValue::Object(HashMap::new()).to_string(), "[object Object]");
+    assert_eq!(JsValue::Array(vec![JsValue::Number(1.0), JsValue::Null, JsValue::Array(vec![JsValue::Bool(true),
+               JsValue::Str("a".into())])]).to_string(), "1,,true,a");
+}
+
+#[test]
+fn test_arithmetic() {
+    let one = JsValue::Number(1.0);
+    let two = JsValue::Str("2".into());
+
+    assert_eq!(&one + &one, JsValue::Number(2.0));
+    assert_eq!(&one + &two, JsValue::Str("12".into()));
+    assert_eq!(&JsValue::Array(vec![one.clone()]) + &one, JsValue::Str("11".into()));
+    assert_eq!(&JsValue::Bool(true) + &JsValue::Null, JsValue::Number(1.0));
+    assert_eq!(&one - &two, JsValue::Number(-1.0));
+    assert_eq!(&two * &two, JsValue::Number(4.0));
+    assert_eq!(&one / &JsValue::Number(0.0), JsValue::Number(f64::INFINITY));
+}
+
+#[test]
+fn test_equality() {
+    assert!(JsValue::Null.loose_equals(&JsValue::Undefined));
+    assert!(!JsValue::Null.strict_equals(&JsValue::Undefined));
+    assert!(JsValue::Str("1".into()).loose_equals(&JsValue::Number(1.0)));
+    assert!(!JsValue::Str("1".into()).strict_equals(&JsValue::Number(1.0)));
+    assert!(!JsValue::Null.loose_equals(&JsValue::Number(0.0)));
+    assert!(!JsValue::Number(f64::NAN).loose_equals(&JsValue::Number(f64::NAN)));
+    assert!(JsValue::Array(vec![JsValue::Number(1.0)]).loose_equals(&JsValue::Str("1".into())));
+    assert!(JsValue::Str("a".into()).less_than(&JsValue::Str("b".into())));
+    assert!(!JsValue::Number(f64::NAN).less_than(&JsValue::Number(1.0)));
+}
+
+#[test]
+fn test_operand_conversions() {
+    assert_eq!(Operand::try_from(JsValue::Number(1.0)).unwrap(), Operand::ShortNum(1));
+    assert_eq!(Operand::try_from(JsValue::Number(1000.0)).unwrap(), Operand::ShortNum16(1000));
+    assert_eq!(Operand::try_from(JsValue::Number(-1.0)).unwrap(), Operand::LongNum(-1));
+    assert_eq!(Operand::try_from(JsValue::Number(0.5)).unwrap(), Operand::FloatNum(0.5));
+    assert_eq!(Operand::try_from(JsValue::Bool(true)).unwrap(), Operand::ShortNum(1));
+    assert_eq!(Operand::try_from(JsValue::Str("a".into())).unwrap(), Operand::String("a".into()));
+    assert_eq!(Operand::try_from(JsValue::Undefined).unwrap(), Operand::Reg(253));
+    assert!(Operand::try_from(JsValue::Null).is_err());
+    assert!(Operand::try_from(JsValue::Object(HashMap::new())).is_err());
+    assert!(Operand::try_from(JsValue::Array(vec![JsValue::Number(1.0), JsValue::Number(2.0)])).is_err());
+
+    for value in [JsValue::Number(7.0), JsValue::Number(300.0), JsValue::Number(-2.5), JsValue::Str("b".into())] {
+        assert_eq!(JsValue::try_from(Operand::try_from(value.clone()).unwrap()).unwrap(), value);
+    }
+
+    assert!(JsValue::try_from(Operand::Reg(0)).is_err());
+    assert!(JsValue::try_from(Operand::function_addr("f".into())).is_err());
+    assert!(JsValue::try_from(Operand::branch_addr(0)).is_err());
+}
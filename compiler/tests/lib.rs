@@ -74,7 +74,7 @@ fn test_compiler_api() {
     let js_code = JSSourceCode::from_str("var a = 10");
 
     let bytecode = compiler.compile(&js_code).unwrap();
-    assert_eq!(bytecode.encode_base64(), "AgAK");
+    assert_eq!(bytecode.encode_base64().unwrap(), "AgAK");
 }
 
 #[test]
@@ -92,6 +92,15 @@ fn test_compile_js_decls() {
         .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(1), Operand::ShortNum(6)]))
     );
 
+    run_test("var a = 1000;", BytecodeCompiler::new(), Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum16, vec![Operand::Reg(0), Operand::ShortNum16(1000)]))
+    );
+
+    run_test("var a = 2.0, b = 1e10;", BytecodeCompiler::new(), Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![Operand::Reg(0), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::LoadFloatNum, vec![Operand::Reg(1), Operand::FloatNum(1e10)]))
+    );
+
     run_test("var s = \"Hello World\";", BytecodeCompiler::new(), Bytecode::new()
         .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(0), Operand::String("Hello World".into())]))
     );
@@ -198,6 +207,113 @@ fn test_bytecode_func_calls() {
     );
 }
 
+#[test]
+fn test_default_params() {
+    run_test("function f(a, b = 5, c = a) { return b; } f(1);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(CallBytecodeFunc, addr!(10), reg!(202), reg_arr![0, 254, 1, 253, 2, 253]))
+        .add(op!(Exit, ))
+        .add(op!(LoadNum, reg!(3), short_num!(5)))
+        .add(op!(DefaultParam, reg!(1), reg!(3)))
+        .add(op!(DefaultParam, reg!(2), reg!(0)))
+        .add(op!(ReturnBytecodeFunc, reg!(1), reg_arr![]))
+    );
+
+    // Default values with possible side effects are only evaluated if needed
+    run_test("function g() {} function f(a = g()) {} f();", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(CallBytecodeFunc, addr!(11), reg!(202), reg_arr![0, 253]))
+        .add(op!(Exit, ))
+        .add(op!(ReturnBytecodeFunc, reg!(253), reg_arr![]))
+        .add(op!(CompStrictEqual, reg!(1), reg!(0), reg!(253)))
+        .add(op!(JumpCondNeg, reg!(1), addr!(28)))
+        .add(op!(CallBytecodeFunc, addr!(8), reg!(0), reg_arr![]))
+        .add_label(0)
+        .add(op!(ReturnBytecodeFunc, reg!(253), reg_arr![]))
+    );
+}
+
+#[test]
+fn test_rest_params() {
+    run_test("function f(a, ...r) { return r; } f(1); f(1,2); f(1,2,3,4);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(CallBytecodeFunc, addr!(48), reg!(202), reg_arr![0, 254]))
+        .add(op!(LoadNum, reg!(0), short_num!(2)))
+        .add(op!(CallBytecodeFunc, addr!(48), reg!(202), reg_arr![0, 254, 202, 0]))
+        .add(op!(LoadNum, reg!(1), short_num!(2)))
+        .add(op!(LoadNum, reg!(2), short_num!(3)))
+        .add(op!(LoadNum, reg!(3), short_num!(4)))
+        .add(op!(CallBytecodeFunc, addr!(48), reg!(202), reg_arr![0, 254, 202, 1, 202, 2, 202, 3]))
+        .add(op!(Exit, ))
+        .add(op!(CollectRestArgs, reg!(1), short_num!(1)))
+        .add(op!(ReturnBytecodeFunc, reg!(1), reg_arr![]))
+    );
+}
+
+#[test]
+fn test_strict_arity() {
+    let config = CompilerConfig { strict_arity: true, ..CompilerConfig::default() };
+
+    run_test("function testy(a, b) {} testy(10);", BytecodeCompiler::with_config(config), Bytecode::new()
+        .add(op!(LoadNum, reg!(0), short_num!(10)))
+        .add(op!(LoadNum, reg!(1), short_num!(1)))
+        .add(op!(CheckArity, short_num!(2), reg!(1)))
+        .add(op!(CallBytecodeFunc, addr!(19), reg!(202), reg_arr![0, 0, 1, 253]))
+        .add(op!(Exit, ))
+        .add(op!(ReturnBytecodeFunc, reg!(253), reg_arr![]))
+    );
+}
+
+#[test]
+fn test_console_assert() {
+    run_test("var a = 1; console.assert(a, \"a is set\"); console.assert(a);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(Copy, reg!(0), reg!(254)))
+        .add(op!(Assert, reg!(0), string!("a is set")))
+        .add(op!(Assert, reg!(0), string!("Assertion failed")))
+    );
+
+    let config = CompilerConfig { strip_asserts: true, ..CompilerConfig::default() };
+    run_test("var a = 1; console.assert(a, \"a is set\"); var b = console.assert(a);", BytecodeCompiler::with_config(config), Bytecode::new()
+        .add(op!(Copy, reg!(0), reg!(254)))
+        .add(op!(Copy, reg!(1), reg!(253)))
+    );
+
+    // A declared 'console' is no builtin
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var console; console.assert(false);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::Assert).is_none());
+}
+
+#[test]
+fn test_console_log() {
+    let js_code = "var a = 1; console.log('a', a); var b = console.log();";
+
+    let config = CompilerConfig { debug_output: true, ..CompilerConfig::default() };
+    run_test(js_code, BytecodeCompiler::with_config(config), Bytecode::new()
+        .add(op!(Copy, reg!(0), reg!(254)))
+        .add(op!(LoadString, reg!(1), string!("a")))
+        .add(op!(ConsoleLog, reg_arr!(1, 0)))
+        .add(op!(ConsoleLog, reg_arr!()))
+        .add(op!(Copy, reg!(2), reg!(253)))
+    );
+
+    // The optimizations keep the logged values
+    let config = CompilerConfig { debug_output: true, ..CompilerConfig::default() };
+    let bytecode = BytecodeCompiler::with_config(config).compile(&JSSourceCode::from_str("var x = 5; console.log(x);")).unwrap();
+    assert_eq!(PassManager::new_default_pipeline().run_all(bytecode.clone()), bytecode);
+
+    // Without debug output, the calls stay regular calls and no ConsoleLog is encoded
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).unwrap();
+    let decoded = Bytecode::decode(&bytecode.to_bytes().unwrap()).unwrap();
+    assert!(decoded.find_command(|cmd| cmd.instruction == Instruction::ConsoleLog).is_none());
+    assert_eq!(decoded.find_all_commands(|cmd| cmd.instruction == Instruction::CallFunc).len(), 2);
+
+    // A declared 'console' is no builtin
+    let config = CompilerConfig { debug_output: true, ..CompilerConfig::default() };
+    let bytecode = BytecodeCompiler::with_config(config).compile(&JSSourceCode::from_str("
+        var console = 1; console.log(1);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ConsoleLog).is_none());
+}
+
 #[test]
 fn test_jump_stmts() {
     run_test("var a = false; if(a){a+=a;}", BytecodeCompiler::new(), Bytecode::new()
@@ -388,6 +504,11 @@ fn test_member_expr() {
                 .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(4), Operand::String("test".into())]))
                 .add(Operation::new(Instruction::PropAccess, vec![Operand::Reg(3), Operand::Reg(0), Operand::Reg(4)])));
 
+    run_test("var l = document.length; var c = document['length']", compiler.clone(), Bytecode::new()
+                .add(Operation::new(Instruction::GetArrayLength, vec![Operand::Reg(1), Operand::Reg(0)]))
+                .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(3), Operand::String("length".into())]))
+                .add(Operation::new(Instruction::PropAccess, vec![Operand::Reg(2), Operand::Reg(0), Operand::Reg(3)])));
+
     // Assignment expression 'equal'
     let mut assignments_compiler = BytecodeCompiler::new();
     assert!(assignments_compiler.add_var_decl("test".into()).is_ok());
@@ -428,6 +549,11 @@ fn test_unary_expr() {
         .add(Operation::new(Instruction::Add, vec![Operand::Reg(0), Operand::Reg(0), Operand::Reg(254)]))
     );
 
+    run_test("var a = \"5\"; var b = +a;", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(0), string!("5")))
+        .add(op!(ToNumber, reg!(1), reg!(0)))
+    );
+
     // Suffix update expressions
     check_is_unsupported_error("a++;", BytecodeCompiler::new());
 }
@@ -439,6 +565,248 @@ fn test_array_expr() {
         .add(Operation::new(Instruction::LoadString, vec![Operand::Reg(2), Operand::String("String".into())]))
         .add(Operation::new(Instruction::LoadArray, vec![Operand::Reg(0), Operand::RegistersArray(vec![255, 1, 2])]))
     );
+
+    // Numeric indices use the specialized array instructions
+    run_test("var a = [1, 2]; var b = a[1]; a[2] = b; var c = a[b];", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadNum, reg!(1), short_num!(2)))
+        .add(op!(LoadArray, reg!(0), reg_arr![254, 1]))
+        .add(op!(ArrayGet, reg!(2), reg!(0), reg!(254)))
+        .add(op!(LoadNum, reg!(3), short_num!(2)))
+        .add(op!(ArraySet, reg!(0), reg!(3), reg!(2)))
+        .add(op!(PropAccess, reg!(4), reg!(0), reg!(2)))
+    );
+}
+
+#[test]
+fn test_template_literal() {
+    run_test("var a = `plain`;", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(0), string!("plain")))
+    );
+
+    run_test("var b = 1; var a = `x${b}y`;", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(Copy, reg!(0), reg!(254)))
+        .add(op!(LoadString, reg!(2), string!("x")))
+        .add(op!(LoadString, reg!(3), string!("y")))
+        .add(op!(Concat, reg!(1), reg!(2), reg!(0)))
+        .add(op!(Concat, reg!(1), reg!(1), reg!(3)))
+    );
+
+    // The target register is also an interpolated part
+    run_test("var a = 1; a = `${a}`;", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(Copy, reg!(0), reg!(254)))
+        .add(op!(LoadString, reg!(1), string!("")))
+        .add(op!(Concat, reg!(2), reg!(1), reg!(0)))
+        .add(op!(Copy, reg!(0), reg!(2)))
+    );
+
+    run_test("var a = \"a\" + \"b\";", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(1), string!("a")))
+        .add(op!(LoadString, reg!(2), string!("b")))
+        .add(op!(Concat, reg!(0), reg!(1), reg!(2)))
+    );
+}
+
+#[test]
+fn test_regex_literal() {
+    run_test("var r = /\\d+/g; var s = /a|b/;", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(CreateRegExp, reg!(0), Operand::RegEx("\\d+".into(), 0b1)))
+        .add(op!(CreateRegExp, reg!(1), Operand::RegEx("a|b".into(), 0b0)))
+    );
+
+    run_test("const r = /a/; var t = r.test(\"abc\"); var m = r.exec(\"abc\");", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(CreateRegExp, reg!(0), Operand::RegEx("a".into(), 0b0)))
+        .add(op!(LoadString, reg!(2), string!("abc")))
+        .add(op!(RegExpTest, reg!(1), reg!(0), reg!(2)))
+        .add(op!(LoadString, reg!(4), string!("abc")))
+        .add(op!(RegExpExec, reg!(3), reg!(0), reg!(4)))
+    );
+
+    // Only calls with exactly one argument are specialized
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("const r = /a/; r.test();")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::RegExpTest).is_none());
+
+    // The receiver is not known to be a regular expression
+    let mut compiler = BytecodeCompiler::new();
+    assert!(compiler.add_var_decl("r".into()).is_ok());
+    run_test("r.test(r);", compiler, Bytecode::new()
+        .add(op!(LoadString, reg!(2), string!("test")))
+        .add(op!(PropAccess, reg!(1), reg!(0), reg!(2)))
+        .add(op!(CallFunc, reg!(202), reg!(1), reg!(0), reg_arr![0]))
+    );
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var r = /a/; var t = r.test('a');")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::RegExpTest).is_none());
+}
+
+#[test]
+fn test_type_coercion_calls() {
+    run_test("var a = String(5); var b = Number(\"1\");", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadNum, reg!(1), Operand::ShortNum(5)))
+        .add(op!(TypeCoerceToString, reg!(0), reg!(1)))
+        .add(op!(LoadString, reg!(3), string!("1")))
+        .add(op!(TypeCoerceToNumber, reg!(2), reg!(3)))
+    );
+
+    run_test("var a = parseInt(\"ff\", 16); var b = parseInt(a); var c = parseFloat(\"1.5\");", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(1), string!("ff")))
+        .add(op!(ParseInt, reg!(0), reg!(1), Operand::ShortNum(16)))
+        .add(op!(ParseInt, reg!(2), reg!(0), Operand::ShortNum(0)))
+        .add(op!(LoadString, reg!(4), string!("1.5")))
+        .add(op!(ParseFloat, reg!(3), reg!(4)))
+    );
+
+    // Calls with other argument counts and of declared functions are regular calls
+    for js_code in &["var a = String();", "var a = parseInt('1', 37);", "var r = 8; var a = parseInt('1', r);",
+                     "var a = parseFloat('1', 2);", "var a = Number(1, 2);", "var String = f; var a = String(1);"] {
+        let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).unwrap();
+        assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::CallFunc).is_some(), "{}", js_code);
+    }
+}
+
+#[test]
+fn test_atomics_memory_barriers() {
+    let barrier = |ordering| Operation::new(Instruction::MemoryBarrier, vec![Operand::from(ordering)]);
+    let compile = |js_code| BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).unwrap();
+
+    let bytecode = compile("var a = Atomics.load(buffer, 0);");
+    assert_eq!(bytecode.elements.last(), Some(&BytecodeElement::Operation(barrier(MemoryOrdering::Acquire))));
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::MemoryBarrier).len(), 1);
+
+    let bytecode = compile("Atomics.store(buffer, 0, 1);");
+    let (barrier_index, _) = bytecode.find_command(|cmd| *cmd == barrier(MemoryOrdering::Release)).unwrap();
+    let (call_index, _) = bytecode.find_command(|cmd| cmd.instruction == Instruction::CallFunc).unwrap();
+    assert!(barrier_index < call_index);
+
+    let bytecode = compile("Atomics.wait(buffer, 0, 0);");
+    assert_eq!(bytecode.find_all_commands(|cmd| *cmd == barrier(MemoryOrdering::SequentiallyConsistent)).len(), 2);
+
+    // Other methods and a declared 'Atomics' need no barriers
+    for js_code in &["Atomics.add(buffer, 0, 1);", "var Atomics = other; Atomics.load(buffer, 0);"] {
+        assert!(compile(js_code).find_command(|cmd| cmd.instruction == Instruction::MemoryBarrier).is_none(), "{}", js_code);
+    }
+}
+
+#[test]
+fn test_array_sort() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function cmp(a, b) { return a - b; }
+        var arr = [3, 1, 2];
+        arr.sort(cmp);
+    ")).unwrap();
+    let (_, sort) = bytecode.find_command(|cmd| cmd.instruction == Instruction::ArraySort).unwrap();
+    assert_eq!(sort.operands[1], reg!(0));
+    assert!(matches!(sort.operands[2], Operand::LongNum(_)));
+    assert_eq!(sort.operands[3], reg_arr!(0, 1));
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::CallFunc).is_none());
+
+    // Arrays which are not declared, default comparators and other comparators are regular calls
+    for js_code in &["function cmp(a, b) { return a - b; } arr.sort(cmp);",
+                     "var arr = [3, 1, 2]; arr.sort();",
+                     "var arr = [3, 1, 2]; arr.sort(cmp);"] {
+        let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).unwrap();
+        assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ArraySort).is_none(), "{}", js_code);
+    }
+}
+
+#[test]
+fn test_array_map() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function f(x, i) { return x + i; }
+        var arr = [1, 2];
+        var mapped = arr.map(f);
+    ")).unwrap();
+    let (_, map) = bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayMap).unwrap();
+    assert_eq!(map.operands[0], reg!(2));
+    assert_eq!(map.operands[1], reg!(0));
+    assert!(matches!(map.operands[2], Operand::LongNum(_)));
+    assert_eq!(map.operands[3], reg_arr!(0, 1));
+
+    // Callbacks which are not bytecode functions and further arguments are regular calls
+    for js_code in &["var arr = [1, 2]; var mapped = arr.map(f);",
+                     "function f(x) { return x; } var arr = [1, 2]; var mapped = arr.map(f, arr);"] {
+        let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).unwrap();
+        assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayMap).is_none(), "{}", js_code);
+    }
+}
+
+#[test]
+fn test_array_filter_reduce() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function double(x) { return x * 2; }
+        function isBig(x) { return x > 2; }
+        function add(a, b) { return a + b; }
+        var arr = [1, 2, 3];
+        var doubled = arr.map(double);
+        var big = doubled.filter(isBig);
+        var sum = big.reduce(add, 0);
+        var sumWithoutInitial = big.reduce(add);
+    ")).unwrap();
+    let (_, filter) = bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayFilter).unwrap();
+    assert_eq!(filter.operands[1], reg!(3));
+    assert_eq!(filter.operands[3], reg_arr!(0));
+
+    let reduces = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::ArrayReduce);
+    assert_eq!(reduces.len(), 2);
+    assert_eq!(reduces[0].1.operands[3], reg_arr!(0, 1));
+    // An initial value of 0 is copied as the register holding 0 stands for a missing initial value
+    assert_ne!(reduces[0].1.operands[4], reg!(255));
+    assert_eq!(reduces[1].1.operands[4], reg!(255));
+
+    // Further arguments are regular calls
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(
+        "function add(a, b) { return a + b; } var arr = [1]; var sum = arr.reduce(add, 0, arr);"
+    )).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayReduce).is_none());
+}
+
+#[test]
+fn test_array_find() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function isBig(x) { return x > 2; }
+        var arr = [1, 2, 3];
+        var big = arr.find(isBig);
+        var bigIndex = arr.findIndex(isBig);
+    ")).unwrap();
+    let (_, find) = bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayFind).unwrap();
+    let (_, find_index) = bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayFindIndex).unwrap();
+    assert_eq!(find.operands[1], reg!(0));
+    assert_eq!(find_index.operands[1], reg!(0));
+    assert_eq!(find.operands[2], find_index.operands[2]);
+    assert_eq!(find_index.operands[3], reg_arr!(0));
+}
+
+#[test]
+fn test_array_every_some() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function isBig(x) { return x > 2; }
+        var arr = [1, 2, 3];
+        var allBig = arr.every(isBig);
+        var anyBig = arr.some(isBig);
+    ")).unwrap();
+    let (_, every) = bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayEvery).unwrap();
+    let (_, some) = bytecode.find_command(|cmd| cmd.instruction == Instruction::ArraySome).unwrap();
+    assert_eq!(every.operands[1], reg!(0));
+    assert_eq!(some.operands[1], reg!(0));
+    assert_eq!(every.operands[2], some.operands[2]);
+    assert_eq!(some.operands[3], reg_arr!(0));
+}
+
+#[test]
+fn test_array_for_each() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function visit(x) { }
+        var arr = [1, 2];
+        arr.forEach(visit);
+        var result = arr.forEach(visit);
+    ")).unwrap();
+    let for_eachs = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::ArrayForEach);
+    assert_eq!(for_eachs.len(), 2);
+    assert_eq!(for_eachs[0].1.operands[0], reg!(0));
+    assert_eq!(for_eachs[0].1.operands[2], reg_arr!(0));
+
+    // The result is only set if it is used
+    let copies = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::Copy);
+    assert_eq!(copies.len(), 1);
+    assert_eq!(copies[0].1.operands, [reg!(2), reg!(253)]);
 }
 
 #[test]
@@ -529,20 +897,20 @@ fn test_try_throw() {
              finally{ console.log(x); }",
              compiler_with_json.clone(),
              Bytecode::new()
-                .add(op!(Try, reg!(7), long_num!(49), long_num!(58)))
+                .add(op!(Try, reg!(7), long_num!(50), long_num!(59)))
                 .add(op!(LoadString, reg!(4), string!("{\"x\": 100}")))
                 .add(op!(LoadString, reg!(6), string!("parse")))
                 .add(op!(PropAccess, reg!(5), reg!(1), reg!(6)))
                 .add(op!(CallFunc, reg!(3), reg!(5), reg!(1), reg_arr!(4)))
-                .add(op!(LoadLongNum, reg!(200), long_num!(81)))
+                .add(op!(LoadLongNum, reg!(200), long_num!(83)))
                 .add_label(0)
                 .add(op!(Copy, reg!(3), reg!(2)))
-                .add(op!(LoadLongNum, reg!(200), long_num!(81)))
+                .add(op!(LoadLongNum, reg!(200), long_num!(83)))
                 .add_label(1)
                 .add(op!(LoadString, reg!(8), string!("log")))
                 .add(op!(PropAccess, reg!(7), reg!(0), reg!(8)))
                 .add(op!(CallFunc, reg!(202), reg!(7), reg!(0), reg_arr!(3)))
-                .add(op!(LoadLongNum, reg!(200), long_num!(81)))
+                .add(op!(LoadLongNum, reg!(200), long_num!(83)))
     );
 
     run_test("var x = 10; try { throw x*2; }", BytecodeCompiler::new(), Bytecode::new()
@@ -576,7 +944,7 @@ fn test_unsupported_exprs() {
 
     // Object related stuff
     check_is_unsupported_error("var x = new X();", BytecodeCompiler::new());
-    check_is_unsupported_error("var x = {};", BytecodeCompiler::new());
+    check_is_unsupported_error("var x = { get a() { return 1; } };", BytecodeCompiler::new());
     check_is_unsupported_error("var x = this;", BytecodeCompiler::new());
 
     // yield, FIXME
@@ -598,6 +966,730 @@ fn test_unsupported_stmts() {
     check_is_unsupported_error("for (x in X) {}", BytecodeCompiler::new());
     check_is_unsupported_error("for (x of X) {}", BytecodeCompiler::new());
 
-    check_is_unsupported_error("with(x) {}", BytecodeCompiler::new());
+    let config = CompilerConfig { disallow_with: true, ..CompilerConfig::default() };
+    check_is_unsupported_error("with(x) {}", BytecodeCompiler::with_config(config));
     check_is_unsupported_error("debugger;", BytecodeCompiler::new());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_compile_with_stmt() {
+    let mut compiler = BytecodeCompiler::new();
+    let js_code = JSSourceCode::from_str("var o = 5; with(o) { var a = 2; }");
+
+    // The initializer of a 'var' assigns the property of the object if it has one
+    assert_eq!(compiler.compile(&js_code).unwrap(), Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![reg!(0), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::WithStatement, vec![reg!(0)]))
+        .add(Operation::new(Instruction::LoadNum, vec![reg!(2), Operand::ShortNum(2)]))
+        .add(Operation::new(Instruction::LoadString, vec![reg!(3), string!("a")]))
+        .add(Operation::new(Instruction::HasBinding, vec![reg!(4), reg!(0), reg!(3)]))
+        .add(Operation::new(Instruction::JumpCondNeg, vec![reg!(4), addr!(32)]))
+        .add(Operation::new(Instruction::PropertySet, vec![reg!(0), reg!(3), reg!(2)]))
+        .add(Operation::new(Instruction::Jump, vec![addr!(35)]))
+        .add_label(1)
+        .add(Operation::new(Instruction::Copy, vec![reg!(1), reg!(2)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::LeaveScope, vec![]))
+    );
+    assert_eq!(compiler.warnings(), &[CompilerWarning::DeprecatedSyntax("'with' statements".into())]);
+
+    // A free name is read from the object or else from the register of the declaration dependency
+    run_test_deps("var o = 5; with(o) { b; }", &["b"], Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![reg!(0), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::WithStatement, vec![reg!(0)]))
+        .add(Operation::new(Instruction::LoadString, vec![reg!(2), string!("b")]))
+        .add(Operation::new(Instruction::HasBinding, vec![reg!(3), reg!(0), reg!(2)]))
+        .add(Operation::new(Instruction::JumpCondNeg, vec![reg!(3), addr!(30)]))
+        .add(Operation::new(Instruction::PropAccess, vec![reg!(202), reg!(0), reg!(2)]))
+        .add(Operation::new(Instruction::Jump, vec![addr!(33)]))
+        .add_label(1)
+        .add(Operation::new(Instruction::Copy, vec![reg!(202), reg!(1)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::LeaveScope, vec![]))
+    );
+
+    // A property of the object shadows a declared variable
+    run_test("var o = 5; var x = 1; with(o) { x; }", BytecodeCompiler::new(), Bytecode::new()
+        .add(Operation::new(Instruction::LoadNum, vec![reg!(0), Operand::ShortNum(5)]))
+        .add(Operation::new(Instruction::Copy, vec![reg!(1), reg!(254)]))
+        .add(Operation::new(Instruction::WithStatement, vec![reg!(0)]))
+        .add(Operation::new(Instruction::LoadString, vec![reg!(2), string!("x")]))
+        .add(Operation::new(Instruction::HasBinding, vec![reg!(3), reg!(0), reg!(2)]))
+        .add(Operation::new(Instruction::JumpCondNeg, vec![reg!(3), addr!(33)]))
+        .add(Operation::new(Instruction::PropAccess, vec![reg!(202), reg!(0), reg!(2)]))
+        .add(Operation::new(Instruction::Jump, vec![addr!(36)]))
+        .add_label(1)
+        .add(Operation::new(Instruction::Copy, vec![reg!(202), reg!(1)]))
+        .add_label(0)
+        .add(Operation::new(Instruction::LeaveScope, vec![]))
+    );
+
+    // The inner object is read through the outer one, then nested objects are checked innermost first
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var o = 5; var p = 6; var x; with(o) { with(p) { x = 1; } }
+    ")).unwrap();
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::HasBinding).iter()
+        .map(|(_, cmd)| cmd.operands[1].clone()).collect::<Vec<_>>(), vec![reg!(0), reg!(3), reg!(0)]);
+
+    // Leaving the body by break also leaves the scope of the object
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var o = 1; while(o) { with(o) { break; } }
+    ")).unwrap();
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::LeaveScope).len(), 2);
+}
+
+#[test]
+fn test_compile_symbols() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var s = Symbol('desc'); var i = Symbol.iterator; var t = Symbol.toStringTag;
+    ")).unwrap();
+    assert_eq!(bytecode, Bytecode::new()
+        .add(Operation::new(Instruction::LoadSymbol, vec![reg!(0), Operand::String("desc".into())]))
+        .add(Operation::new(Instruction::LoadWellKnownSymbol, vec![reg!(1), WellKnownSymbol::Iterator.into()]))
+        .add(Operation::new(Instruction::LoadWellKnownSymbol, vec![reg!(2), Operand::ShortNum(11)])));
+
+    // Symbols without a literal description, unknown properties and a shadowed Symbol are compiled as usual
+    for js_code in &["var s = Symbol();", "var d = 'desc'; var s = Symbol(d);", "var s = Symbol.for;",
+                     "var Symbol = 1; var s = Symbol('desc'); var i = Symbol.iterator;"] {
+        let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).unwrap();
+        assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::LoadSymbol ||
+                                            cmd.instruction == Instruction::LoadWellKnownSymbol).is_none(), "{}", js_code);
+    }
+}
+
+#[test]
+fn test_symbol_prop_access() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var arr = [1, 2]; var it = arr[Symbol.iterator]; var s = arr[Symbol('s')]; var a = arr['a'];
+    ")).unwrap();
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::PropAccess).len(), 3);
+
+    // Well-known symbols are keys of PropAccess, without loading them into a register
+    let (_, prop_access) = bytecode.find_command(|cmd| cmd.instruction == Instruction::PropAccess).unwrap();
+    assert_eq!(prop_access.operands[2], Operand::symbol_key(WellKnownSymbol::Iterator));
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::LoadWellKnownSymbol).is_none());
+    assert_eq!(Bytecode::decode(&bytecode.to_bytes().unwrap()).unwrap(), bytecode);
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var Symbol = 1; var arr = [1, 2]; var it = arr[Symbol.iterator];
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.operands.contains(&Operand::symbol_key(WellKnownSymbol::Iterator))).is_none());
+}
+
+#[test]
+fn test_string_length() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var a = 'abc'; var b = ('x' + a).length; var c = a.length; var d = `t`.length;
+    ")).unwrap();
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::GetLength).len(), 2);
+    // The type of variables is unknown
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::GetArrayLength).len(), 1);
+}
+
+#[test]
+fn test_string_slice() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const s = 'abc'; var a = s.slice(1); var b = s.substring(0, 2);
+    ")).unwrap();
+    let slices = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::StringSlice);
+    assert_eq!(slices.len(), 2);
+    assert_eq!(slices[0].1.operands[3], reg!(253));
+    assert_eq!(slices[0].1.operands[4], StringSliceKind::Slice.into());
+    assert_eq!(slices[1].1.operands[4], StringSliceKind::Substring.into());
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const s = 'abc'; var a = s.slice(); var b = s.substring(0, 1, 2); var c = s.substr(1);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringSlice).is_none());
+
+    // The receiver is not known to be a string, e.g. it might be an array
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var s = 'abc'; var a = s.slice(1); var b = s.substring(0, 2);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringSlice).is_none());
+}
+
+#[test]
+fn test_string_index_of() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const s = 'abc'; var a = s.indexOf('b'); var b = s.indexOf('c', 1); var c = s.indexOf();
+    ")).unwrap();
+    let index_ofs = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::StringIndexOf);
+    assert_eq!(index_ofs.len(), 2);
+    // Without start position, the search starts at the register holding 0
+    assert_eq!(index_ofs[0].1.operands[3], reg!(255));
+    assert_ne!(index_ofs[1].1.operands[3], reg!(255));
+
+    // Arrays have an indexOf method too
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var a = [1, 2]; var i = a.indexOf(2); var s = 'abc'; var j = s.indexOf('b');
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringIndexOf).is_none());
+}
+
+#[test]
+fn test_string_char_at() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const s = 'abc'; var i = 1; var a = s.charAt(i); var b = s.charCodeAt(i);
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringCharAt).unwrap().1.operands,
+               vec![reg!(2), reg!(0), reg!(1)]);
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringCharCodeAt).unwrap().1.operands,
+               vec![reg!(3), reg!(0), reg!(1)]);
+
+    // The receiver is not known to be a string
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var s = 'abc'; var a = s.charAt(1); var b = [s].charAt(0);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringCharAt).is_none());
+}
+
+#[test]
+fn test_string_trim() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const s = ' a '; var a = s.trim(); var b = s.trimStart(); var c = s.trimEnd();
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringTrim).unwrap().1.operands,
+               vec![reg!(1), reg!(0)]);
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringTrimStart).unwrap().1.operands,
+               vec![reg!(2), reg!(0)]);
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringTrimEnd).unwrap().1.operands,
+               vec![reg!(3), reg!(0)]);
+}
+
+#[test]
+fn test_string_match() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const s = 'a1b2'; var m = s.match(/\\d/); var all = s.matchAll(/(\\d)/g);
+    ")).unwrap();
+    let (_, matched) = bytecode.find_command(|cmd| cmd.instruction == Instruction::StringMatch).unwrap();
+    let (_, matched_all) = bytecode.find_command(|cmd| cmd.instruction == Instruction::StringMatchAll).unwrap();
+    assert_eq!(matched.operands[..2], [reg!(1), reg!(0)]);
+    assert_eq!(matched_all.operands[..2], [reg!(3), reg!(0)]);
+}
+
+#[test]
+fn test_string_replace() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function twice(match, c) { return c + c; }
+        const s = 'a1b2'; var r = s.replace(/([a-z])/g, twice); var t = s.replaceAll('1', '$&!');
+    ")).unwrap();
+    let (_, callback) = bytecode.find_command(|cmd| cmd.instruction == Instruction::BytecodeFuncCallback).unwrap();
+    let (_, replace) = bytecode.find_command(|cmd| cmd.instruction == Instruction::StringReplace).unwrap();
+    let (_, replace_all) = bytecode.find_command(|cmd| cmd.instruction == Instruction::StringReplaceAll).unwrap();
+    assert_eq!(replace.operands[..2], [reg!(1), reg!(0)]);
+    assert_eq!(replace.operands[3], callback.operands[0]);
+    assert_eq!(replace_all.operands[..2], [reg!(4), reg!(0)]);
+}
+
+#[test]
+fn test_function_apply() {
+    let mut compiler = BytecodeCompiler::new();
+    assert!(compiler.add_var_decl("f".into()).is_ok());
+    run_test("var args = [1]; var r = f.apply(args, args);", compiler, Bytecode::new()
+        .add(op!(LoadArray, reg!(1), reg_arr![254]))
+        .add(op!(CallApply, reg!(2), reg!(0), reg!(1), reg!(1)))
+    );
+}
+
+#[test]
+fn test_string_repeat() {
+    run_test("const s = 'ab'; var r = s.repeat(3);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(0), string!("ab")))
+        .add(op!(LoadNum, reg!(2), short_num!(3)))
+        .add(op!(StringRepeat, reg!(1), reg!(0), reg!(2)))
+    );
+}
+
+#[test]
+fn test_math_calls() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var x = 1.5; var a = Math.floor(x); var b = Math.abs(x); var c = Math.floor(x, 2);
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MathFloor).unwrap().1.operands,
+               vec![reg!(1), reg!(0)]);
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MathAbs).is_some());
+    // Calls with other argument counts are regular calls
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::CallFunc).len(), 1);
+
+    // A declared 'Math' is no builtin
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var Math = 1; var a = Math.floor(2);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MathFloor).is_none());
+}
+
+#[test]
+fn test_math_min_max() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var x = 1; var y = 2; var a = Math.min(x, y, 3); var b = Math.max(); var c = Math.pow(x, y);
+    ")).unwrap();
+    let min = bytecode.find_command(|cmd| cmd.instruction == Instruction::MathMin).unwrap().1;
+    assert!(matches!(&min.operands[1], Operand::RegistersArray(regs) if regs.len() == 3 && regs[..2] == [0, 1]));
+    // Math.max() is -Infinity, the VM takes care of that
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MathMax).unwrap().1.operands[1], reg_arr!());
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MathPow).unwrap().1.operands[1..],
+               [reg!(0), reg!(1)]);
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::CallFunc).is_none());
+}
+
+#[test]
+fn test_math_random() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var a = Math.random(); var b = Math.random(a);
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MathRandom).unwrap().1.operands,
+               vec![reg!(0)]);
+    // The arguments may have side effects, so the call is kept
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::CallFunc).is_some());
+}
+
+#[test]
+fn test_date_now() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var a = Date.now();
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::DateNow).unwrap().1.operands,
+               vec![reg!(0)]);
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var Date = 1; var a = Date.now();
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::DateNow).is_none());
+}
+
+#[test]
+fn test_global_this() {
+    run_test("var a = globalThis.Array;", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadGlobalThis, reg!(1)))
+        .add(op!(LoadString, reg!(2), string!("Array")))
+        .add(op!(PropAccess, reg!(0), reg!(1), reg!(2)))
+    );
+
+    // A declared variable shadows the global object
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var globalThis = 1; var a = globalThis;
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::LoadGlobalThis).is_none());
+}
+
+#[test]
+fn test_object_spread() {
+    run_test("var a = {x: 1}; var b = {...a, y: a};", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(1), string!("x")))
+        .add(op!(ObjectSpread, reg!(0), reg!(255), reg!(253)))
+        .add(op!(PropertySet, reg!(0), reg!(1), reg!(254)))
+        .add(op!(LoadString, reg!(3), string!("y")))
+        .add(op!(ObjectSpread, reg!(2), reg!(255), reg!(0)))
+        .add(op!(PropertySet, reg!(2), reg!(3), reg!(0)))
+    );
+
+    // The object is built in another register if the properties read the target
+    run_test("var a = {}; a = {x: 1, ...a};", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(ObjectSpread, reg!(0), reg!(255), reg!(253)))
+        .add(op!(LoadString, reg!(1), string!("x")))
+        .add(op!(ObjectSpread, reg!(2), reg!(255), reg!(253)))
+        .add(op!(PropertySet, reg!(2), reg!(1), reg!(254)))
+        .add(op!(ObjectSpread, reg!(2), reg!(2), reg!(0)))
+        .add(op!(Copy, reg!(0), reg!(2)))
+    );
+}
+
+#[test]
+fn test_array_slice() {
+    run_test("const a = [1]; var b = a.slice(1); var c = a.slice(); var d = a.slice(1, 0);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadArray, reg!(0), reg_arr![254]))
+        .add(op!(ArraySlice, reg!(1), reg!(0), reg!(254), reg!(255)))
+        .add(op!(ArraySlice, reg!(2), reg!(0), reg!(255), reg!(255)))
+        // An end of 0 is copied as the register holding 0 stands for the end of the array
+        .add(op!(Copy, reg!(4), reg!(255)))
+        .add(op!(ArraySlice, reg!(3), reg!(0), reg!(254), reg!(4)))
+    );
+
+    // Receivers not known to be arrays keep using string slices
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var a = [1]; var b = a.slice(1);")).unwrap();
+    assert!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::ArraySlice).is_empty());
+}
+
+#[test]
+fn test_array_concat() {
+    run_test("const a = [1]; var b = a.concat(); var c = a.concat(a, 1);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadArray, reg!(0), reg_arr![254]))
+        .add(op!(ArrayConcat, reg!(1), reg!(0), reg_arr![]))
+        .add(op!(ArrayConcat, reg!(2), reg!(0), reg_arr![0, 254]))
+    );
+}
+
+#[test]
+fn test_array_flat() {
+    run_test("const a = [1]; var b = a.flat(); var c = a.flat(0); var d = a.flat(Infinity);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadArray, reg!(0), reg_arr![254]))
+        .add(op!(ArrayFlat, reg!(1), reg!(0), reg!(254)))
+        // A depth of 0 is copied as the register holding 0 stands for an infinite depth
+        .add(op!(Copy, reg!(3), reg!(255)))
+        .add(op!(ArrayFlat, reg!(2), reg!(0), reg!(3)))
+        .add(op!(ArrayFlat, reg!(4), reg!(0), reg!(255)))
+    );
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(
+        "function f(x) { return x; } var a = [1]; var b = a.flatMap(f);"
+    )).unwrap();
+    let (_, flat_map) = bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayFlatMap).unwrap();
+    assert_eq!(flat_map.operands[1], reg!(0));
+    assert_eq!(flat_map.operands[3], reg_arr!(0));
+}
+
+#[test]
+fn test_string_split() {
+    run_test("const s = 'a,b'; var p = s.split(','); var q = s.split(',', 0);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(0), string!("a,b")))
+        .add(op!(LoadString, reg!(2), string!(",")))
+        .add(op!(StringSplit, reg!(1), reg!(0), reg!(2), reg!(255)))
+        .add(op!(LoadString, reg!(4), string!(",")))
+        // A limit of 0 is copied as the register holding 0 stands for no limit
+        .add(op!(Copy, reg!(5), reg!(255)))
+        .add(op!(StringSplit, reg!(3), reg!(0), reg!(4), reg!(5)))
+    );
+
+    // The receiver is not known to be a string
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var s = 'a,b'; var p = s.split(',');")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringSplit).is_none());
+}
+
+#[test]
+fn test_string_pad() {
+    run_test("const s = 'a'; var p = s.padStart(2); var q = s.padEnd(2, 0);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(0), string!("a")))
+        .add(op!(LoadNum, reg!(2), short_num!(2)))
+        .add(op!(StringPadStart, reg!(1), reg!(0), reg!(2), reg!(255)))
+        .add(op!(LoadNum, reg!(4), short_num!(2)))
+        // A fill of 0 is copied as the register holding 0 stands for a space as fill
+        .add(op!(Copy, reg!(5), reg!(255)))
+        .add(op!(StringPadEnd, reg!(3), reg!(0), reg!(4), reg!(5)))
+    );
+
+    // The receiver is not known to be a string
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var s = 'a'; var p = s.padStart(2);")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringPadStart).is_none());
+}
+
+#[test]
+fn test_string_search() {
+    run_test("const s = 'ab'; var a = s.includes('a'); var b = s.endsWith('a', 0);", BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadString, reg!(0), string!("ab")))
+        .add(op!(LoadString, reg!(2), string!("a")))
+        .add(op!(StringIncludes, reg!(1), reg!(0), reg!(2), reg!(255)))
+        .add(op!(LoadString, reg!(4), string!("a")))
+        // A position of 0 is copied as the register holding 0 stands for the default position
+        .add(op!(Copy, reg!(5), reg!(255)))
+        .add(op!(StringEndsWith, reg!(3), reg!(0), reg!(4), reg!(5)))
+    );
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("const s = 'ab'; var a = s.startsWith('a', 1);")).unwrap();
+    assert_ne!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringStartsWith).unwrap().1.operands[3], reg!(255));
+
+    // Arrays have an includes method too
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var a = [1, 2]; var b = a.includes(1);")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::StringIncludes).is_none());
+}
+
+#[test]
+fn test_json_calls() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var a = [1, [2, [3]]]; var s = JSON.stringify(a); var t = JSON.stringify(a, null, 2);
+        var b = JSON.parse(s); var c = JSON.parse(s, f);
+    ")).unwrap();
+    let stringifies = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::JSONStringify);
+    assert_eq!(stringifies.len(), 2);
+    // Missing arguments are passed as the register holding 0
+    assert_eq!(stringifies[0].1.operands[1..], [reg!(0), reg!(255), reg!(255)]);
+    assert_ne!(stringifies[1].1.operands[2], reg!(255));
+    assert_ne!(stringifies[1].1.operands[3], reg!(255));
+
+    let parses = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::JSONParse);
+    assert_eq!(parses.len(), 2);
+    assert_eq!(parses[0].1.operands[2], reg!(255));
+    assert_ne!(parses[1].1.operands[2], reg!(255));
+
+    // Calls with unexpected argument counts are regular calls
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var s = JSON.stringify();")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::JSONStringify).is_none());
+}
+
+#[test]
+fn test_object_assign() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var a = x; var b = y; var c = Object.assign(a, b, z); var d = Object.assign(a); Object.assign();
+    ")).unwrap();
+    let assigns = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::ObjectAssign);
+    assert_eq!(assigns.len(), 2);
+    assert_eq!(assigns[0].1.operands[1], reg!(0));
+    assert!(matches!(&assigns[0].1.operands[2], Operand::RegistersArray(regs) if regs.len() == 2 && regs[0] == 1));
+    // Without sources, the target is returned unchanged
+    assert_eq!(assigns[1].1.operands[1..], [reg!(0), reg_arr!()]);
+    // There is no target to return without arguments
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::CallFunc).is_some());
+}
+
+#[test]
+fn test_object_keys_values_entries() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var o = x; var k = Object.keys(o); var v = Object.values(o); var e = Object.entries(o);
+    ")).unwrap();
+    for instruction in &[Instruction::ObjectKeys, Instruction::ObjectValues, Instruction::ObjectEntries] {
+        assert_eq!(bytecode.find_command(|cmd| cmd.instruction == *instruction).unwrap().1.operands[1], reg!(0));
+    }
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var Object = 1; var k = Object.keys(x);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ObjectKeys).is_none());
+}
+
+#[test]
+fn test_object_freeze() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var o = x; var p = Object.freeze(o); var f = Object.isFrozen(p);
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ObjectFreeze).unwrap().1.operands,
+               vec![reg!(1), reg!(0)]);
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ObjectIsFrozen).unwrap().1.operands,
+               vec![reg!(2), reg!(1)]);
+}
+
+#[test]
+fn test_array_from() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var s = 'ab'; var f = x; var a = Array.from(s); var b = Array.from(s, f); var c = Array.from(s, f, 1);
+    ")).unwrap();
+    let array_froms = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::ArrayFrom);
+    assert_eq!(array_froms.len(), 2);
+    // Without map function, register 255 is passed
+    assert_eq!(array_froms[0].1.operands[1..], [reg!(0), reg!(255)]);
+    assert_eq!(array_froms[1].1.operands[1..], [reg!(0), reg!(1)]);
+}
+
+#[test]
+fn test_array_is_array() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var a = [1]; var b = Array.isArray(a);
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayIsArray).unwrap().1.operands,
+               vec![reg!(1), reg!(0)]);
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var Array = x; var b = Array.isArray([]);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::ArrayIsArray).is_none());
+}
+
+#[test]
+fn test_promise_combinators() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var p = [x, y]; var a = Promise.all(p); var b = Promise.race(p); var c = Promise.any(p);
+    ")).unwrap();
+    for instruction in &[Instruction::PromiseAll, Instruction::PromiseRace, Instruction::PromiseAny] {
+        assert_eq!(bytecode.find_command(|cmd| cmd.instruction == *instruction).unwrap().1.operands[1], reg!(0));
+    }
+}
+
+#[test]
+fn test_new_set_map() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var a = new Set([1, 2, 2]); var b = new Set(); var c = new Map([['a', 1]]);
+    ")).unwrap();
+    let sets = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::NewSet);
+    assert_eq!(sets.len(), 2);
+    assert_ne!(sets[0].1.operands[1], reg!(255));
+    // Without initial values, register 255 is passed
+    assert_eq!(sets[1].1.operands[1], reg!(255));
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::NewMap).is_some());
+
+    // The methods of a const weak map are lowered as well
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const wm = new WeakMap(); var k = {}; wm.set(k, 1); var v = wm.get(k);
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::NewWeakMap).unwrap().1.operands,
+               vec![reg!(0), reg!(255)]);
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::WeakMapSet).unwrap().1.operands[1], reg!(0));
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::WeakMapGet).unwrap().1.operands[1], reg!(0));
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::CallFunc).is_none());
+
+    // Other constructors are still unsupported
+    for js_code in &["var a = new Array(1);", "var Set = x; var a = new Set();", "var a = new Map(x, y);"] {
+        assert!(BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).is_err(), "{}", js_code);
+    }
+}
+
+#[test]
+fn test_set_operations() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const s = new Set(); var a = s.add(1); var b = s.has(1); var c = s.delete(2);
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::SetAdd).unwrap().1.operands[1], reg!(0));
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::SetHas).unwrap().1.operands[1], reg!(0));
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::SetDelete).is_some());
+
+    // Neither variables nor other constants are known to be sets
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        var s = new Set(); const t = x; var a = s.has(1); var b = t.has(1);
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::SetHas).is_none());
+}
+
+#[test]
+fn test_map_operations() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        const m = new Map(); var a = m.set('a', 1); var b = m.get('a'); var c = m.has('b');
+    ")).unwrap();
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MapSet).unwrap().1.operands.len(), 4);
+    assert_eq!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MapGet).unwrap().1.operands[1], reg!(0));
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::MapHas).is_some());
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::SetHas).is_none());
+}
+
+#[test]
+fn test_tail_call() {
+    run_test("function count(n, acc) { if (n === 0) { return acc; } return count(n - 1, acc + 1); } count(10, 0);",
+             BytecodeCompiler::new(), Bytecode::new()
+        .add(op!(LoadNum, reg!(0), short_num!(10)))
+        .add(op!(CallBytecodeFunc, addr!(15), reg!(202), reg_arr![0, 0, 1, 255]))
+        .add(op!(Exit, ))
+        .add(op!(CompStrictEqual, reg!(2), reg!(0), reg!(255)))
+        .add(op!(JumpCondNeg, reg!(2), addr!(28)))
+        .add(op!(ReturnBytecodeFunc, reg!(1), reg_arr![]))
+        .add_label(0)
+        .add(op!(Minus, reg!(4), reg!(0), reg!(254)))
+        .add(op!(Add, reg!(5), reg!(1), reg!(254)))
+        .add(op!(TailCall, addr!(15), reg_arr![0, 4, 1, 5], reg_arr![1]))
+    );
+
+    // Calls within try statements and calls whose result is used are no tail calls
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function f(n) { try { return f(n); } catch(e) {} return f(n) + 1; }
+    ")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::TailCall).is_none());
+    assert_eq!(bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::CallBytecodeFunc).len(), 2);
+}
+
+#[test]
+fn test_symbol_table() {
+    let mut compiler = BytecodeCompiler::new();
+    compiler.compile(&JSSourceCode::from_str("
+        var a = 1; var b = 'b';
+        function f(x) { var y = x; return y; }
+    ")).unwrap();
+
+    let table = compiler.symbol_table();
+    assert_eq!(table.lookup("a"), Some(0));
+    assert_eq!(table.lookup("b"), Some(1));
+    // Functions are called by their address and have no register
+    assert_eq!(table.lookup("f"), None);
+    assert_eq!(table.lookup("x"), None);
+    assert_eq!(table.lookup("y"), None);
+    assert!(table.parent().is_none());
+}
+
+#[test]
+fn test_decode_compiled_bytecode() {
+    let mut compiler = BytecodeCompiler::new();
+    let js_code = JSSourceCode::from_str("
+        function f(a, b = 2, ...c) { return a + b; }
+        var x = [1, 2.5, 'a', /a+/g];
+        for(var i = 0; i < x.length; ++i) { x[0] = f(i); }
+        try { throw x; } catch(e) { console.log(e[1]); }
+        setTimeout(f, 100000);
+    ");
+
+    let bytecode = compiler.compile(&js_code).unwrap();
+    let encoded = bytecode.to_bytes().unwrap();
+
+    assert_eq!(Bytecode::decode(&encoded).unwrap().to_bytes().unwrap(), encoded);
+}
+
+#[test]
+fn test_too_many_arguments() {
+    let mut compiler = BytecodeCompiler::new();
+    let args = vec!["0"; 256].join(", ");
+    let js_code = JSSourceCode::new(format!("console.log({});", args));
+
+    let bytecode = compiler.compile(&js_code).unwrap();
+    assert!(matches!(bytecode.encode_base64(), Err(CompilerError::TooManyArguments(256))));
+}
+
+#[test]
+fn test_collect_string_constants() {
+    let mut compiler = BytecodeCompiler::new();
+    let js_code = JSSourceCode::from_str("
+        var a = 'first';
+        function f(x) { return x + 'second'; }
+        console.log(f(a), 'first', /third/g);
+    ");
+
+    let bytecode = compiler.compile(&js_code).unwrap();
+
+    let mut visited = vec![];
+    bytecode.for_each_string_operand(|string| visited.push(string.to_string()));
+    for expected in &["first", "second", "log"] {
+        assert!(visited.iter().any(|string| string == expected));
+    }
+    assert!(!visited.iter().any(|string| string == "third"));
+
+    let constants = bytecode.collect_string_constants();
+    assert_eq!(constants.iter().filter(|string| *string == "first").count(), 1);
+
+    visited.sort();
+    visited.dedup();
+    assert_eq!(constants.len(), visited.len());
+}
+
+#[test]
+fn test_compile_eval() {
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var a = eval('1');")).unwrap();
+    assert_eq!(bytecode, Bytecode::new()
+        .add(Operation::new(Instruction::LoadString, vec![reg!(1), string!("1")]))
+        .add(Operation::new(Instruction::EvalDirect, vec![reg!(0), reg!(1)]))
+    );
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("var a = (0, eval)('1');")).unwrap();
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::EvalIndirect).is_some());
+    assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::EvalDirect).is_none());
+
+    // Calls through other references and of a declared 'eval' are regular calls
+    for js_code in &["window.eval('1');", "var eval = f; eval('1');", "eval('1', 2);"] {
+        let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code)).unwrap();
+        assert!(bytecode.find_command(|cmd| cmd.instruction == Instruction::CallFunc).is_some(), "{}", js_code);
+    }
+}
+
+#[test]
+fn test_scopes_around_eval() {
+    let count = |bytecode: &Bytecode, instruction: Instruction| {
+        bytecode.find_all_commands(|cmd| cmd.instruction == instruction).len()
+    };
+
+    let mut compiler = BytecodeCompiler::new();
+    let bytecode = compiler.compile(&JSSourceCode::from_str("
+        function f(x) {
+            while(x) { if(x) { break; } x = eval('x - 1'); }
+            return x;
+        }
+        function g(x) { while(x) { if(x) { break; } x = x - 1; } return x; }
+        f(1); g(1);
+    ")).unwrap();
+
+    // Two blocks in f, each entered once and left at its end, plus once more at the break
+    assert_eq!(count(&bytecode, Instruction::EnterScope), 2);
+    assert_eq!(count(&bytecode, Instruction::LeaveScope), 4);
+
+    let (break_index, _) = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::Jump)[0];
+    let leave_indices = bytecode.find_all_commands(|cmd| cmd.instruction == Instruction::LeaveScope);
+    assert!(leave_indices.iter().any(|(index, _)| *index + 1 == break_index));
+
+    let bytecode = BytecodeCompiler::new().compile(&JSSourceCode::from_str("
+        function g(x) { while(x) { if(x) { break; } x = x - 1; } return x; }
+        g(1);
+    ")).unwrap();
+    assert_eq!(count(&bytecode, Instruction::EnterScope), 0);
+    assert_eq!(count(&bytecode, Instruction::LeaveScope), 0);
+}
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use jsyc_compiler::{Bytecode, ToBytes};
+
+// Every byte sequence that decodes successfully has to encode back to exactly
+// the same bytes.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(bytecode) = Bytecode::decode(data) {
+        assert_eq!(bytecode.to_bytes().unwrap(), data);
+    }
+});
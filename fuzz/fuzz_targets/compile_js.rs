@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use jsyc_compiler::{BytecodeCompiler, JSSourceCode};
+
+// The compiler may reject any input, but it must do so with an error, not a panic.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(js_code) = std::str::from_utf8(data) {
+        let _ = BytecodeCompiler::new().compile(&JSSourceCode::from_str(js_code));
+    }
+});
@@ -0,0 +1,184 @@
+require("../vm.js")();
+
+// Compares specialized instructions with the generic instruction sequences they replace.
+// Run with 'npm run bench'.
+
+///! HELPERS
+
+function encodeBytecode(nonEncodedBytecode)
+{
+  return Buffer.from(nonEncodedBytecode).toString('base64')
+}
+
+function encodeRegistersArray(array)
+{
+  const arrayLength = array.length;
+
+  var encodedArray = array.slice();
+  encodedArray.unshift(arrayLength & 0xff);
+
+  return encodedArray;
+}
+
+function encodeLongNum(num)
+{
+  return [(num >> 24) & 0xff, (num >> 16) & 0xff, (num >> 8) & 0xff, (num >> 0) & 0xff]
+}
+
+const ITERATIONS = 200000;
+const RUNS = 5;
+
+// Runs the body ITERATIONS times, registers 140 to 142 are used by the loop itself
+function loopBytecode(body)
+{
+  const loopStart = 9;
+  const loopEnd = loopStart + 10 + body.length + 9;
+
+  return [
+    OP.LOAD_LONG_NUM, 140, ...encodeLongNum(ITERATIONS),
+    OP.COPY, 141, REGS.NUM_0,
+    // loopStart
+    OP.COMP_LESS_THAN, 142, 141, 140,
+    OP.JUMP_COND_NEG, 142, ...encodeLongNum(loopEnd),
+    ...body,
+    OP.ADD, 141, 141, REGS.NUM_1,
+    OP.JUMP, ...encodeLongNum(loopStart)
+    // loopEnd
+  ];
+}
+
+// Returns the fastest run time of the body in nanoseconds per iteration
+function measure(benchmark, body)
+{
+  var encodedBytecode = encodeBytecode(loopBytecode(body));
+  var fastest = Infinity;
+
+  for(var run = 0; run < RUNS; run++) {
+    var vm = new VM();
+    vm.atob = s => Buffer.from(s, "base64").toString("binary");
+
+    for(let reg_init of benchmark.init_regeisters || []) {
+      vm.setReg(reg_init[0], reg_init[1]);
+    }
+
+    vm.init(encodedBytecode);
+
+    var start = process.hrtime.bigint();
+    vm.run();
+    fastest = Math.min(fastest, Number(process.hrtime.bigint() - start));
+  }
+
+  return fastest / ITERATIONS;
+}
+
+const benchmarks = [
+  {
+    name: "Concat vs Add for strings",
+    init_regeisters: [
+      [150, "foo"],
+      [151, "bar"]
+    ],
+    generic: [
+      OP.ADD, 152, 150, 151
+    ],
+    specialized: [
+      OP.CONCAT, 152, 150, 151
+    ]
+  },
+  {
+    name: "RegExpTest vs PropAccess + CallFunc",
+    init_regeisters: [
+      [150, /\d+/],
+      [151, "abc123"],
+      [152, "test"]
+    ],
+    generic: [
+      OP.PROPACCESS, 153, 150, 0, 152,
+      OP.FUNC_CALL, 154, 153, 150, ...encodeRegistersArray([151])
+    ],
+    specialized: [
+      OP.REGEXP_TEST, 154, 150, 151
+    ]
+  },
+  {
+    name: "AddImmediate vs LoadNum + Add",
+    init_regeisters: [
+      [150, 0]
+    ],
+    generic: [
+      OP.LOAD_NUM, 151, 5,
+      OP.ADD, 150, 150, 151
+    ],
+    specialized: [
+      OP.ADD_IMMEDIATE, 150, 150, 5
+    ]
+  },
+  {
+    name: "GetArrayLength vs PropAccess",
+    init_regeisters: [
+      [150, [1, 2, 3]],
+      [151, "length"]
+    ],
+    generic: [
+      OP.PROPACCESS, 152, 150, 0, 151
+    ],
+    specialized: [
+      OP.GET_ARRAY_LENGTH, 152, 150
+    ]
+  },
+  {
+    name: "ParseInt vs PropAccess + CallFunc",
+    init_regeisters: [
+      [150, "42"],
+      [160, globalThis],
+      [161, "parseInt"]
+    ],
+    generic: [
+      OP.PROPACCESS, 151, 160, 0, 161,
+      OP.LOAD_NUM, 152, 10,
+      OP.FUNC_CALL, 153, 151, 160, ...encodeRegistersArray([150, 152])
+    ],
+    specialized: [
+      OP.PARSE_INT, 153, 150, 10
+    ]
+  },
+  {
+    name: "GetLength vs PropAccess for strings",
+    init_regeisters: [
+      [150, "hello world"],
+      [151, "length"]
+    ],
+    generic: [
+      OP.PROPACCESS, 152, 150, 0, 151
+    ],
+    specialized: [
+      OP.GET_LENGTH, 152, 150
+    ]
+  },
+  {
+    name: "MathFloor vs PropAccess + PropAccess + CallFunc",
+    init_regeisters: [
+      [150, 2.5],
+      [160, globalThis],
+      [161, "Math"],
+      [162, "floor"]
+    ],
+    generic: [
+      OP.PROPACCESS, 151, 160, 0, 161,
+      OP.PROPACCESS, 152, 151, 0, 162,
+      OP.FUNC_CALL, 153, 152, 151, ...encodeRegistersArray([150])
+    ],
+    specialized: [
+      OP.MATH_FLOOR, 153, 150
+    ]
+  }
+]
+
+for(let benchmark of benchmarks) {
+  var generic = measure(benchmark, benchmark.generic);
+  var specialized = measure(benchmark, benchmark.specialized);
+
+  console.log(benchmark.name);
+  console.log("  generic:     " + generic.toFixed(1) + " ns/iteration, " + benchmark.generic.length + " bytes");
+  console.log("  specialized: " + specialized.toFixed(1) + " ns/iteration, " + benchmark.specialized.length + " bytes");
+}
@@ -98,6 +98,52 @@ const testDataSet = [
       [153, ["Hello World", 120, 12.5]]
     ],
   },
+  {
+    name: "Assert passing",
+    bytecode: [
+      OP.LOAD_NUM, 150, 1,
+      OP.ASSERT, 150, 0x00, 0x02, 0x6f, 0x6b,
+      OP.LOAD_NUM, 151, 2
+    ],
+    expected_registers: [
+      [151, 2]
+    ],
+  },
+  {
+    name: "Load long string",
+    bytecode: [
+      OP.LOAD_STRING, 150, 0x01, 0x2c, ...new Array(300).fill(0x61),
+      OP.LOAD_NUM, 151, 1
+    ],
+    expected_registers: [
+      [150, "a".repeat(300)],
+      [151, 1]
+    ],
+  },
+  {
+    name: "Load special floats",
+    bytecode: [
+      OP.LOAD_FLOAT, 150, ...[0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+      OP.LOAD_FLOAT, 151, ...[0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+      OP.LOAD_FLOAT, 152, ...[0xff, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+      OP.LOAD_FLOAT, 153, ...[0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+    ],
+    expected_registers: [
+      [150, NaN],
+      [151, Infinity],
+      [152, -Infinity],
+      [153, -0]
+    ],
+  },
+  {
+    name: "Load 16 bit num",
+    bytecode: [
+      OP.LOAD_NUM16, 150, 0x03, 0xe8
+    ],
+    expected_registers: [
+      [150, 1000],
+    ],
+  },
   {
     name: "Load long num positiv",
     bytecode: [
@@ -127,6 +173,240 @@ const testDataSet = [
       [150, 6]
     ],
   },
+  {
+    name: "Add immediate",
+    bytecode: [
+      OP.LOAD_NUM, 150, 3,
+      OP.LOAD_STRING, 151, ...encodeString("x"),
+      OP.ADD_IMMEDIATE, 152, 150, 200,
+      OP.ADD_IMMEDIATE, 153, 151, 1,
+    ],
+    expected_registers: [
+      [152, 203],
+      [153, "x1"]
+    ],
+  },
+  {
+    name: "Concat strings",
+    bytecode: [
+      OP.LOAD_STRING, 150, ...encodeString("Hello "),
+      OP.LOAD_NUM, 151, 12,
+      OP.LOAD_NUM, 152, 3,
+      OP.CONCAT, 153, 150, 151,
+      OP.CONCAT, 154, 151, 152, // Numbers are not added
+    ],
+    expected_registers: [
+      [153, "Hello 12"],
+      [154, "123"]
+    ],
+  },
+  {
+    name: "Coerce to number",
+    init_regeisters: [
+      [160, null],
+      [161, void 0],
+      [162, true],
+      [163, "12px"]
+    ],
+    bytecode: [
+      OP.LOAD_STRING, 150, ...encodeString(" 42 "),
+      OP.LOAD_STRING, 151, ...encodeString(""),
+      OP.TO_NUMBER, 152, 150,
+      OP.TO_NUMBER, 153, 151,
+      OP.TO_NUMBER, 154, 160,
+      OP.TO_NUMBER, 155, 161,
+      OP.TO_NUMBER, 156, 162,
+      OP.TO_NUMBER, 157, 163,
+    ],
+    expected_registers: [
+      [152, 42],
+      [153, 0],
+      [154, 0],
+      [155, NaN],
+      [156, 1],
+      [157, NaN]
+    ],
+  },
+  {
+    name: "Coerce to string",
+    init_regeisters: [
+      [160, null],
+      [161, void 0],
+      [162, [1, [2, 3]]],
+      [163, {}]
+    ],
+    bytecode: [
+      OP.LOAD_NUM, 150, 12,
+      OP.TO_STRING, 151, 150,
+      OP.TO_STRING, 152, 160,
+      OP.TO_STRING, 153, 161,
+      OP.TO_STRING, 154, 162,
+      OP.TO_STRING, 155, 163,
+    ],
+    expected_registers: [
+      [151, "12"],
+      [152, "null"],
+      [153, "undefined"],
+      [154, "1,2,3"],
+      [155, "[object Object]"]
+    ],
+  },
+  {
+    name: "Builtin type coercions",
+    init_regeisters: [
+      [160, Symbol("s")],
+      [161, BigInt(7)],
+      [162, [1, [2, 3]]]
+    ],
+    bytecode: [
+      OP.TYPE_COERCE_TO_STRING, 150, 160,
+      OP.TYPE_COERCE_TO_STRING, 151, 162,
+      OP.TYPE_COERCE_TO_NUMBER, 152, 161,
+      OP.TYPE_COERCE_TO_NUMBER, 153, 151,
+    ],
+    expected_registers: [
+      [150, "Symbol(s)"],
+      [151, "1,2,3"],
+      [152, 7],
+      [153, NaN]
+    ],
+  },
+  {
+    name: "Parse numbers",
+    bytecode: [
+      OP.LOAD_STRING, 150, ...encodeString("0x1f"),
+      OP.LOAD_STRING, 151, ...encodeString("101px"),
+      OP.LOAD_STRING, 152, ...encodeString("2.5e1abc"),
+      OP.PARSE_INT, 160, 150, 0,
+      OP.PARSE_INT, 161, 151, 2,
+      OP.PARSE_INT, 162, 151, 10,
+      OP.PARSE_FLOAT, 163, 152,
+      OP.PARSE_FLOAT, 164, 151,
+    ],
+    expected_registers: [
+      [160, 31],
+      [161, 5],
+      [162, 101],
+      [163, 25],
+      [164, 101]
+    ],
+  },
+  {
+    name: "Memory barriers",
+    init_regeisters: [
+      [160, new Int32Array(new SharedArrayBuffer(4))]
+    ],
+    bytecode: [
+      OP.MEMORY_BARRIER, 2,
+      OP.PROPSET, 160, 255, 254,
+      OP.MEMORY_BARRIER, 1,
+      OP.ARRAY_GET, 150, 160, 255,
+      OP.MEMORY_BARRIER, 0,
+    ],
+    expected_registers: [
+      [150, 1]
+    ],
+  },
+  {
+    name: "Coerce to boolean",
+    init_regeisters: [
+      [160, NaN],
+      [161, []],
+      [162, null],
+      [163, false]
+    ],
+    bytecode: [
+      OP.LOAD_STRING, 150, ...encodeString(""),
+      OP.LOAD_STRING, 151, ...encodeString("0"),
+      OP.TO_BOOLEAN, 152, 150,
+      OP.TO_BOOLEAN, 153, 151,
+      OP.TO_BOOLEAN, 154, 160,
+      OP.TO_BOOLEAN, 155, 161,
+      OP.TO_BOOLEAN, 156, 162,
+      OP.TO_BOOLEAN, 157, 163,
+      OP.TO_BOOLEAN, 158, REGS.NUM_0,
+    ],
+    expected_registers: [
+      [152, false],
+      [153, true],
+      [154, false],
+      [155, true],
+      [156, false],
+      [157, false],
+      [158, false]
+    ],
+  },
+  {
+    name: "Create regular expressions",
+    bytecode: [
+      OP.CREATE_REGEXP, 150, ...encodeString("\\d+"), 0b000001,
+      OP.CREATE_REGEXP, 151, ...encodeString("^a|b$"), 0b000110,
+      OP.CREATE_REGEXP, 152, ...encodeString("x"), 0b111111,
+    ],
+    expected_registers: [
+      [150, /\d+/g],
+      [151, /^a|b$/im],
+      [152, /x/gimsuy]
+    ],
+  },
+  {
+    name: "Test and execute regular expressions",
+    bytecode: [
+      OP.CREATE_REGEXP, 150, ...encodeString("(\\d+)-(\\d+)"), 0b000000,
+      OP.LOAD_STRING, 151, ...encodeString("from 10-20"),
+      OP.LOAD_STRING, 152, ...encodeString("none"),
+      OP.REGEXP_TEST, 153, 150, 151,
+      OP.REGEXP_TEST, 154, 150, 152,
+      OP.REGEXP_EXEC, 155, 150, 151,
+      OP.REGEXP_EXEC, 156, 150, 152,
+    ],
+    expected_registers: [
+      [153, true],
+      [154, false],
+      [155, /(\d+)-(\d+)/.exec("from 10-20")],
+      [156, null]
+    ],
+  },
+  {
+    name: "Check arity",
+    init_regeisters: [
+      [150, 1],
+      [151, 1]
+    ],
+    bytecode: [
+      OP.CHECK_ARITY, 1, 151,
+      OP.TRY, 10, ...encodeLongNum(22), ...encodeLongNum(44),
+      OP.CHECK_ARITY, 2, 150,
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(50),
+      OP.LOAD_STRING, 11, ...encodeString("message"),
+      OP.PROPACCESS, 12, 10, 0, 11,
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(50),
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(50)
+    ],
+    expected_registers: [
+      [12, "Expected 2 arguments, but got 1"]
+    ],
+  },
+  {
+    name: "Default parameters",
+    init_regeisters: [
+      [150, 1],
+      [151, void 0],
+      [152, void 0],
+      [153, 10],
+      [154, "default"]
+    ],
+    bytecode: [
+      OP.DEFAULT_PARAM, 150, 153,
+      OP.DEFAULT_PARAM, 151, 153,
+      OP.DEFAULT_PARAM, 152, 154,
+    ],
+    expected_registers: [
+      [150, 1],
+      [151, 10],
+      [152, "default"]
+    ],
+  },
   {
     name: "Call member function",
     init: function() {
@@ -134,7 +414,7 @@ const testDataSet = [
     },
     bytecode: [
       OP.LOAD_STRING, 150, ...encodeString("testFunc"),
-      OP.PROPACCESS, 151, REGS.WINDOW, 150,
+      OP.PROPACCESS, 151, REGS.WINDOW, 0, 150,
       OP.FUNC_CALL, 152, 151, REGS.WINDOW, ...encodeRegistersArray([])
     ],
     expected_registers: [
@@ -149,7 +429,7 @@ const testDataSet = [
     },
     bytecode: [
       OP.LOAD_STRING, 150, ...encodeString("testFunc"),
-      OP.PROPACCESS, 151, REGS.WINDOW, 150,
+      OP.PROPACCESS, 151, REGS.WINDOW, 0, 150,
       OP.LOAD_NUM, 160, 60,
       OP.LOAD_NUM, 161, 6,
       // Cal the function
@@ -164,7 +444,7 @@ const testDataSet = [
     name: "Create object",
     bytecode: [
       OP.LOAD_STRING, 150, ...encodeString("String"),
-      OP.PROPACCESS, 151, REGS.WINDOW, 150,
+      OP.PROPACCESS, 151, REGS.WINDOW, 0, 150,
       OP.FUNC_CALL, 152, 151, REGS.WINDOW, ...encodeRegistersArray([])
     ],
     expected_registers: [
@@ -195,11 +475,106 @@ const testDataSet = [
       [160, 4416]
     ]
   },
+  {
+    name: "Collect rest arguments",
+    bytecode: [
+      OP.LOAD_NUM, 150, 1,
+      OP.LOAD_NUM, 151, 2,
+      OP.LOAD_NUM, 152, 3,
+      // 43 is the offset of the bytecode function below
+      OP.CALL_BCFUNC, ...encodeLongNum(43), 160, ...encodeRegistersArray([170, 150]),
+      OP.CALL_BCFUNC, ...encodeLongNum(43), 161, ...encodeRegistersArray([170, 150, 202, 151]),
+      OP.CALL_BCFUNC, ...encodeLongNum(43), 162, ...encodeRegistersArray([170, 150, 202, 151, 202, 152]),
+      OP.EXIT,
+
+      // The function: function(a, ...rest) { return rest; }
+      // a: 170
+      // rest: 171
+      OP.COLLECT_REST_ARGS, 171, 1,
+      OP.RETURN_BCFUNC, 171, ...encodeRegistersArray([])
+    ],
+    expected_registers: [
+      [160, []],
+      [161, [2]],
+      [162, [2, 3]]
+    ]
+  },
+  {
+    name: "Get array length",
+    init_regeisters: [
+      [150, [1, 2, 3]],
+      [151, "test"]
+    ],
+    bytecode: [
+      OP.GET_ARRAY_LENGTH, 152, 150,
+      OP.GET_ARRAY_LENGTH, 153, 151,
+    ],
+    expected_registers: [
+      [152, 3],
+      [153, 4]
+    ]
+  },
+  {
+    name: "Array get and set",
+    init_regeisters: [
+      [150, [1, 2, 3]],
+      [151, 1],
+      [152, 5],
+      [153, "x"]
+    ],
+    bytecode: [
+      OP.ARRAY_GET, 160, 150, 151,
+      OP.ARRAY_GET, 161, 150, 152,
+      OP.ARRAY_SET, 150, 151, 153,
+    ],
+    expected_registers: [
+      [160, 2],
+      [161, undefined],
+      [150, [1, "x", 3]]
+    ]
+  },
+  {
+    name: "Sort array with bytecode function",
+    init_regeisters: [
+      [160, [3, 1, 2]]
+    ],
+    bytecode: [
+      OP.ARRAY_SORT, 150, 160, ...encodeLongNum(11), ...encodeRegistersArray([0, 1]),
+      OP.EXIT,
+      // function(a, b) { return a - b; }
+      OP.MINUS, 2, 0, 1,
+      OP.RETURN_BCFUNC, 2, ...encodeRegistersArray([]),
+    ],
+    expected_registers: [
+      [150, [1, 2, 3]],
+      [160, [1, 2, 3]]
+    ]
+  },
+  {
+    name: "Map array with bytecode function",
+    init_regeisters: [
+      [5, "length"],
+      [160, [3, 1, 2]]
+    ],
+    bytecode: [
+      OP.ARRAY_MAP, 150, 160, ...encodeLongNum(12), ...encodeRegistersArray([0, 1, 2]),
+      OP.EXIT,
+      // function(x, i, arr) { return x * i + arr.length; }
+      OP.MUL, 3, 0, 1,
+      OP.PROPACCESS, 4, 2, 0, 5,
+      OP.ADD, 3, 3, 4,
+      OP.RETURN_BCFUNC, 3, ...encodeRegistersArray([]),
+    ],
+    expected_registers: [
+      [150, [3, 4, 7]],
+      [160, [3, 1, 2]]
+    ]
+  },
   {
     name: "Load and call custom function",
     bytecode: [
       OP.LOAD_STRING, 150, ...encodeString("0,function(){return 66;}"),
-      OP.EVAL, 150, 150,
+      OP.EVAL_DIRECT, 150, 150,
       OP.FUNC_CALL, 151, 150, 252, ...encodeRegistersArray([]),
     ],
     expected_registers: [
@@ -218,15 +593,15 @@ const testDataSet = [
   //     OP.TRY, 7, ...encodeLongNum(49), ...encodeLongNum(72),
   //     OP.LOAD_STRING, 4, ...encodeString('{"x": 100}'),
   //     OP.LOAD_STRING, 6, ...encodeString("parse"),
-  //     OP.PROPACCESS, 5, 1, 6,
+  //     OP.PROPACCESS, 5, 1, 0, 6,
   //     OP.FUNC_CALL, 3, 5, 1, ...encodeRegistersArray([4]),
   //     OP.LOAD_LONG_NUM, 200, ...encodeLongNum(98),
   //     OP.LOAD_STRING, 9, ...encodeString("log"),
-  //     OP.PROPACCESS, 8, 0, 9,
+  //     OP.PROPACCESS, 8, 0, 0, 9,
   //     OP.FUNC_CALL, 202, 8, 0, ...encodeRegistersArray([7]),
   //     OP.LOAD_LONG_NUM, 200, ...encodeLongNum(98),
   //     OP.LOAD_STRING, 8, ...encodeString("create"),
-  //     OP.PROPACCESS, 7, 2, 8,
+  //     OP.PROPACCESS, 7, 2, 0, 8,
   //     OP.FUNC_CALL, 3, 7, 2, ...encodeRegistersArray([9]),
   //     OP.LOAD_LONG_NUM, 200, ...encodeLongNum(98)
   //   ],
@@ -242,18 +617,18 @@ const testDataSet = [
       [2, {}]
     ],
     bytecode: [
-      OP.TRY, 7, ...encodeLongNum(49), ...encodeLongNum(58),
+      OP.TRY, 7, ...encodeLongNum(50), ...encodeLongNum(59),
       OP.LOAD_STRING, 4, ...encodeString('{"x": 100}'),
       OP.LOAD_STRING, 6, ...encodeString("parse"),
-      OP.PROPACCESS, 5, 1, 6,
+      OP.PROPACCESS, 5, 1, 0, 6,
       OP.FUNC_CALL, 3, 5, 1, ...encodeRegistersArray([4]),
-      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(81),
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(83),
       OP.COPY, 3, 2,
-      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(81),
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(83),
       OP.LOAD_STRING, 8, ...encodeString("log"),
-      OP.PROPACCESS, 7, 0, 8,
+      OP.PROPACCESS, 7, 0, 0, 8,
       OP.FUNC_CALL, 202, 7, 0, ...encodeRegistersArray([3]),
-      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(81)
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(83)
     ],
     expected_registers: [
       [3, {x: 100}]
@@ -267,22 +642,678 @@ const testDataSet = [
       [2, {}]
     ],
     bytecode: [
-      OP.TRY, 7, ...encodeLongNum(57), ...encodeLongNum(66),
+      OP.TRY, 7, ...encodeLongNum(58), ...encodeLongNum(67),
       OP.LOAD_STRING, 4, ...encodeString('{invalid, invalid}'),
       OP.LOAD_STRING, 6, ...encodeString("parse"),
-      OP.PROPACCESS, 5, 1, 6,
+      OP.PROPACCESS, 5, 1, 0, 6,
       OP.FUNC_CALL, 3, 5, 1, ...encodeRegistersArray([4]),
-      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(89),
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(91),
       OP.COPY, 3, 2,
-      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(89),
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(91),
       OP.LOAD_STRING, 8, ...encodeString("log"),
-      OP.PROPACCESS, 7, 0, 8,
+      OP.PROPACCESS, 7, 0, 0, 8,
       OP.FUNC_CALL, 202, 7, 0, ...encodeRegistersArray([3]),
-      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(89)
+      OP.LOAD_LONG_NUM, 200, ...encodeLongNum(91)
     ],
     expected_registers: [
       [3, {}]
     ]
+  },
+  {
+    name: "Halt",
+    bytecode: [
+      OP.LOAD_NUM, 150, 1,
+      OP.HALT,
+      OP.LOAD_NUM, 151, 2,
+    ],
+    expected_registers: [
+      [150, 1],
+      [151, undefined]
+    ],
+    expected_result: VM.HALTED
+  },
+  {
+    name: "Enter and leave scopes",
+    bytecode: [
+      OP.ENTER_SCOPE,
+      OP.ENTER_SCOPE,
+      OP.LEAVE_SCOPE,
+      OP.LOAD_STRING, 0, ...encodeString("typeof undeclaredVariable"),
+      OP.EVAL_DIRECT, 1, 0,
+    ],
+    expected_registers: [
+      [1, "undefined"]
+    ],
+    expected_scope_depth: 1
+  },
+  {
+    name: "With statement",
+    init_regeisters: [
+      [0, {x: 42}]
+    ],
+    bytecode: [
+      OP.WITH_STATEMENT, 0,
+      OP.LOAD_STRING, 1, ...encodeString("x"),
+      OP.EVAL_DIRECT, 2, 1,
+      OP.LEAVE_SCOPE,
+    ],
+    expected_registers: [
+      [2, 42]
+    ],
+    expected_scope_depth: 0
+  },
+  {
+    name: "Has binding",
+    init_regeisters: [
+      [0, {x: 42}],
+      [1, "x"],
+      [2, "y"],
+      [3, "values"],
+      [4, [1, 2]],
+      [5, "toFixed"],
+      [6, 5]
+    ],
+    bytecode: [
+      OP.HAS_BINDING, 10, 0, 1,
+      OP.HAS_BINDING, 11, 0, 2,
+      OP.HAS_BINDING, 12, 4, 3,
+      OP.HAS_BINDING, 13, 6, 5,
+    ],
+    expected_registers: [
+      [10, true],
+      [11, false],
+      // Array.prototype.values is listed in Symbol.unscopables
+      [12, false],
+      // Primitives are converted to objects
+      [13, true]
+    ]
+  },
+  {
+    name: "Indirect eval ignores scopes",
+    init_regeisters: [
+      [0, {x: 42}]
+    ],
+    bytecode: [
+      OP.WITH_STATEMENT, 0,
+      OP.LOAD_STRING, 1, ...encodeString("typeof x"),
+      OP.EVAL_INDIRECT, 2, 1,
+      OP.EVAL_DIRECT, 3, 1,
+      OP.LEAVE_SCOPE,
+    ],
+    expected_registers: [
+      [2, "undefined"],
+      [3, "number"]
+    ]
+  },
+  {
+    name: "Load constants",
+    constants: [
+      0, 2,
+      OP.LOAD_STRING, ...encodeString("hello"),
+      OP.LOAD_LONG_NUM, ...encodeLongNum(100000),
+    ],
+    bytecode: [
+      OP.LOAD_CONST, 150, 0, 1,
+      OP.LOAD_CONST, 151, 0, 0,
+      OP.LOAD_CONST, 152, 0, 0,
+    ],
+    expected_registers: [
+      [150, 100000],
+      [151, "hello"],
+      [152, "hello"]
+    ]
+  },
+  {
+    name: "Load symbols",
+    bytecode: [
+      OP.LOAD_SYMBOL, 150, ...encodeString("desc"),
+      OP.LOAD_WELL_KNOWN_SYMBOL, 151, 0,
+      OP.LOAD_WELL_KNOWN_SYMBOL, 152, 11,
+      OP.LOAD_STRING, 153, ...encodeString("description"),
+      OP.PROPACCESS, 154, 150, 0, 153,
+    ],
+    expected_registers: [
+      [151, Symbol.iterator],
+      [152, Symbol.toStringTag],
+      [154, "desc"]
+    ]
+  },
+  {
+    name: "Property access with symbol register",
+    init_regeisters: [
+      [150, [1, 2]]
+    ],
+    bytecode: [
+      OP.LOAD_WELL_KNOWN_SYMBOL, 151, 0,
+      OP.PROPACCESS, 152, 150, 0, 151,
+    ],
+    expected_registers: [
+      [152, Array.prototype[Symbol.iterator]]
+    ]
+  },
+  {
+    name: "Property access with symbol key",
+    init_regeisters: [
+      [150, [1, 2]]
+    ],
+    bytecode: [
+      OP.PROPACCESS, 151, 150, 1, ...encodeLongNum(0),
+    ],
+    expected_registers: [
+      [151, Array.prototype[Symbol.iterator]]
+    ]
+  },
+  {
+    name: "Get string length",
+    bytecode: [
+      OP.LOAD_STRING, 150, ...encodeString("hello"),
+      OP.GET_LENGTH, 151, 150,
+      OP.LOAD_STRING, 152, ...encodeString(""),
+      OP.GET_LENGTH, 153, 152,
+    ],
+    expected_registers: [
+      [151, 5],
+      [153, 0]
+    ]
+  },
+  {
+    name: "Slice strings",
+    init_regeisters: [
+      [150, "abcdef"],
+      [151, 1],
+      [152, 3],
+      [153, -2],
+      [154, 100],
+    ],
+    bytecode: [
+      OP.STRING_SLICE, 160, 150, 151, 152, 0,
+      OP.STRING_SLICE, 161, 150, 152, 151, 0,
+      OP.STRING_SLICE, 162, 150, 152, 151, 1,
+      OP.STRING_SLICE, 163, 150, 153, REGS.VOID, 0,
+      OP.STRING_SLICE, 164, 150, 153, 152, 1,
+      OP.STRING_SLICE, 165, 150, 152, 154, 0,
+    ],
+    expected_registers: [
+      [160, "bc"],
+      [161, ""],
+      [162, "bc"],
+      [163, "ef"],
+      [164, "abc"],
+      [165, "def"]
+    ]
+  },
+  {
+    name: "Find substrings",
+    init_regeisters: [
+      [150, "abcabc"],
+      [151, "bc"],
+      [152, "x"],
+      [153, ""],
+      [154, "abcabcabc"],
+      [155, 2],
+    ],
+    bytecode: [
+      OP.STRING_INDEX_OF, 160, 150, 151, REGS.NUM_0,
+      OP.STRING_INDEX_OF, 161, 150, 152, REGS.NUM_0,
+      OP.STRING_INDEX_OF, 162, 150, 151, 155,
+      OP.STRING_INDEX_OF, 163, 150, 153, 155,
+      OP.STRING_INDEX_OF, 164, 150, 154, REGS.NUM_0,
+    ],
+    expected_registers: [
+      [160, 1],
+      [161, -1],
+      [162, 4],
+      [163, 2],
+      [164, -1]
+    ]
+  },
+  {
+    name: "Read characters",
+    init_regeisters: [
+      [150, "abc"],
+      [151, 1],
+      [152, 3],
+    ],
+    bytecode: [
+      OP.STRING_CHAR_AT, 160, 150, 151,
+      OP.STRING_CHAR_AT, 161, 150, 152,
+      OP.STRING_CHAR_CODE_AT, 162, 150, 151,
+      OP.STRING_CHAR_CODE_AT, 163, 150, 152,
+    ],
+    expected_registers: [
+      [160, "b"],
+      [161, ""],
+      [162, 98],
+      [163, NaN]
+    ]
+  },
+  {
+    name: "Math functions",
+    init_regeisters: [
+      [150, -2.5],
+    ],
+    bytecode: [
+      OP.MATH_FLOOR, 160, 150,
+      OP.MATH_CEIL, 161, 150,
+      OP.MATH_ROUND, 162, 150,
+      OP.MATH_ABS, 163, 150,
+    ],
+    expected_registers: [
+      [160, -3],
+      [161, -2],
+      [162, -2],
+      [163, 2.5]
+    ]
+  },
+  {
+    name: "Math functions with multiple arguments",
+    init_regeisters: [
+      [150, 9],
+      [151, 2],
+      [152, -3],
+    ],
+    bytecode: [
+      OP.MATH_SQRT, 160, 150,
+      OP.MATH_POW, 161, 151, 150,
+      OP.MATH_MIN, 162, 3, 150, 151, 152,
+      OP.MATH_MAX, 163, 3, 150, 151, 152,
+      OP.MATH_MIN, 164, 0,
+      OP.MATH_MAX, 165, 0,
+    ],
+    expected_registers: [
+      [160, 3],
+      [161, 512],
+      [162, -3],
+      [163, 9],
+      [164, Infinity],
+      [165, -Infinity]
+    ]
+  },
+  {
+    name: "JSON",
+    init_regeisters: [
+      [150, {a: [1, {b: "c"}], d: null}],
+      [151, '{"a":[1,{"b":"c"}],"d":null}'],
+      [152, ["a"]],
+      [153, 2],
+      [154, function(key, value) { return typeof value === "number" ? value + 1 : value; }],
+    ],
+    bytecode: [
+      OP.JSON_STRINGIFY, 160, 150, 255, 255,
+      OP.JSON_STRINGIFY, 161, 150, 152, 255,
+      OP.JSON_STRINGIFY, 162, 150, 255, 153,
+      OP.JSON_PARSE, 163, 151, 255,
+      OP.JSON_PARSE, 164, 151, 154,
+    ],
+    expected_registers: [
+      [160, '{"a":[1,{"b":"c"}],"d":null}'],
+      [161, '{"a":[1,{}]}'],
+      [162, '{\n  "a": [\n    1,\n    {\n      "b": "c"\n    }\n  ],\n  "d": null\n}'],
+      [163, {a: [1, {b: "c"}], d: null}],
+      [164, {a: [2, {b: "c"}], d: null}]
+    ]
+  },
+  {
+    name: "Object.assign",
+    init_regeisters: [
+      [150, {a: 1, b: 1}],
+      [151, {b: 2, c: 2}],
+      [152, {c: 3}],
+      [153, Object.create({inherited: 4}, {
+        own: {value: 4, enumerable: true},
+        hidden: {value: 4, enumerable: false}
+      })],
+      [154, {a: 5}],
+    ],
+    bytecode: [
+      OP.OBJECT_ASSIGN, 160, 150, 2, 151, 152,
+      OP.OBJECT_ASSIGN, 161, 153, 0,
+      OP.OBJECT_ASSIGN, 162, 154, 1, 153,
+    ],
+    expected_registers: [
+      // Later sources win
+      [160, {a: 1, b: 2, c: 3}],
+      [150, {a: 1, b: 2, c: 3}],
+      [161, Object.create({inherited: 4}, {
+        own: {value: 4, enumerable: true},
+        hidden: {value: 4, enumerable: false}
+      })],
+      // Neither inherited nor non-enumerable properties are copied
+      [162, {a: 5, own: 4}]
+    ]
+  },
+  {
+    name: "Object keys, values and entries",
+    init_regeisters: [
+      [150, Object.create({inherited: 1}, {
+        a: {value: 2, enumerable: true},
+        hidden: {value: 3, enumerable: false},
+        [Symbol.iterator]: {value: 4, enumerable: true}
+      })],
+    ],
+    bytecode: [
+      OP.OBJECT_KEYS, 160, 150,
+      OP.OBJECT_VALUES, 161, 150,
+      OP.OBJECT_ENTRIES, 162, 150,
+    ],
+    expected_registers: [
+      // Inherited, non-enumerable and symbol-keyed properties are excluded
+      [160, ["a"]],
+      [161, [2]],
+      [162, [["a", 2]]]
+    ]
+  },
+  {
+    name: "Array.from",
+    init_regeisters: [
+      [150, "ab"],
+      [151, new Set([1, 2, 2])],
+      [152, new Map([["a", 1], ["b", 2]])],
+      [153, (function*() { yield 1; yield 2; })()],
+      [154, function(x) { return x + x; }],
+    ],
+    bytecode: [
+      OP.ARRAY_FROM, 160, 150, 255,
+      OP.ARRAY_FROM, 161, 151, 255,
+      OP.ARRAY_FROM, 162, 152, 255,
+      OP.ARRAY_FROM, 163, 153, 154,
+      OP.ARRAY_FROM, 164, 150, 154,
+    ],
+    expected_registers: [
+      [160, ["a", "b"]],
+      [161, [1, 2]],
+      [162, [["a", 1], ["b", 2]]],
+      [163, [2, 4]],
+      [164, ["aa", "bb"]]
+    ]
+  },
+  {
+    name: "Array.isArray",
+    init_regeisters: [
+      [150, []],
+      [151, {}],
+      [152, new Array(3)],
+      [153, {length: 1, 0: "a"}],
+      [154, "abc"],
+    ],
+    bytecode: [
+      OP.ARRAY_IS_ARRAY, 160, 150,
+      OP.ARRAY_IS_ARRAY, 161, 151,
+      OP.ARRAY_IS_ARRAY, 162, 152,
+      OP.ARRAY_IS_ARRAY, 163, 153,
+      OP.ARRAY_IS_ARRAY, 164, 154,
+    ],
+    expected_registers: [
+      [160, true],
+      [161, false],
+      [162, true],
+      // Array-like objects are no arrays
+      [163, false],
+      [164, false]
+    ]
+  },
+  {
+    name: "Create sets and maps",
+    init_regeisters: [
+      [150, [3, 1, 3, 2, 1]],
+      [151, [["b", 1], ["a", 2], ["b", 3]]],
+    ],
+    bytecode: [
+      OP.NEW_SET, 160, 150,
+      OP.NEW_SET, 161, 255,
+      OP.NEW_MAP, 162, 151,
+      OP.NEW_MAP, 163, 255,
+    ],
+    expected_registers: [
+      // Sets deduplicate, later map entries overwrite earlier ones
+      [160, new Set([3, 1, 2])],
+      [161, new Set()],
+      [162, new Map([["b", 3], ["a", 2]])],
+      [163, new Map()]
+    ]
+  },
+  {
+    name: "Set operations",
+    init_regeisters: [
+      [150, new Set([1])],
+      [151, 2],
+    ],
+    bytecode: [
+      OP.SET_ADD, 160, 150, 151,
+      OP.SET_HAS, 161, 150, 151,
+      OP.SET_DELETE, 162, 150, 254,
+      OP.SET_DELETE, 163, 150, 254,
+      OP.SET_HAS, 164, 150, 254,
+    ],
+    expected_registers: [
+      [160, new Set([2])],
+      [161, true],
+      [162, true],
+      [163, false],
+      [164, false]
+    ]
+  },
+  {
+    name: "Map operations",
+    init_regeisters: [
+      [150, new Map([["a", 1], ["n", null]])],
+      [151, "a"],
+      [152, "b"],
+      [153, "n"],
+      [154, "default"],
+    ],
+    bytecode: [
+      OP.MAP_GET, 160, 150, 151,
+      OP.MAP_GET, 161, 150, 152,
+      OP.MAP_SET, 162, 150, 152, 254,
+      OP.MAP_HAS, 163, 150, 152,
+      OP.MAP_GET_OR_DEFAULT, 164, 150, 151, 154,
+      OP.MAP_GET_OR_DEFAULT, 165, 150, 153, 154,
+      OP.MAP_GET_OR_DEFAULT, 166, 150, 154, 154,
+      OP.MAP_GET_OR_DEFAULT, 167, 150, 153, 255,
+    ],
+    expected_registers: [
+      [160, 1],
+      [161, undefined],
+      [162, new Map([["a", 1], ["n", null], ["b", 1]])],
+      [163, true],
+      [164, 1],
+      [165, "default"],
+      [166, "default"],
+      [167, 0]
+    ]
+  },
+  {
+    name: "Load global this",
+    bytecode: [
+      OP.LOAD_GLOBAL_THIS, 150,
+      OP.LOAD_STRING, 151, ...encodeString("Array"),
+      OP.PROPACCESS, 152, 150, 0, 151
+    ],
+    expected_registers: [
+      [150, globalThis],
+      [152, Array]
+    ]
+  },
+  {
+    name: "Array slice",
+    init_regeisters: [
+      [150, [1, 2, 3, 4]],
+      [151, 1],
+      [152, -1],
+      [153, -3]
+    ],
+    bytecode: [
+      OP.ARRAY_SLICE, 160, 150, 151, REGS.NUM_0,
+      OP.ARRAY_SLICE, 161, 150, REGS.NUM_0, REGS.NUM_0,
+      OP.ARRAY_SLICE, 162, 150, 153, 152,
+      OP.ARRAY_SLICE, 163, 150, 152, REGS.VOID,
+      OP.ARRAY_SLICE, 164, 150, 151, 151
+    ],
+    expected_registers: [
+      [160, [2, 3, 4]],
+      [161, [1, 2, 3, 4]],
+      [162, [2, 3]],
+      [163, [4]],
+      [164, []]
+    ]
+  },
+  {
+    name: "Array concat",
+    init_regeisters: [
+      [150, [1, 2]],
+      [151, [3, [4]]],
+      [152, "5"],
+      [153, {length: 1, 0: 6}]
+    ],
+    bytecode: [
+      OP.ARRAY_CONCAT, 160, 150, ...encodeRegistersArray([]),
+      OP.ARRAY_CONCAT, 161, 150, ...encodeRegistersArray([151]),
+      OP.ARRAY_CONCAT, 162, 150, ...encodeRegistersArray([151, 152, 153, REGS.NUM_1])
+    ],
+    expected_registers: [
+      [160, [1, 2]],
+      [161, [1, 2, 3, [4]]],
+      [162, [1, 2, 3, [4], "5", {length: 1, 0: 6}, 1]]
+    ]
+  },
+  {
+    name: "Array flat",
+    init_regeisters: [
+      [150, [1, [2, [3, [4]]]]],
+      [151, 0],
+      [152, 2]
+    ],
+    bytecode: [
+      OP.ARRAY_FLAT, 160, 150, 151,
+      OP.ARRAY_FLAT, 161, 150, REGS.NUM_1,
+      OP.ARRAY_FLAT, 162, 150, 152,
+      OP.ARRAY_FLAT, 163, 150, REGS.NUM_0
+    ],
+    expected_registers: [
+      [160, [1, [2, [3, [4]]]]],
+      [161, [1, 2, [3, [4]]]],
+      [162, [1, 2, 3, [4]]],
+      [163, [1, 2, 3, 4]]
+    ]
+  },
+  {
+    name: "String split",
+    init_regeisters: [
+      [150, "a,b;c"],
+      [151, ","],
+      [152, ""],
+      [153, /[,;]/],
+      [154, 2],
+      [155, 0]
+    ],
+    bytecode: [
+      OP.STRING_SPLIT, 160, 150, 151, REGS.NUM_0,
+      OP.STRING_SPLIT, 161, 150, 152, REGS.NUM_0,
+      OP.STRING_SPLIT, 162, 150, 154, REGS.NUM_0,
+      OP.STRING_SPLIT, 163, 150, 153, 154,
+      OP.STRING_SPLIT, 164, 150, 152, 155
+    ],
+    expected_registers: [
+      [160, ["a", "b;c"]],
+      [161, ["a", ",", "b", ";", "c"]],
+      [162, ["a,b;c"]],
+      [163, ["a", "b"]],
+      [164, []]
+    ]
+  },
+  {
+    name: "String pad",
+    init_regeisters: [
+      [150, "abc"],
+      [151, 6],
+      [152, ""],
+      [153, "12"],
+      [154, 3],
+      [155, 0]
+    ],
+    bytecode: [
+      OP.STRING_PAD_START, 160, 150, 151, REGS.NUM_0,
+      OP.STRING_PAD_END, 161, 150, 151, REGS.NUM_0,
+      OP.STRING_PAD_START, 162, 150, 151, 152,
+      OP.STRING_PAD_START, 163, 150, 151, 153,
+      OP.STRING_PAD_END, 164, 150, 151, 153,
+      OP.STRING_PAD_START, 165, 150, 154, 153,
+      OP.STRING_PAD_END, 166, 150, REGS.NUM_1, 153,
+      OP.STRING_PAD_START, 167, 150, 151, 155
+    ],
+    expected_registers: [
+      [160, "   abc"],
+      [161, "abc   "],
+      [162, "abc"],
+      [163, "121abc"],
+      [164, "abc121"],
+      [165, "abc"],
+      [166, "abc"],
+      [167, "000abc"]
+    ]
+  },
+  {
+    name: "String trim",
+    init_regeisters: [
+      [150, "\t\n\u00a0\ufeff\u2028 a \tb\r\u3000\v\f"],
+      [151, ""],
+      [152, " \t\n\u00a0 "]
+    ],
+    bytecode: [
+      OP.STRING_TRIM, 160, 150,
+      OP.STRING_TRIM_START, 161, 150,
+      OP.STRING_TRIM_END, 162, 150,
+      OP.STRING_TRIM, 163, 151,
+      OP.STRING_TRIM_START, 164, 152,
+      OP.STRING_TRIM_END, 165, 152
+    ],
+    expected_registers: [
+      [160, "a \tb"],
+      [161, "a \tb\r\u3000\v\f"],
+      [162, "\t\n\u00a0\ufeff\u2028 a \tb"],
+      [163, ""],
+      [164, ""],
+      [165, ""]
+    ]
+  },
+  {
+    name: "String search",
+    init_regeisters: [
+      [150, "a\ud83d\ude00b"],
+      [151, ""],
+      [152, "\ud83d"],
+      [153, "\ude00b"],
+      [154, -5],
+      [155, "a"],
+      [156, 0],
+      [157, [1, NaN]],
+      [158, NaN]
+    ],
+    bytecode: [
+      OP.STRING_INCLUDES, 160, 150, 151, REGS.NUM_0,
+      OP.STRING_STARTS_WITH, 161, 150, 151, 154,
+      OP.STRING_ENDS_WITH, 162, 150, 151, 156,
+      OP.STRING_INCLUDES, 163, 150, 152, REGS.NUM_0,
+      OP.STRING_ENDS_WITH, 164, 150, 153, REGS.NUM_0,
+      OP.STRING_STARTS_WITH, 165, 150, 155, 154,
+      OP.STRING_INCLUDES, 166, 150, 155, REGS.NUM_1,
+      OP.STRING_ENDS_WITH, 167, 150, 155, REGS.NUM_1,
+      OP.STRING_ENDS_WITH, 168, 150, 155, 156,
+      OP.STRING_INCLUDES, 169, 157, 158, REGS.NUM_0
+    ],
+    expected_registers: [
+      [160, true],
+      [161, true],
+      [162, true],
+      [163, true],
+      [164, true],
+      [165, true],
+      [166, false],
+      [167, true],
+      [168, false],
+      [169, true]
+    ]
   }
 ]
 
@@ -306,15 +1337,23 @@ function runVMTests(testData) {
   vm.atob = require("atob");
   vm.setReg(REGS.WINDOW, window);
 
-  vm.init(encodedBytecode);
+  if(typeof testData.constants !== "undefined") {
+    vm.init(encodedBytecode, encodeBytecode(testData.constants));
+  } else {
+    vm.init(encodedBytecode);
+  }
 
   const result = vm.run();
-  assert.equal(result, 0);
+  assert.equal(result, testData.expected_result || 0);
 
   for(let regData of testData.expected_registers) {
     assert.deepEqual(vm.getReg(regData[0]), regData[1],
-                "Expected register " + regData[0] +  " to be " + regData[1] +
-                " but it is " + vm.getReg(regData[0]));
+                "Expected register " + regData[0] +  " to be " + String(regData[1]) +
+                " but it is " + String(vm.getReg(regData[0])));
+  }
+
+  if(typeof testData.expected_scope_depth !== "undefined") {
+    assert.equal(vm.scopes.length, testData.expected_scope_depth);
   }
 }
 
@@ -324,5 +1363,523 @@ describe("VM Tests", function() {
     for(let testData of testDataSet) {
       it(testData.name, () => runVMTests(testData));
     }
+
+    it("Map insertion order", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.NEW_MAP, 160, 150,
+      ]));
+      vm.setReg(150, [["b", 1], ["a", 2], ["b", 3]]);
+
+      vm.run();
+      assert.deepEqual(Array.from(vm.getReg(160).keys()), ["b", "a"]);
+    });
+
+    it("Create weak maps", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.NEW_WEAK_MAP, 160, 150,
+        OP.NEW_WEAK_MAP, 161, 255,
+      ]));
+      var key = {};
+      vm.setReg(150, [[key, 1]]);
+
+      vm.run();
+      assert.ok(vm.getReg(160) instanceof WeakMap);
+      assert.equal(vm.getReg(160).get(key), 1);
+      assert.ok(vm.getReg(161) instanceof WeakMap);
+      assert.equal(vm.getReg(161).has(key), false);
+    });
+  });
+
+  describe("Lifecycle Tests", function() {
+    it("Resume after halt", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.LOAD_NUM, 150, 1,
+        OP.HALT,
+        OP.LOAD_NUM, 151, 2,
+      ]));
+
+      assert.equal(vm.run(), VM.HALTED);
+      assert.equal(vm.getReg(150), 1);
+      assert.equal(vm.getReg(151), undefined);
+
+      assert.equal(vm.run(), 0);
+      assert.equal(vm.getReg(151), 2);
+    });
+
+    it("Replaced random source", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      var next = 0;
+      vm.random = function() { return (next++ % 4) / 4; };
+      vm.init(encodeBytecode([
+        OP.MATH_RANDOM, 150,
+        OP.MATH_RANDOM, 151,
+      ]));
+
+      vm.run();
+      assert.equal(vm.getReg(150), 0);
+      assert.equal(vm.getReg(151), 0.25);
+    });
+
+    it("Non-decreasing timestamps", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      var times = [1000, 500, 2000];
+      vm.now = function() { return times.shift(); };
+      vm.init(encodeBytecode([
+        OP.DATE_NOW, 150,
+        OP.DATE_NOW, 151,
+        OP.DATE_NOW, 152,
+      ]));
+
+      vm.run();
+      assert.equal(vm.getReg(150), 1000);
+      assert.equal(vm.getReg(151), 1000);
+      assert.equal(vm.getReg(152), 2000);
+    });
+
+    it("Mutual recursion with tail calls", function() {
+      // isEven(n) { if(n === 0) return 1; return isOdd(n - 1); } and isOdd vice versa
+      function parityBytecode(tailCalls) {
+        function call(offset) {
+          return tailCalls ?
+            [OP.TAIL_CALL, ...encodeLongNum(offset), ...encodeRegistersArray([150, 150]), ...encodeRegistersArray([])] :
+            [OP.CALL_BCFUNC, ...encodeLongNum(offset), 152, ...encodeRegistersArray([150, 150]),
+             OP.RETURN_BCFUNC, 152, ...encodeRegistersArray([])];
+        }
+        function func(offset, baseCaseReg, otherOffset) {
+          return [
+            OP.COMP_STRICT_EQUAL, 151, 150, 255,
+            OP.JUMP_COND_NEG, 151, ...encodeLongNum(offset + 13),
+            OP.RETURN_BCFUNC, baseCaseReg, ...encodeRegistersArray([]),
+            OP.MINUS, 150, 150, 254,
+            ...call(otherOffset)
+          ];
+        }
+
+        var isEven = 10, isOdd = isEven + func(0, 0, 0).length;
+        return [
+          OP.CALL_BCFUNC, ...encodeLongNum(isEven), 153, ...encodeRegistersArray([150, 150]),
+          OP.EXIT,
+          ...func(isEven, 254, isOdd),
+          ...func(isOdd, 255, isEven)
+        ];
+      }
+
+      function run(tailCalls, n) {
+        var vm = new VM();
+        vm.atob = require("atob");
+        vm.init(encodeBytecode(parityBytecode(tailCalls)));
+
+        var maxDepth = 0;
+        [OP.CALL_BCFUNC, OP.TAIL_CALL].forEach(function(op) {
+          var handler = vm.ops[op];
+          vm.ops[op] = function(vm) {
+            handler(vm);
+            maxDepth = Math.max(maxDepth, vm.reg_backups.length);
+          };
+        });
+
+        vm.setReg(150, n);
+        vm.run();
+        return [vm.getReg(153), maxDepth];
+      }
+
+      // Without tail calls every call keeps a backup of all registers until the recursion ends
+      assert.deepEqual(run(false, 10000), [1, 10001]);
+      assert.deepEqual(run(true, 10000), [1, 1]);
+      assert.deepEqual(run(true, 100001), [0, 1]);
+    });
+
+    it("Object spread", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.OBJECT_SPREAD, 160, 255, 253,
+        OP.OBJECT_SPREAD, 160, 160, 150,
+        OP.OBJECT_SPREAD, 161, 255, 151,
+        OP.OBJECT_SPREAD, 162, 152, 150,
+      ]));
+      var source = {a: 1, ["__proto__"]: 2};
+      Object.defineProperty(source, "hidden", {value: 3, enumerable: false});
+      source[Symbol("symbol")] = 4;
+      vm.setReg(150, source);
+      vm.setReg(151, "ab");
+      vm.setReg(152, {b: 5});
+
+      vm.run();
+      // Non-enumerable and symbol-keyed properties are skipped
+      assert.deepEqual(Object.getOwnPropertyNames(vm.getReg(160)), ["a", "__proto__"]);
+      assert.deepEqual(Object.getOwnPropertySymbols(vm.getReg(160)), []);
+      assert.equal(Object.getPrototypeOf(vm.getReg(160)), Object.prototype);
+      assert.deepEqual(vm.getReg(161), {0: "a", 1: "b"});
+      assert.equal(vm.getReg(162), vm.getReg(152));
+      assert.deepEqual(Object.keys(vm.getReg(162)), ["b", "a", "__proto__"]);
+    });
+
+    it("Set property of frozen object", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.OBJECT_IS_FROZEN, 160, 150,
+        OP.OBJECT_FREEZE, 161, 150,
+        OP.OBJECT_IS_FROZEN, 162, 150,
+        OP.LOAD_STRING, 151, ...encodeString("a"),
+        OP.PROPSET, 150, 151, 254,
+      ]));
+      vm.setReg(150, {a: 0});
+
+      assert.throws(() => vm.run(), TypeError);
+      assert.equal(vm.getReg(160), false);
+      assert.equal(vm.getReg(161), vm.getReg(150));
+      assert.equal(vm.getReg(162), true);
+      assert.deepEqual(vm.getReg(150), {a: 0});
+    });
+
+    it("Promise combinators", async function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.PROMISE_ALL, 160, 150,
+        OP.PROMISE_RACE, 161, 150,
+        OP.PROMISE_ANY, 162, 151,
+      ]));
+      var later = new Promise(resolve => setTimeout(() => resolve("later"), 10));
+      vm.setReg(150, [later, Promise.resolve("now")]);
+      vm.setReg(151, [Promise.reject(new Error("rejected")), later]);
+
+      vm.run();
+      assert.deepEqual(await vm.getReg(160), ["later", "now"]);
+      assert.equal(await vm.getReg(161), "now");
+      assert.equal(await vm.getReg(162), "later");
+    });
+
+    it("Weak map access", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.WEAK_MAP_SET, 160, 150, 151, 254,
+        OP.WEAK_MAP_GET, 161, 150, 151,
+        OP.WEAK_MAP_GET, 162, 150, 152,
+      ]));
+      vm.setReg(150, new WeakMap());
+      vm.setReg(151, {});
+      vm.setReg(152, "no object");
+
+      assert.throws(() => vm.run(), TypeError);
+      assert.equal(vm.getReg(160), vm.getReg(150));
+      assert.equal(vm.getReg(161), 1);
+      assert.equal(vm.getReg(162), undefined);
+
+      vm.init(encodeBytecode([
+        OP.WEAK_MAP_SET, 160, 150, 255, 254,
+      ]));
+      vm.setReg(150, new WeakMap());
+      assert.throws(() => vm.run(), TypeError);
+    });
+
+    it("Array map, filter and reduce pipeline", function() {
+      // function double(x) { return x * 2; }, function isBig(x) { return x > 2; }, function add(a, b) { return a + b; }
+      var double = [OP.MUL, 1, 0, 152, OP.RETURN_BCFUNC, 1, ...encodeRegistersArray([])];
+      var isBig = [OP.COMP_GREATHER_THAN, 1, 0, 152, OP.RETURN_BCFUNC, 1, ...encodeRegistersArray([])];
+      var add = [OP.ADD, 2, 0, 1, OP.RETURN_BCFUNC, 2, ...encodeRegistersArray([])];
+      function pipeline(initial) {
+        var doubleOffset = 41, isBigOffset = doubleOffset + double.length, addOffset = isBigOffset + isBig.length;
+        return [
+          OP.ARRAY_MAP, 160, 150, ...encodeLongNum(doubleOffset), ...encodeRegistersArray([0]),
+          OP.ARRAY_FILTER, 161, 160, ...encodeLongNum(isBigOffset), ...encodeRegistersArray([0]),
+          OP.ARRAY_REDUCE, 162, 161, ...encodeLongNum(addOffset), ...encodeRegistersArray([0, 1]), initial,
+          OP.ARRAY_REDUCE, 163, 161, ...encodeLongNum(addOffset), ...encodeRegistersArray([0, 1]), 255,
+          OP.EXIT,
+          ...double, ...isBig, ...add
+        ];
+      }
+
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode(pipeline(151)));
+      vm.setReg(150, [1, 2, 3]);
+      vm.setReg(151, "sum: ");
+      vm.setReg(152, 2);
+
+      vm.run();
+      assert.deepEqual(vm.getReg(160), [2, 4, 6]);
+      assert.deepEqual(vm.getReg(161), [4, 6]);
+      assert.equal(vm.getReg(162), "sum: 46");
+      assert.equal(vm.getReg(163), 10);
+      assert.deepEqual(vm.getReg(150), [1, 2, 3]);
+
+      // Reducing an empty array without an initial value throws
+      vm.init(encodeBytecode(pipeline(151)));
+      vm.setReg(150, [1]);
+      vm.setReg(151, 0);
+      vm.setReg(152, 2);
+      assert.throws(() => vm.run(), TypeError);
+      assert.deepEqual(vm.getReg(161), []);
+      assert.equal(vm.getReg(162), 0);
+    });
+
+    it("Array find and find index", function() {
+      // function matches(x) { return x.id === wanted; }
+      var matches = [OP.PROPACCESS, 1, 0, 0, 151, OP.COMP_STRICT_EQUAL, 1, 1, 152, OP.RETURN_BCFUNC, 1, ...encodeRegistersArray([])];
+      var items = [{id: "a"}, {id: "b"}, {id: "c"}];
+
+      [["a", items[0], 0], ["c", items[2], 2], ["d", undefined, -1]].forEach(function([wanted, found, index]) {
+        var vm = new VM();
+        vm.atob = require("atob");
+        vm.init(encodeBytecode([
+          OP.ARRAY_FIND, 160, 150, ...encodeLongNum(19), ...encodeRegistersArray([0]),
+          OP.ARRAY_FIND_INDEX, 161, 150, ...encodeLongNum(19), ...encodeRegistersArray([0]),
+          OP.EXIT,
+          ...matches
+        ]));
+        vm.setReg(150, items);
+        vm.setReg(151, "id");
+        vm.setReg(152, wanted);
+
+        vm.run();
+        assert.equal(vm.getReg(160), found);
+        assert.equal(vm.getReg(161), index);
+      });
+    });
+
+    it("Array every and some short-circuit", function() {
+      // function isPositive(x) { counter.calls += 1; return x > 0; }
+      var isPositive = [
+        OP.PROPACCESS, 2, 153, 0, 151,
+        OP.ADD, 2, 2, 254,
+        OP.PROPSET, 153, 151, 2,
+        OP.COMP_GREATHER_THAN, 1, 0, 255,
+        OP.RETURN_BCFUNC, 1, ...encodeRegistersArray([])
+      ];
+
+      [[OP.ARRAY_EVERY, [1, -1, 2, 3], false, 2],
+       [OP.ARRAY_EVERY, [1, 2, 3], true, 3],
+       [OP.ARRAY_SOME, [-1, 1, -2, 3], true, 2],
+       [OP.ARRAY_SOME, [-1, -2], false, 2]].forEach(function([op, arr, result, calls]) {
+        var vm = new VM();
+        vm.atob = require("atob");
+        vm.init(encodeBytecode([
+          op, 160, 150, ...encodeLongNum(10), ...encodeRegistersArray([0]),
+          OP.EXIT,
+          ...isPositive
+        ]));
+        vm.setReg(150, arr);
+        vm.setReg(151, "calls");
+        vm.setReg(153, {calls: 0});
+
+        vm.run();
+        assert.equal(vm.getReg(160), result);
+        assert.equal(vm.getReg(153).calls, calls);
+      });
+    });
+
+    it("Array for each with modifications", function() {
+      // function visit(x, i, arr) { visited.push(x); arr.push(x); arr[i + 1] = x * 10; }
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.ARRAY_FOR_EACH, 150, ...encodeLongNum(11), ...encodeRegistersArray([0, 1, 2]),
+        OP.EXIT,
+        OP.FUNC_CALL, 202, 152, 151, ...encodeRegistersArray([0]),
+        OP.FUNC_CALL, 202, 152, 2, ...encodeRegistersArray([0]),
+        OP.ADD, 3, 1, 254,
+        OP.MUL, 4, 0, 153,
+        OP.ARRAY_SET, 2, 3, 4,
+        OP.RETURN_BCFUNC, 253, ...encodeRegistersArray([])
+      ]));
+      vm.setReg(150, [1, 2]);
+      vm.setReg(151, []);
+      vm.setReg(152, Array.prototype.push);
+      vm.setReg(153, 10);
+
+      vm.run();
+      // Appended elements are not visited, changed elements are visited with their new value
+      assert.deepEqual(vm.getReg(151), [1, 10]);
+      assert.deepEqual(vm.getReg(150), [1, 10, 100, 10]);
+    });
+
+    it("Array flat map", function() {
+      // function pairs(x, i) { return i === 1 ? x : [x, [i]]; }
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.ARRAY_FLAT_MAP, 160, 150, ...encodeLongNum(11), ...encodeRegistersArray([0, 1]),
+        OP.EXIT,
+        OP.COMP_STRICT_EQUAL, 2, 1, 254,
+        OP.JUMP_COND_NEG, 2, ...encodeLongNum(24),
+        OP.RETURN_BCFUNC, 0, ...encodeRegistersArray([]),
+        OP.LOAD_ARRAY, 3, ...encodeRegistersArray([1]),
+        OP.LOAD_ARRAY, 3, ...encodeRegistersArray([0, 3]),
+        OP.RETURN_BCFUNC, 3, ...encodeRegistersArray([])
+      ]));
+      vm.setReg(150, ["a", "b", "c"]);
+
+      vm.run();
+      // Non-array results are kept, array results are flattened by one level
+      assert.deepEqual(vm.getReg(160), ["a", [0], "b", "c", [2]]);
+    });
+
+    it("String repeat", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.STRING_REPEAT, 160, 150, 151,
+        OP.STRING_REPEAT, 161, 150, 255,
+        OP.STRING_REPEAT, 162, 150, 152,
+        OP.STRING_REPEAT, 163, 150, 153,
+        OP.STRING_REPEAT, 164, 150, 154,
+      ]));
+      vm.setReg(150, "ab");
+      vm.setReg(151, 3);
+      vm.setReg(152, 2.7);
+      vm.setReg(153, -0.5);
+      vm.setReg(154, -1);
+
+      assert.throws(() => vm.run(), RangeError);
+      assert.equal(vm.getReg(160), "ababab");
+      assert.equal(vm.getReg(161), "");
+      // Non-integer counts are truncated
+      assert.equal(vm.getReg(162), "abab");
+      assert.equal(vm.getReg(163), "");
+      assert.equal(vm.getReg(164), undefined);
+    });
+
+    it("String match", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.STRING_MATCH, 160, 150, 151,
+        OP.STRING_MATCH, 161, 150, 152,
+        OP.STRING_MATCH, 162, 150, 153,
+        OP.STRING_MATCH_ALL, 163, 150, 152,
+        OP.STRING_MATCH_ALL, 164, 150, 151,
+      ]));
+      vm.setReg(150, "a1 b22");
+      vm.setReg(151, /([a-z])(\d+)/);
+      vm.setReg(152, /([a-z])(\d+)/g);
+      vm.setReg(153, /x/);
+
+      // Regular expressions without the global flag are rejected by matchAll
+      assert.throws(() => vm.run(), TypeError);
+      assert.deepEqual(Array.from(vm.getReg(160)), ["a1", "a", "1"]);
+      assert.equal(vm.getReg(160).index, 0);
+      assert.deepEqual(vm.getReg(161), ["a1", "b22"]);
+      assert.equal(vm.getReg(162), null);
+      assert.deepEqual(Array.from(vm.getReg(163), match => Array.from(match)), [["a1", "a", "1"], ["b22", "b", "22"]]);
+      assert.equal(vm.getReg(164), undefined);
+    });
+
+    it("String replace", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.BCFUNC_CALLBACK, 155, ...encodeLongNum(41), ...encodeRegistersArray([0, 1, 2]),
+        OP.STRING_REPLACE, 160, 150, 151, 153,
+        OP.STRING_REPLACE, 161, 150, 152, 153,
+        OP.STRING_REPLACE_ALL, 162, 150, 152, 154,
+        OP.STRING_REPLACE, 163, 150, 152, 155,
+        OP.STRING_REPLACE_ALL, 164, 150, 151, 154,
+        OP.STRING_REPLACE_ALL, 165, 150, 156, 155,
+        OP.EXIT,
+        // function(match, letter, digits) { return digits + letter; }
+        OP.ADD, 3, 2, 1,
+        OP.RETURN_BCFUNC, 3, ...encodeRegistersArray([])
+      ]));
+      vm.setReg(150, "a1 b22 a1");
+      vm.setReg(151, "a1");
+      vm.setReg(152, /([a-z])(\d+)/g);
+      vm.setReg(153, "[$&]");
+      vm.setReg(154, "$2$1");
+      vm.setReg(156, /([a-z])(\d+)/);
+
+      // Regular expressions without the global flag are rejected by replaceAll
+      assert.throws(() => vm.run(), TypeError);
+      assert.equal(vm.getReg(160), "[a1] b22 a1");
+      assert.equal(vm.getReg(161), "[a1] [b22] [a1]");
+      assert.equal(vm.getReg(162), "1a 22b 1a");
+      // Function replacements are called with the match and its groups
+      assert.equal(vm.getReg(163), "1a 22b 1a");
+      assert.equal(vm.getReg(164), "$2$1 b22 $2$1");
+      assert.equal(vm.getReg(165), undefined);
+    });
+
+    it("String pool", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.initWithStringPool(encodeBytecode([
+        0, 2, ...encodeString("hello"), ...encodeString("world"),
+        OP.LOAD_STRING, 150, 0, 0,
+        OP.JUMP, ...encodeLongNum(13),
+        OP.LOAD_STRING, 151, 0, 1,
+        OP.LOAD_STRING, 152, 0, 0,
+        OP.ASSERT, 150, 0, 1,
+        OP.LOAD_SYMBOL, 153, 0, 1,
+      ]));
+
+      vm.run();
+      assert.equal(vm.getReg(150), "hello");
+      assert.equal(vm.getReg(151), undefined);
+      assert.equal(vm.getReg(152), "hello");
+      assert.equal(vm.getReg(153).description, "world");
+
+      vm = new VM();
+      vm.atob = require("atob");
+      vm.initWithStringPool(encodeBytecode([
+        0, 1, ...encodeString("hello"),
+        OP.LOAD_STRING, 150, 0, 1,
+      ]));
+      assert.throws(() => vm.run(), Error);
+    });
+
+    it("Call apply", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.CALL_APPLY, 160, 150, REGS.VOID, 151,
+        OP.CALL_APPLY, 161, 152, REGS.VOID, 153,
+        OP.CALL_APPLY, 162, 154, 155, REGS.VOID,
+        OP.CALL_APPLY, 163, 154, REGS.VOID, 151,
+      ]));
+      vm.setReg(150, Math.max);
+      vm.setReg(151, [1, 3, 2]);
+      vm.setReg(152, function() { return Array.from(arguments); });
+      vm.setReg(153, [1, , 3]);
+      vm.setReg(154, function() { return this; });
+      vm.setReg(155, {a: 1});
+
+      vm.run();
+      assert.equal(vm.getReg(160), 3);
+      // Holes are passed as undefined
+      assert.deepEqual(vm.getReg(161), [1, undefined, 3]);
+      assert.equal(vm.getReg(162), vm.getReg(155));
+      // Non-strict functions get the global this instead of undefined
+      assert.equal(vm.getReg(163), globalThis);
+    });
+
+    it("Console output", function() {
+      var vm = new VM();
+      vm.atob = require("atob");
+      vm.init(encodeBytecode([
+        OP.CONSOLE_LOG, 2, 150, 151,
+      ]));
+      vm.setReg(150, "a");
+      vm.setReg(151, 1);
+
+      var log = console.log, printed = [];
+      console.log = function() { printed.push(Array.from(arguments)); };
+      try {
+        vm.run();
+      } finally {
+        console.log = log;
+      }
+      assert.deepEqual(printed, [["a", 1]]);
+    });
   });
 });
@@ -16,6 +16,12 @@ const REGS = {
   NUM_0: 255,
 };
 
+// The names of the well-known symbols, indexed by the operand of LOAD_WELL_KNOWN_SYMBOL
+const WELL_KNOWN_SYMBOLS = [
+  "iterator", "toPrimitive", "asyncIterator", "hasInstance", "isConcatSpreadable", "match",
+  "matchAll", "replace", "search", "species", "split", "toStringTag", "unscopables"
+];
+
 const OP = {
   // Loaders
   LOAD_STRING: 1,
@@ -23,13 +29,18 @@ const OP = {
   LOAD_FLOAT: 3,
   LOAD_LONG_NUM: 4,
   LOAD_ARRAY: 5,
+  LOAD_NUM16: 6,
+  LOAD_CONST: 7,
+  LOAD_SYMBOL: 120,
+  LOAD_WELL_KNOWN_SYMBOL: 121,
 
   // Misc
   PROPACCESS: 10,
   FUNC_CALL: 11,
-  EVAL: 12,
+  EVAL_DIRECT: 12,
   CALL_BCFUNC: 13,
   RETURN_BCFUNC: 14,
+  TAIL_CALL: 165,
   COPY: 15,
   EXIT: 16,
   COND_JUMP: 17,
@@ -40,6 +51,8 @@ const OP = {
   TRY: 22,
   THROW: 23,
   NOP: 24,
+  HALT: 117,
+  EVAL_INDIRECT: 63,
 
   // Comparisons
   COMP_EQUAL: 50,
@@ -51,11 +64,122 @@ const OP = {
   COMP_LESS_THAN_EQUAL: 56,
   COMP_GREATHER_THAN_EQUAL: 57,
 
+  // Coercions
+  TO_NUMBER: 84,
+  TO_STRING: 85,
+  TO_BOOLEAN: 86,
+  TYPE_COERCE_TO_STRING: 111,
+  TYPE_COERCE_TO_NUMBER: 112,
+  PARSE_INT: 113,
+  PARSE_FLOAT: 114,
+  ARRAY_SORT: 119,
+  MEMORY_BARRIER: 118,
+
+  // Regular expressions
+  CREATE_REGEXP: 87,
+  REGEXP_TEST: 88,
+  REGEXP_EXEC: 89,
+
+  // Functions
+  CHECK_ARITY: 90,
+  DEFAULT_PARAM: 91,
+  COLLECT_REST_ARGS: 92,
+
+  // Arrays
+  GET_ARRAY_LENGTH: 93,
+  ARRAY_GET: 94,
+  ARRAY_SET: 95,
+
+  // Strings
+  GET_LENGTH: 123,
+  STRING_SLICE: 124,
+  STRING_INDEX_OF: 125,
+  STRING_CHAR_AT: 126,
+  STRING_CHAR_CODE_AT: 127,
+
+  // Debugging
+  ASSERT: 96,
+
+  // Scopes
+  ENTER_SCOPE: 97,
+  LEAVE_SCOPE: 98,
+  WITH_STATEMENT: 99,
+
   // Math
   ADD: 100,
   MUL: 101,
   MINUS: 102,
-  DIV: 103
+  DIV: 103,
+  ADD_IMMEDIATE: 110,
+  MATH_FLOOR: 130,
+  MATH_CEIL: 131,
+  MATH_ROUND: 132,
+  MATH_ABS: 133,
+  MATH_SQRT: 134,
+  MATH_POW: 135,
+  MATH_MIN: 136,
+  MATH_MAX: 137,
+  MATH_RANDOM: 138,
+  DATE_NOW: 139,
+  CONSOLE_LOG: 140,
+  JSON_STRINGIFY: 141,
+  JSON_PARSE: 142,
+  OBJECT_ASSIGN: 143,
+  OBJECT_KEYS: 144,
+  OBJECT_VALUES: 145,
+  OBJECT_ENTRIES: 146,
+  OBJECT_FREEZE: 147,
+  OBJECT_IS_FROZEN: 148,
+  ARRAY_FROM: 149,
+  ARRAY_IS_ARRAY: 150,
+  PROMISE_ALL: 151,
+  PROMISE_RACE: 152,
+  PROMISE_ANY: 153,
+  NEW_SET: 154,
+  NEW_MAP: 155,
+  SET_ADD: 156,
+  SET_HAS: 157,
+  SET_DELETE: 158,
+  MAP_GET: 159,
+  MAP_SET: 160,
+  MAP_HAS: 161,
+  MAP_GET_OR_DEFAULT: 162,
+  WEAK_MAP_GET: 163,
+  WEAK_MAP_SET: 164,
+  LOAD_GLOBAL_THIS: 166,
+  OBJECT_SPREAD: 167,
+  ARRAY_SLICE: 168,
+  ARRAY_CONCAT: 169,
+  ARRAY_MAP: 170,
+  ARRAY_FILTER: 171,
+  ARRAY_REDUCE: 172,
+  ARRAY_FIND: 173,
+  ARRAY_FIND_INDEX: 174,
+  ARRAY_EVERY: 175,
+  ARRAY_SOME: 176,
+  ARRAY_FOR_EACH: 177,
+  ARRAY_FLAT: 178,
+  ARRAY_FLAT_MAP: 179,
+  STRING_SPLIT: 180,
+  STRING_REPEAT: 181,
+  STRING_PAD_START: 182,
+  STRING_PAD_END: 183,
+  STRING_TRIM: 184,
+  STRING_TRIM_START: 185,
+  STRING_TRIM_END: 186,
+  STRING_INCLUDES: 187,
+  STRING_STARTS_WITH: 188,
+  STRING_ENDS_WITH: 189,
+  STRING_MATCH: 190,
+  STRING_MATCH_ALL: 191,
+  STRING_REPLACE: 192,
+  STRING_REPLACE_ALL: 193,
+  CALL_APPLY: 194,
+  HAS_BINDING: 195,
+  NEW_WEAK_MAP: 196,
+
+  // Strings
+  CONCAT: 109
 };
 
 class VM {
@@ -65,15 +189,27 @@ class VM {
     this.ops = [];
     this.reg_backups = [];
     this.modified_regs = [];
+    this.scopes = [];
+    this.constants = [];
+    // The strings of the string pool, if the bytecode has one
+    this.strings = null;
+    this.halted = false;
     try {
       this.atob = window.atob;
     } catch(e) {}
+    // The source of MATH_RANDOM, it must return numbers in the range [0, 1). The VM never seeds it,
+    // replace it by a seeded generator for reproducible runs.
+    this.random = Math.random;
+    // The clock of DATE_NOW in milliseconds. The VM never returns a timestamp smaller than the
+    // previous one, even if the clock is set back.
+    this.now = Date.now;
+    this.lastNow = -Infinity;
 
     this.ops[OP.NOP] = function(vm) {
         // no instruction
     };
     this.ops[OP.LOAD_STRING] = function(vm) {
-      var dst = vm.getByte(), str = vm._loadString();
+      var dst = vm.getByte(), str = vm._loadStringOperand();
       vm.setReg(dst, str);
     };
 
@@ -97,13 +233,525 @@ class VM {
       vm.setReg(dst, array);
     };
 
+    this.ops[OP.LOAD_NUM16] = function(vm) {
+      var dst = vm.getByte(), val = (vm.getByte() << 8) | vm.getByte();
+      vm.setReg(dst, val);
+    };
+
+    this.ops[OP.LOAD_CONST] = function(vm) {
+      var dst = vm.getByte(), index = (vm.getByte() << 8) | vm.getByte();
+      vm.setReg(dst, vm.constants[index]);
+    };
+
+    this.ops[OP.LOAD_SYMBOL] = function(vm) {
+      var dst = vm.getByte(), description = vm._loadStringOperand();
+      vm.setReg(dst, Symbol(description));
+    };
+
+    this.ops[OP.LOAD_WELL_KNOWN_SYMBOL] = function(vm) {
+      var dst = vm.getByte(), symbol = vm.getByte();
+      vm.setReg(dst, Symbol[WELL_KNOWN_SYMBOLS[symbol]]);
+    };
+
     this.ops[OP.PROPACCESS] = function(vm) {
-      var dst = vm.getByte(), obj = vm.getByte(), prop = vm.getByte();
-      obj = vm.getReg(obj); prop = vm.getReg(prop);
+      var dst = vm.getByte(), obj = vm.getByte(), prop = vm._loadPropertyKey();
+      obj = vm.getReg(obj);
 
       vm.setReg(dst, obj[prop]);
     };
 
+    this.ops[OP.GET_ARRAY_LENGTH] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte();
+
+      vm.setReg(dst, vm.getReg(arr).length);
+    };
+
+    this.ops[OP.GET_LENGTH] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte();
+
+      vm.setReg(dst, vm.getReg(str).length);
+    };
+
+    this.ops[OP.STRING_SLICE] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), start = vm.getByte(), end = vm.getByte(), kind = vm.getByte();
+      str = vm.getReg(str); start = vm.getReg(start); end = vm.getReg(end);
+
+      // 0: slice, 1: substring
+      vm.setReg(dst, kind === 0 ? str.slice(start, end) : str.substring(start, end));
+    };
+
+    this.ops[OP.STRING_INDEX_OF] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), needle = vm.getByte(), start = vm.getByte();
+      str = vm.getReg(str); needle = vm.getReg(needle); start = vm.getReg(start);
+
+      vm.setReg(dst, str.indexOf(needle, start));
+    };
+
+    this.ops[OP.STRING_SPLIT] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), separator = vm.getByte(), limit = vm.getByte();
+      str = vm.getReg(str); separator = vm.getReg(separator);
+
+      // The register holding 0 stands for no limit
+      limit = limit === REGS.NUM_0 ? undefined : vm.getReg(limit);
+      vm.setReg(dst, str.split(separator, limit));
+    };
+
+    this.ops[OP.STRING_REPEAT] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), count = vm.getByte();
+
+      vm.setReg(dst, vm.getReg(str).repeat(vm.getReg(count)));
+    };
+
+    this.ops[OP.STRING_PAD_START] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), length = vm.getByte(), fill = vm.getByte();
+
+      // The register holding 0 stands for a space as fill
+      fill = fill === REGS.NUM_0 ? " " : vm.getReg(fill);
+      vm.setReg(dst, vm.getReg(str).padStart(vm.getReg(length), fill));
+    };
+
+    this.ops[OP.STRING_PAD_END] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), length = vm.getByte(), fill = vm.getByte();
+
+      // The register holding 0 stands for a space as fill
+      fill = fill === REGS.NUM_0 ? " " : vm.getReg(fill);
+      vm.setReg(dst, vm.getReg(str).padEnd(vm.getReg(length), fill));
+    };
+
+    this.ops[OP.STRING_TRIM] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte();
+      vm.setReg(dst, vm.getReg(str).trim());
+    };
+
+    this.ops[OP.STRING_TRIM_START] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte();
+      vm.setReg(dst, vm.getReg(str).trimStart());
+    };
+
+    this.ops[OP.STRING_TRIM_END] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte();
+      vm.setReg(dst, vm.getReg(str).trimEnd());
+    };
+
+    this.ops[OP.STRING_INCLUDES] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), search = vm.getByte(), position = vm.getByte();
+
+      // The register holding 0 stands for the default position
+      position = position === REGS.NUM_0 ? undefined : vm.getReg(position);
+      vm.setReg(dst, vm.getReg(str).includes(vm.getReg(search), position));
+    };
+
+    this.ops[OP.STRING_STARTS_WITH] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), search = vm.getByte(), position = vm.getByte();
+
+      // The register holding 0 stands for the default position
+      position = position === REGS.NUM_0 ? undefined : vm.getReg(position);
+      vm.setReg(dst, vm.getReg(str).startsWith(vm.getReg(search), position));
+    };
+
+    this.ops[OP.STRING_ENDS_WITH] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), search = vm.getByte(), position = vm.getByte();
+
+      // The register holding 0 stands for the default position
+      position = position === REGS.NUM_0 ? undefined : vm.getReg(position);
+      vm.setReg(dst, vm.getReg(str).endsWith(vm.getReg(search), position));
+    };
+
+    this.ops[OP.STRING_MATCH] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), regexp = vm.getByte();
+      vm.setReg(dst, vm.getReg(str).match(vm.getReg(regexp)));
+    };
+
+    this.ops[OP.STRING_MATCH_ALL] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), regexp = vm.getByte();
+      vm.setReg(dst, vm.getReg(str).matchAll(vm.getReg(regexp)));
+    };
+
+    this.ops[OP.STRING_REPLACE] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), pattern = vm.getByte(), replacement = vm.getByte();
+      vm.setReg(dst, vm.getReg(str).replace(vm.getReg(pattern), vm.getReg(replacement)));
+    };
+
+    this.ops[OP.STRING_REPLACE_ALL] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), pattern = vm.getByte(), replacement = vm.getByte();
+      vm.setReg(dst, vm.getReg(str).replaceAll(vm.getReg(pattern), vm.getReg(replacement)));
+    };
+
+    this.ops[OP.CALL_APPLY] = function(vm) {
+      var dst = vm.getByte(), func = vm.getByte(), thisArg = vm.getByte(), args = vm.getByte();
+      vm.setReg(dst, vm.getReg(func).apply(vm.getReg(thisArg), vm.getReg(args)));
+    };
+
+    this.ops[OP.STRING_CHAR_AT] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), index = vm.getByte();
+
+      vm.setReg(dst, vm.getReg(str).charAt(vm.getReg(index)));
+    };
+
+    this.ops[OP.STRING_CHAR_CODE_AT] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), index = vm.getByte();
+
+      vm.setReg(dst, vm.getReg(str).charCodeAt(vm.getReg(index)));
+    };
+
+    this.ops[OP.MATH_FLOOR] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, Math.floor(vm.getReg(src)));
+    };
+
+    this.ops[OP.MATH_CEIL] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, Math.ceil(vm.getReg(src)));
+    };
+
+    this.ops[OP.MATH_ROUND] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, Math.round(vm.getReg(src)));
+    };
+
+    this.ops[OP.MATH_ABS] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, Math.abs(vm.getReg(src)));
+    };
+
+    this.ops[OP.MATH_SQRT] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, Math.sqrt(vm.getReg(src)));
+    };
+
+    this.ops[OP.MATH_POW] = function(vm) {
+      var dst = vm.getByte(), base = vm.getByte(), exponent = vm.getByte();
+      vm.setReg(dst, Math.pow(vm.getReg(base), vm.getReg(exponent)));
+    };
+
+    this.ops[OP.MATH_MIN] = function(vm) {
+      var dst = vm.getByte(), args = vm._loadArrayFromRegister();
+      // Without arguments this is Infinity
+      vm.setReg(dst, Math.min.apply(Math, args));
+    };
+
+    this.ops[OP.MATH_MAX] = function(vm) {
+      var dst = vm.getByte(), args = vm._loadArrayFromRegister();
+      // Without arguments this is -Infinity
+      vm.setReg(dst, Math.max.apply(Math, args));
+    };
+
+    this.ops[OP.MATH_RANDOM] = function(vm) {
+      var dst = vm.getByte();
+      vm.setReg(dst, vm.random());
+    };
+
+    this.ops[OP.DATE_NOW] = function(vm) {
+      var dst = vm.getByte();
+      vm.lastNow = Math.max(vm.lastNow, vm.now());
+      vm.setReg(dst, vm.lastNow);
+    };
+
+    this.ops[OP.CONSOLE_LOG] = function(vm) {
+      var args = vm._loadArrayFromRegister();
+      console.log.apply(console, args);
+    };
+
+    this.ops[OP.JSON_STRINGIFY] = function(vm) {
+      var dst = vm.getByte(), value = vm.getByte(), replacer = vm.getByte(), indent = vm.getByte();
+      // Without replacer or indent, these are 0, which JSON.stringify ignores
+      vm.setReg(dst, JSON.stringify(vm.getReg(value), vm.getReg(replacer), vm.getReg(indent)));
+    };
+
+    this.ops[OP.JSON_PARSE] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte(), reviver = vm.getByte();
+      // Without reviver, this is 0, which JSON.parse ignores
+      vm.setReg(dst, JSON.parse(vm.getReg(str), vm.getReg(reviver)));
+    };
+
+    this.ops[OP.OBJECT_ASSIGN] = function(vm) {
+      var dst = vm.getByte(), target = vm.getByte(), sources = vm._loadArrayFromRegister();
+      // Without sources, this returns the target unchanged
+      vm.setReg(dst, Object.assign.apply(Object, [vm.getReg(target)].concat(sources)));
+    };
+
+    this.ops[OP.OBJECT_KEYS] = function(vm) {
+      var dst = vm.getByte(), obj = vm.getByte();
+      vm.setReg(dst, Object.keys(vm.getReg(obj)));
+    };
+
+    this.ops[OP.OBJECT_VALUES] = function(vm) {
+      var dst = vm.getByte(), obj = vm.getByte();
+      vm.setReg(dst, Object.values(vm.getReg(obj)));
+    };
+
+    this.ops[OP.OBJECT_ENTRIES] = function(vm) {
+      var dst = vm.getByte(), obj = vm.getByte();
+      vm.setReg(dst, Object.entries(vm.getReg(obj)));
+    };
+
+    this.ops[OP.OBJECT_FREEZE] = function(vm) {
+      var dst = vm.getByte(), obj = vm.getByte();
+      // The VM is strict mode code, so PROPSET on the frozen object throws a TypeError
+      vm.setReg(dst, Object.freeze(vm.getReg(obj)));
+    };
+
+    this.ops[OP.OBJECT_IS_FROZEN] = function(vm) {
+      var dst = vm.getByte(), obj = vm.getByte();
+      vm.setReg(dst, Object.isFrozen(vm.getReg(obj)));
+    };
+
+    this.ops[OP.OBJECT_SPREAD] = function(vm) {
+      var dst = vm.getByte(), target = vm.getByte(), source = vm.getByte();
+      var obj = target === REGS.NUM_0 ? {} : vm.getReg(target);
+      source = vm.getReg(source);
+
+      // Like spread elements, this defines the properties instead of assigning them and
+      // Object.keys skips non-enumerable and symbol-keyed properties
+      if(source !== null && source !== void 0) {
+        for(let key of Object.keys(Object(source))) {
+          Object.defineProperty(obj, key, {value: source[key], writable: true, enumerable: true, configurable: true});
+        }
+      }
+
+      vm.setReg(dst, obj);
+    };
+
+    this.ops[OP.ARRAY_FROM] = function(vm) {
+      var dst = vm.getByte(), iterable = vm.getByte(), mapFn = vm.getByte();
+      iterable = vm.getReg(iterable);
+
+      // Without map function, this is the register holding 0, which is no function
+      if(mapFn === REGS.NUM_0) {
+        vm.setReg(dst, Array.from(iterable));
+      } else {
+        vm.setReg(dst, Array.from(iterable, vm.getReg(mapFn)));
+      }
+    };
+
+    this.ops[OP.ARRAY_IS_ARRAY] = function(vm) {
+      var dst = vm.getByte(), val = vm.getByte();
+      vm.setReg(dst, Array.isArray(vm.getReg(val)));
+    };
+
+    this.ops[OP.PROMISE_ALL] = function(vm) {
+      var dst = vm.getByte(), promises = vm.getByte();
+      vm.setReg(dst, Promise.all(vm.getReg(promises)));
+    };
+
+    this.ops[OP.PROMISE_RACE] = function(vm) {
+      var dst = vm.getByte(), promises = vm.getByte();
+      vm.setReg(dst, Promise.race(vm.getReg(promises)));
+    };
+
+    this.ops[OP.PROMISE_ANY] = function(vm) {
+      var dst = vm.getByte(), promises = vm.getByte();
+      // Promise.any is ES2021, older engines throw a TypeError here
+      vm.setReg(dst, Promise.any(vm.getReg(promises)));
+    };
+
+    this.ops[OP.NEW_SET] = function(vm) {
+      var dst = vm.getByte(), values = vm.getByte();
+      // Without initial values, this is the register holding 0, which is not iterable
+      vm.setReg(dst, values === REGS.NUM_0 ? new Set() : new Set(vm.getReg(values)));
+    };
+
+    this.ops[OP.NEW_MAP] = function(vm) {
+      var dst = vm.getByte(), entries = vm.getByte();
+      vm.setReg(dst, entries === REGS.NUM_0 ? new Map() : new Map(vm.getReg(entries)));
+    };
+
+    this.ops[OP.NEW_WEAK_MAP] = function(vm) {
+      var dst = vm.getByte(), entries = vm.getByte();
+      vm.setReg(dst, entries === REGS.NUM_0 ? new WeakMap() : new WeakMap(vm.getReg(entries)));
+    };
+
+    this.ops[OP.SET_ADD] = function(vm) {
+      var dst = vm.getByte(), set = vm.getByte(), value = vm.getByte();
+      vm.setReg(dst, vm.getReg(set).add(vm.getReg(value)));
+    };
+
+    this.ops[OP.SET_HAS] = function(vm) {
+      var dst = vm.getByte(), set = vm.getByte(), value = vm.getByte();
+      vm.setReg(dst, vm.getReg(set).has(vm.getReg(value)));
+    };
+
+    this.ops[OP.SET_DELETE] = function(vm) {
+      var dst = vm.getByte(), set = vm.getByte(), value = vm.getByte();
+      vm.setReg(dst, vm.getReg(set).delete(vm.getReg(value)));
+    };
+
+    this.ops[OP.MAP_GET] = function(vm) {
+      var dst = vm.getByte(), map = vm.getByte(), key = vm.getByte();
+      vm.setReg(dst, vm.getReg(map).get(vm.getReg(key)));
+    };
+
+    this.ops[OP.MAP_SET] = function(vm) {
+      var dst = vm.getByte(), map = vm.getByte(), key = vm.getByte(), value = vm.getByte();
+      vm.setReg(dst, vm.getReg(map).set(vm.getReg(key), vm.getReg(value)));
+    };
+
+    this.ops[OP.MAP_HAS] = function(vm) {
+      var dst = vm.getByte(), map = vm.getByte(), key = vm.getByte();
+      vm.setReg(dst, vm.getReg(map).has(vm.getReg(key)));
+    };
+
+    this.ops[OP.MAP_GET_OR_DEFAULT] = function(vm) {
+      var dst = vm.getByte(), map = vm.getByte(), key = vm.getByte(), defaultValue = vm.getByte();
+      var value = vm.getReg(map).get(vm.getReg(key));
+      vm.setReg(dst, (value === undefined || value === null) ? vm.getReg(defaultValue) : value);
+    };
+
+    this.ops[OP.WEAK_MAP_GET] = function(vm) {
+      var dst = vm.getByte(), weakMap = vm.getByte(), key = vm.getByte();
+      vm.setReg(dst, vm.getReg(weakMap).get(vm._checkWeakMapKey(vm.getReg(key))));
+    };
+
+    this.ops[OP.WEAK_MAP_SET] = function(vm) {
+      var dst = vm.getByte(), weakMap = vm.getByte(), key = vm.getByte(), value = vm.getByte();
+      vm.setReg(dst, vm.getReg(weakMap).set(vm._checkWeakMapKey(vm.getReg(key)), vm.getReg(value)));
+    };
+
+    this.ops[OP.LOAD_GLOBAL_THIS] = function(vm) {
+      var dst = vm.getByte();
+      vm.setReg(dst, globalThis);
+    };
+
+    this.ops[OP.ARRAY_SLICE] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), start = vm.getByte(), end = vm.getByte();
+      arr = vm.getReg(arr);
+
+      // The register holding 0 stands for the end of the array
+      end = end === REGS.NUM_0 ? arr.length : vm.getReg(end);
+      vm.setReg(dst, Array.prototype.slice.call(arr, vm.getReg(start), end));
+    };
+
+    this.ops[OP.ARRAY_FLAT] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), depth = vm.getByte();
+
+      // The register holding 0 stands for an infinite depth
+      depth = depth === REGS.NUM_0 ? Infinity : vm.getReg(depth);
+      vm.setReg(dst, Array.prototype.flat.call(vm.getReg(arr), depth));
+    };
+
+    this.ops[OP.ARRAY_CONCAT] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), others = vm._loadArrayFromRegister();
+      vm.setReg(dst, Array.prototype.concat.apply(vm.getReg(arr), others));
+    };
+
+    this.ops[OP.ARRAY_GET] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), idx = vm.getByte();
+
+      // Out-of-bounds reads result in undefined
+      vm.setReg(dst, vm.getReg(arr)[vm.getReg(idx)]);
+    };
+
+    this.ops[OP.ARRAY_SET] = function(vm) {
+      var arr = vm.getByte(), idx = vm.getByte(), val = vm.getByte();
+
+      vm.getReg(arr)[vm.getReg(idx)] = vm.getReg(val);
+    };
+
+    this.ops[OP.ARRAY_SORT] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray();
+      arr = vm.getReg(arr);
+
+      Array.prototype.sort.call(arr, function(a, b) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [a, b]);
+      });
+      vm.setReg(dst, arr);
+    }
+
+    this.ops[OP.ARRAY_MAP] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray();
+
+      vm.setReg(dst, Array.prototype.map.call(vm.getReg(arr), function(element, index, array) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [element, index, array]);
+      }));
+    }
+
+    this.ops[OP.ARRAY_FILTER] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray();
+
+      vm.setReg(dst, Array.prototype.filter.call(vm.getReg(arr), function(element, index, array) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [element, index, array]);
+      }));
+    }
+
+    this.ops[OP.ARRAY_REDUCE] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray(), initial = vm.getByte();
+
+      var args = [function(acc, element, index, array) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [acc, element, index, array]);
+      }];
+      // The register holding 0 stands for a missing initial value
+      if(initial !== REGS.NUM_0) {
+        args.push(vm.getReg(initial));
+      }
+      vm.setReg(dst, Array.prototype.reduce.apply(vm.getReg(arr), args));
+    }
+
+    this.ops[OP.ARRAY_FIND] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray();
+
+      vm.setReg(dst, Array.prototype.find.call(vm.getReg(arr), function(element, index, array) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [element, index, array]);
+      }));
+    }
+
+    this.ops[OP.ARRAY_FIND_INDEX] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray();
+
+      vm.setReg(dst, Array.prototype.findIndex.call(vm.getReg(arr), function(element, index, array) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [element, index, array]);
+      }));
+    }
+
+    this.ops[OP.ARRAY_EVERY] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray();
+
+      vm.setReg(dst, Array.prototype.every.call(vm.getReg(arr), function(element, index, array) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [element, index, array]);
+      }));
+    }
+
+    this.ops[OP.ARRAY_SOME] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray();
+
+      vm.setReg(dst, Array.prototype.some.call(vm.getReg(arr), function(element, index, array) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [element, index, array]);
+      }));
+    }
+
+    this.ops[OP.ARRAY_FOR_EACH] = function(vm) {
+      var arr = vm.getByte(), funcOffset = vm._loadLongNum(), argRegs = vm._loadRegistersArray();
+
+      // The length is fixed before the first call, like for Array.prototype.forEach
+      Array.prototype.forEach.call(vm.getReg(arr), function(element, index, array) {
+        vm.callBytecodeFunc(funcOffset, argRegs, [element, index, array]);
+      });
+    }
+
+    this.ops[OP.ARRAY_FLAT_MAP] = function(vm) {
+      var dst = vm.getByte(), arr = vm.getByte(), funcOffset = vm._loadLongNum(),
+          argRegs = vm._loadRegistersArray();
+
+      vm.setReg(dst, Array.prototype.flatMap.call(vm.getReg(arr), function(element, index, array) {
+        return vm.callBytecodeFunc(funcOffset, argRegs, [element, index, array]);
+      }));
+    }
+
+    this.ops[OP.ASSERT] = function(vm) {
+      var cond = vm.getByte(), msg = vm._loadStringOperand();
+
+      if(!vm.getReg(cond)) {
+        throw new Error("Assertion failed: " + msg);
+      }
+    };
+
     this.ops[OP.PROPSET] = function(vm) {
       var dstObj = vm.getByte(), dstProp = vm.getByte(), val = vm.getByte();
       dstObj = vm.getReg(dstObj);
@@ -117,10 +765,12 @@ class VM {
       var catchBlockExceptReg = vm.getByte();
       var catchBlockOffset = vm._loadLongNum();
       var finallyBlockOffset = vm._loadLongNum();
+      var scopeDepth = vm.scopes.length;
 
       try {
         vm.run();
       } catch(e) {
+        vm.scopes.length = scopeDepth;
         vm.setReg(catchBlockExceptReg, e);
         vm.runAt(catchBlockOffset);
       } finally {
@@ -142,21 +792,83 @@ class VM {
       vm.setReg(dst, func.apply(funcThis, args));
     }
 
-    this.ops[OP.EVAL] = function(vm) {
+    this.ops[OP.EVAL_DIRECT] = function(vm) {
       var dst = vm.getByte(), str = vm.getByte();
       str = vm.getReg(str);
 
-      vm.setReg(dst, eval(str));
+      // Free identifiers are resolved through the entered scopes, innermost first
+      var withScopes = vm.scopes.map(function(_, i) { return "with(s[" + i + "])"; }).join(" ");
+      vm.setReg(dst, new Function("s", "str", withScopes + " { return eval(str); }")(vm.scopes, str));
+    }
+
+    this.ops[OP.EVAL_INDIRECT] = function(vm) {
+      var dst = vm.getByte(), str = vm.getByte();
+      str = vm.getReg(str);
+
+      // Evaluates in the global scope, neither the VM nor its scopes are visible
+      vm.setReg(dst, (0, eval)(str));
+    }
+
+    this.ops[OP.ENTER_SCOPE] = function(vm) {
+      vm.scopes.push({});
+    }
+
+    this.ops[OP.LEAVE_SCOPE] = function(vm) {
+      vm.scopes.pop();
+    }
+
+    this.ops[OP.WITH_STATEMENT] = function(vm) {
+      var obj = vm.getByte();
+      vm.scopes.push(vm.getReg(obj));
+    }
+
+    this.ops[OP.HAS_BINDING] = function(vm) {
+      var dst = vm.getByte(), obj = vm.getByte(), name = vm.getByte();
+      obj = Object(vm.getReg(obj));
+      name = vm.getReg(name);
+
+      var unscopables = obj[Symbol.unscopables];
+      vm.setReg(dst, name in obj && !(unscopables && unscopables[name]));
+    }
+
+    this.ops[OP.CHECK_ARITY] = function(vm) {
+      var expected = vm.getByte(), actual = vm.getByte();
+      actual = vm.getReg(actual);
+
+      if(expected !== actual) {
+        throw new TypeError("Expected " + expected + " arguments, but got " + actual);
+      }
+    }
+
+    this.ops[OP.DEFAULT_PARAM] = function(vm) {
+      var param = vm.getByte(), defaultVal = vm.getByte();
+
+      if(vm.getReg(param) === void 0) {
+        vm.setReg(param, vm.getReg(defaultVal));
+      }
+    }
+
+    this.ops[OP.COLLECT_REST_ARGS] = function(vm) {
+      var dst = vm.getByte(), startIndex = vm.getByte();
+      var args = vm.reg_backups[vm.reg_backups.length-1][2];
+
+      vm.setReg(dst, args.slice(startIndex));
     }
 
     this.ops[OP.CALL_BCFUNC] = function(vm) {
       var funcOffset = vm._loadLongNum();
       var returnReg = vm.getByte();
       var argsArray = vm._loadRegistersArray();
-      vm.reg_backups.push([vm.regs.slice(), returnReg]);
+      var args = [];
 
       for(let i = 0; i < argsArray.length; i+=2) {
-        vm.setReg(argsArray[i], vm.getReg(argsArray[i+1]));
+        args.push(vm.getReg(argsArray[i+1]));
+      }
+
+      vm.reg_backups.push([vm.regs.slice(), returnReg, args]);
+
+      for(let i = 0; i < argsArray.length; i+=2) {
+        vm.setReg(argsArray[i], args[i/2]);
       }
 
       vm.setReg(REGS.BYTECODE_PTR, funcOffset);
@@ -184,6 +896,27 @@ class VM {
       vm.regs = regBackups;
     }
 
+    // Calls in tail position reuse the frame of the calling function, the callee returns to its caller
+    this.ops[OP.TAIL_CALL] = function(vm) {
+      var funcOffset = vm._loadLongNum();
+      var argsArray = vm._loadRegistersArray();
+      var exceptedRegs = vm._loadRegistersArray();
+      var args = [];
+
+      for(let i = 0; i < argsArray.length; i+=2) {
+        args.push(vm.getReg(argsArray[i+1]));
+      }
+
+      vm.modified_regs = [...new Set([...vm.modified_regs, ...exceptedRegs])];
+      vm.reg_backups[vm.reg_backups.length-1][2] = args;
+
+      for(let i = 0; i < argsArray.length; i+=2) {
+        vm.setReg(argsArray[i], args[i/2]);
+      }
+
+      vm.setReg(REGS.BYTECODE_PTR, funcOffset);
+    }
+
     this.ops[OP.COPY] = function(vm) {
       var dst = vm.getByte(), src = vm.getByte();
       vm.setReg(dst, vm.getReg(src));
@@ -193,6 +926,11 @@ class VM {
       vm.setReg(REGS.BYTECODE_PTR, vm.bytecode.length);
     }
 
+    // Suspends the VM, run() returns HALTED and the next run() resumes after this
+    this.ops[OP.HALT] = function(vm) {
+      vm.halted = true;
+    }
+
     this.ops[OP.COND_JUMP] = function(vm) {
       var cond = vm.getByte();
       var offset = vm._loadLongNum();
@@ -220,11 +958,10 @@ class VM {
 
     this.ops[OP.BCFUNC_CALLBACK] = function(vm) {
       var dst = vm.getByte(), func_offset = vm._loadLongNum(), arg_regs = vm._loadRegistersArray();
+      // The function may be called while the VM runs, e.g. by a native method, thus it must not continue
+      // the execution of the caller after returning
       vm.setReg(dst, function() {
-        for(let i = 0; i<arg_regs.length; ++i) {
-          vm.setReg(arg_regs[i], arguments[i]);
-        }
-        vm.runFuncAt(func_offset)
+        return vm.callBytecodeFunc(func_offset, arg_regs, Array.prototype.slice.call(arguments));
       });
     }
 
@@ -292,6 +1029,72 @@ class VM {
       vm.setReg(dst, left >= right);
     }
 
+    this.ops[OP.TO_NUMBER] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, Number(vm.getReg(src)));
+    }
+
+    this.ops[OP.TO_STRING] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, String(vm.getReg(src)));
+    }
+
+    this.ops[OP.TYPE_COERCE_TO_STRING] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, String(vm.getReg(src)));
+    }
+
+    this.ops[OP.TYPE_COERCE_TO_NUMBER] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, Number(vm.getReg(src)));
+    }
+
+    this.ops[OP.PARSE_INT] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte(), radix = vm.getByte();
+      vm.setReg(dst, parseInt(vm.getReg(src), radix));
+    }
+
+    this.ops[OP.PARSE_FLOAT] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      vm.setReg(dst, parseFloat(vm.getReg(src)));
+    }
+
+    // Operations are executed in order and shared memory is only accessed
+    // through Atomics, which is sequentially consistent. Thus, every barrier
+    // is already fulfilled.
+    this.ops[OP.MEMORY_BARRIER] = function(vm) {
+      // Skips the ordering operand
+      vm.getByte();
+    }
+
+    this.ops[OP.TO_BOOLEAN] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte();
+      src = vm.getReg(src);
+
+      vm.setReg(dst, (typeof src === "boolean") ? src : !!src);
+    }
+
+    this.ops[OP.CREATE_REGEXP] = function(vm) {
+      var dst = vm.getByte(), pattern = vm._loadString(), flags = vm._loadRegExpFlags();
+      vm.setReg(dst, new RegExp(pattern, flags));
+    }
+
+    this.ops[OP.REGEXP_TEST] = function(vm) {
+      var dst = vm.getByte(), regexp = vm.getByte(), str = vm.getByte();
+      regexp = vm.getReg(regexp);
+      str = vm.getReg(str);
+
+      vm.setReg(dst, regexp.test(str));
+    }
+
+    this.ops[OP.REGEXP_EXEC] = function(vm) {
+      var dst = vm.getByte(), regexp = vm.getByte(), str = vm.getByte();
+      regexp = vm.getReg(regexp);
+      str = vm.getReg(str);
+
+      vm.setReg(dst, regexp.exec(str));
+    }
+
     this.ops[OP.ADD] = function(vm) {
       var dst = vm.getByte(), src0 = vm.getByte(), src1 = vm.getByte();
       vm.setReg(dst, vm.getReg(src0) + vm.getReg(src1));
@@ -312,6 +1115,16 @@ class VM {
       vm.setReg(dst, vm.regs[src0] / vm.regs[src1]);
     }
 
+    this.ops[OP.ADD_IMMEDIATE] = function(vm) {
+      var dst = vm.getByte(), src = vm.getByte(), imm = vm.getByte();
+      vm.setReg(dst, vm.getReg(src) + imm);
+    }
+
+    this.ops[OP.CONCAT] = function(vm) {
+      var dst = vm.getByte(), src0 = vm.getByte(), src1 = vm.getByte();
+      vm.setReg(dst, String(vm.getReg(src0)) + String(vm.getReg(src1)));
+    }
+
   }
 
   setReg(reg, value) {
@@ -327,7 +1140,9 @@ class VM {
   }
 
   run() {
-    while(this.regs[REGS.BYTECODE_PTR] < this.bytecode.length) {
+    this.halted = false;
+
+    while(!this.halted && this.regs[REGS.BYTECODE_PTR] < this.bytecode.length) {
       var op_code = this.getByte();
       var op = this.ops[op_code];
 
@@ -338,11 +1153,28 @@ class VM {
         throw e;
       }
     }
-    return 0;
+    return this.halted ? VM.HALTED : 0;
   }
 
-  runFuncAt(offset) {
-    this.reg_backups.push([this.regs.slice(), REGS.BCFUNC_RETURN]);
+  // Runs a bytecode function until it returns and returns its return value
+  callBytecodeFunc(offset, argRegs, args) {
+    var depth = this.reg_backups.length;
+    this.reg_backups.push([this.regs.slice(), REGS.BCFUNC_RETURN, args]);
+
+    for(let i = 0; i < argRegs.length; ++i) {
+      this.setReg(argRegs[i], args[i]);
+    }
+    this.setReg(REGS.BYTECODE_PTR, offset);
+
+    while(this.reg_backups.length > depth) {
+      this.ops[this.getByte()](this);
+    }
+
+    return this.getReg(REGS.BCFUNC_RETURN);
+  }
+
+  runFuncAt(offset, args) {
+    this.reg_backups.push([this.regs.slice(), REGS.BCFUNC_RETURN, args || []]);
     this.runAt(offset);
   }
 
@@ -351,7 +1183,10 @@ class VM {
     this.run();
   }
 
-  init(bytecode) {
+  init(bytecode, constants) {
+    if(constants !== undefined) {
+      this._loadConstantTable(constants);
+    }
     this.bytecode = this._decodeBytecode(bytecode);
     this.setReg(REGS.BYTECODE_PTR, 0);
 
@@ -362,6 +1197,24 @@ class VM {
     this.setReg(FutureDeclerationsPlaceHolder, 0);
   }
 
+  // Like init, but the bytecode starts with a string pool. The pool holds the
+  // number of strings (2 bytes), followed by the strings. The string operands
+  // of the bytecode are 2 byte indices into the pool.
+  initWithStringPool(bytecode, constants) {
+    this.init(bytecode, constants);
+
+    var count = (this.getByte() << 8) | this.getByte();
+    var strings = [];
+    for(let i = 0; i < count; ++i) {
+      strings.push(this._loadString());
+    }
+
+    // The addresses in the bytecode do not include the pool
+    this.bytecode = this.bytecode.slice(this.getReg(REGS.BYTECODE_PTR));
+    this.setReg(REGS.BYTECODE_PTR, 0);
+    this.strings = strings;
+  }
+
   _decodeBytecode(encodedBytecode) {
     var bytecode = atob(encodedBytecode);
     var bytes = [];
@@ -378,12 +1231,41 @@ class VM {
     return bytes;
   }
 
+  // The constant table holds the number of constants (2 bytes), followed by
+  // the constants. Each constant is encoded as the opcode of the load
+  // instruction for it, followed by its operand.
+  _loadConstantTable(encodedTable) {
+    this.bytecode = this._decodeBytecode(encodedTable);
+    this.setReg(REGS.BYTECODE_PTR, 0);
+
+    var count = (this.getByte() << 8) | this.getByte();
+    this.constants = [];
+    for(let i = 0; i < count; ++i) {
+      switch(this.getByte()) {
+        case OP.LOAD_STRING: this.constants.push(this._loadString()); break;
+        case OP.LOAD_NUM: this.constants.push(this.getByte()); break;
+        case OP.LOAD_FLOAT: this.constants.push(this._loadFloat()); break;
+        case OP.LOAD_LONG_NUM: this.constants.push(this._loadLongNum()); break;
+        case OP.LOAD_NUM16: this.constants.push((this.getByte() << 8) | this.getByte()); break;
+        default: throw new Error("Invalid constant table");
+      }
+    }
+  }
+
+  // A property key is a register (tag 0) or the index of a well-known symbol (tag 1)
+  _loadPropertyKey() {
+    if(this.getByte() === 0) {
+      return this.getReg(this.getByte());
+    }
+    return Symbol[WELL_KNOWN_SYMBOLS[this._loadLongNum()]];
+  }
+
   _loadString() {
     // With a 1 byte string length it would only be possible to load
     // string up to a length of 256. However, this might be to short
     // load functions or so. 2 bytes and thus a maximal length of 65536
     // should be sufficient.
-    var stringLength = (this.getByte() << 8) || this.getByte();
+    var stringLength = (this.getByte() << 8) | this.getByte();
     var string = "";
 
     for(var i = 0;i<stringLength;i++) {
@@ -393,6 +1275,41 @@ class VM {
     return string;
   }
 
+  _loadStringOperand() {
+    if(this.strings === null) {
+      return this._loadString();
+    }
+
+    var index = (this.getByte() << 8) | this.getByte();
+    if(index >= this.strings.length) {
+      throw new Error("Invalid string reference " + index);
+    }
+    return this.strings[index];
+  }
+
+  _loadRegExpFlags() {
+    // The flags are encoded as bit field, see Operand::encode_regex_flags
+    var encodedFlags = this.getByte();
+    var knownFlags = "gimsuy";
+    var flags = "";
+
+    for(var i = 0;i<knownFlags.length;i++) {
+      if(encodedFlags & (1 << i)) {
+        flags += knownFlags.charAt(i);
+      }
+    }
+
+    return flags;
+  }
+
+  _checkWeakMapKey(key) {
+    // Unlike WeakMap.prototype.get, reading with a primitive key throws as well
+    if(key === null || (typeof key !== "object" && typeof key !== "function")) {
+      throw new TypeError("Invalid value used as weak map key");
+    }
+    return key;
+  }
+
   _loadArrayFromRegister() {
     var arrayLength = this.getByte();
     var array = [];
@@ -415,10 +1332,13 @@ class VM {
     var significandBase = binary.substr(12);
 
     var significandBin;
-    if (exponent == 0) {
+    if (exponent == 0x7ff) {
+      // NaN if any significand bit is set, otherwise (-)Infinity
+      return (significandBase.indexOf('1') == -1) ? sign * Infinity : NaN;
+    } else if (exponent == 0) {
         if (significandBase.indexOf('1') == -1) {
           // exponent and significand are zero
-            return 0;
+            return sign * 0;
         } else {
             exponent = -0x3fe;
             significandBin = '0' + significandBase;
@@ -455,6 +1375,9 @@ class VM {
 }
 
 
+// Returned by run() if the VM was suspended by a HALT
+VM.HALTED = 1;
+
 module.exports = function() {
     this.REGS = REGS;
     this.OP = OP;